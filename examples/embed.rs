@@ -0,0 +1,92 @@
+// embeds Absurd into the application, errors of the sources are returned to it
+use absurd::{Engine, IntoAbsurd, LiteralType, Module};
+use std::collections::HashMap;
+
+fn main() {
+    let mut engine = Engine::new();
+
+    engine.set("base", 40);
+    engine.register_fn("twice", 1, |args| match &args[0] {
+        LiteralType::Int(n) => Ok(LiteralType::Int(n * 2)),
+        arg => Err(format!("twice() expects an int, got {}", arg.type_name())),
+    });
+    engine.register("repeat", repeat);
+    engine.register("scores", || HashMap::from([("ann".to_string(), 25_i64)]));
+    engine.register_module(
+        "greetings",
+        Module::new()
+            .func("greet", 1, |args| {
+                Ok(format!("hello, {}", args[0]).into_absurd())
+            })
+            .native("shout", |text: String| text.to_uppercase()),
+    );
+
+    let value = engine.eval("let answer = base + 2;\nanswer * 10").unwrap();
+    println!("eval: {}", value);
+    let answer: i64 = engine.get("answer").unwrap();
+    println!("get: {}", answer);
+
+    engine
+        .eval("func add(a: int, b: int) -> int {\n    return a + b;\n}")
+        .unwrap();
+    let sum: i64 = engine
+        .call("add", vec![2.into_absurd(), 40.into_absurd()])
+        .unwrap();
+    println!("call: {}", sum);
+    engine.eval(TASKS).unwrap();
+    let later: i64 = engine.call("later", vec![5.into_absurd()]).unwrap();
+    println!("async: {}", later);
+    let greeting = engine
+        .eval("use greet, shout from \"greetings\";\nshout(greet(\"world\"))")
+        .unwrap();
+    println!("module: {}", greeting);
+
+    engine.eval("let words = repeat(1.5, \"ab\");").unwrap();
+    let words: Vec<String> = engine.get("words").unwrap();
+    println!("native: {:?}", words);
+    engine.eval("let pairs = (1, \"one\");").unwrap();
+    let pairs: (i64, String) = engine.get("pairs").unwrap();
+    println!("tuple: {:?}", pairs);
+    let scores: HashMap<String, Option<i64>> = engine.call("scores", vec![]).unwrap();
+    println!("map: {:?}", scores);
+
+    // the engine keeps running after the failed sources
+    let failures = [
+        engine.eval("let x = ;"),
+        engine.eval("twice(\"text\")"),
+        engine.eval("twice(1, 2)"),
+        engine.eval("repeat(\"text\", \"ab\")"),
+        engine.eval("repeat(2)"),
+        engine.eval("panic(\"stopped\");"),
+        engine.eval("exit(3);"),
+        engine.eval("use nope from std::core::io;"),
+        engine.eval("spawn(broken());\nawait sleep(5);"),
+        engine.call("missing", vec![]),
+        engine.call("add", vec![1.into_absurd()]),
+        engine.call("answer", vec![]),
+        engine.eval_file("missing.abs"),
+    ];
+    for failure in failures {
+        println!("error: {}", failure.unwrap_err());
+    }
+    let words: Result<Vec<i64>, _> = engine.get("words");
+    println!("error: {}", words.unwrap_err());
+    println!("still running: {}", engine.eval("twice(answer)").unwrap());
+}
+
+/// async functions, `broken` fails after its task is suspended
+const TASKS: &str = "use sleep, spawn from std::core::task;
+func async later(n: int) -> int {
+    await sleep(1);
+    return n;
+}
+func async broken() -> int {
+    await sleep(1);
+    panic(\"task stopped\");
+    return 0;
+}";
+
+/// the text repeated `times` times, rounded down
+fn repeat(times: f64, text: String) -> Vec<String> {
+    vec![text; times as usize]
+}
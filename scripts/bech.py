@@ -1 +1,44 @@
-# run code benchmarks
\ No newline at end of file
+# run code benchmarks
+import glob
+import os
+import subprocess
+import sys
+import time
+from utils import run
+
+binary = os.path.join("target", "release", "absurd")
+# every benchmark runs this many times, the fastest run is reported
+runs = 5
+
+
+def measure(path, *flags):
+    best = None
+    for _ in range(runs):
+        start = time.perf_counter()
+        result = subprocess.run([binary, "run", path, *flags], capture_output=True, text=True)
+        elapsed = time.perf_counter() - start
+        if result.returncode != 0:
+            print(f">>> fail: {path}: {result.stderr.strip()}")
+            sys.exit(1)
+        best = elapsed if best is None else min(best, elapsed)
+    return best, result.stdout
+
+
+def main():
+    print(">>> building the binary...")
+    run("cargo build --release")
+
+    # programs in `benches/` run on the tree-walker and on the VM, their outputs must match
+    print(f"{'benchmark':<24}{'tree-walker':>14}{'vm':>14}{'speedup':>10}")
+    for path in sorted(glob.glob(os.path.join("benches", "*.abs"))):
+        tree, tree_output = measure(path)
+        vm, vm_output = measure(path, "--vm")
+        if tree_output != vm_output:
+            print(f">>> fail: {path}: outputs of the tree-walker and the VM differ")
+            sys.exit(1)
+        name = os.path.basename(path)
+        print(f"{name:<24}{tree * 1000:>12.1f}ms{vm * 1000:>12.1f}ms{tree / vm:>9.2f}x")
+
+
+if __name__ == "__main__":
+    main()
@@ -1 +1,229 @@
-# run tests
\ No newline at end of file
+# run tests
+import glob
+import os
+import re
+import shutil
+import subprocess
+import sys
+import tempfile
+import time
+from utils import run
+
+binary = os.path.join("target", "debug", "absurd")
+failures = []
+
+
+def absurd(*args, input=None):
+    return subprocess.run([binary, *args], capture_output=True, text=True, input=input)
+
+
+def fail(name, reason):
+    failures.append(name)
+    print(f">>> fail: {name}: {reason}")
+
+
+def test_run(path):
+    # every program in `tests/` runs in the testing mode and must not fail
+    result = absurd("run", path, "--test")
+    if result.returncode != 0 or result.stderr or "fail:" in result.stdout:
+        fail(path, result.stderr.strip() or result.stdout.strip())
+    return result.stdout
+
+
+def test_vm(path, output):
+    # every program in `tests/` must print the same output on the VM
+    result = absurd("run", path, "--test", "--vm")
+    if result.returncode != 0 or result.stderr or result.stdout != output:
+        fail(path, "the VM output differs from the tree-walker")
+
+
+def test_check(path):
+    # every program in `tests/` must pass the type checker
+    result = absurd("check", path)
+    if result.returncode != 0 or result.stderr:
+        fail(path, result.stderr.strip())
+
+
+def test_format_round_trip(path, output, tmp):
+    # formatting must be idempotent and must not change the behaviour
+    copy = os.path.join(tmp, os.path.basename(path))
+    shutil.copy(path, copy)
+    absurd("format", copy)
+    if absurd("format", copy, "--check").returncode != 0:
+        fail(path, "formatting isn't idempotent")
+    elif absurd("run", copy, "--test").stdout != output:
+        fail(path, "formatting changed the output")
+
+
+def test_format_expected(path, tmp):
+    # `tests/format/name.abs` must be formatted into `tests/format/name.expected`
+    copy = os.path.join(tmp, os.path.basename(path))
+    shutil.copy(path, copy)
+    absurd("format", copy)
+    with open(copy) as f, open(path[: -len(".abs")] + ".expected") as e:
+        if f.read() != e.read():
+            fail(path, "unexpected formatting")
+
+
+def test_lint(path):
+    # every program in `tests/` must be lint clean
+    result = absurd("lint", path)
+    if result.returncode != 0:
+        fail(path, result.stderr.strip())
+
+
+def test_lint_expected(path):
+    # `tests/lint/name.abs` must report lints from `tests/lint/name.expected`
+    result = absurd("lint", path, "--json")
+    with open(path[: -len(".abs")] + ".expected") as e:
+        if result.returncode != 1 or result.stdout != e.read():
+            fail(path, "unexpected lints")
+
+
+def test_emit_expected(path):
+    # `tests/emit/name.abs` must emit `tests/emit/name.expected` with `--emit ast`
+    result = absurd("run", path, "--emit", "ast")
+    with open(path[: -len(".abs")] + ".expected") as e:
+        if result.returncode != 0 or result.stdout != e.read():
+            fail(path, "unexpected ast")
+
+
+def test_errors_expected(path, *flags):
+    # `tests/errors/name.abs` must fail with every error from `tests/errors/name.expected`,
+    # one `code line` pair per line (line is omitted for errors without a span), in the reported order,
+    # notes pointing at other lines are listed as `note line`
+    result = absurd("run", path, *flags)
+    stderr = re.sub(r"\x1b\[[0-9;]*m", "", result.stderr)
+    pattern = r"(E0x\d+|note): .*?(?:, at line (\d+):\d+-\d+)?$"
+    found = [(c, l) for c, l in re.findall(pattern, stderr, re.M) if c != "note" or l]
+    errors = "".join(f"{c} {l}".strip() + "\n" for c, l in found)
+    with open(path[: -len(".abs")] + ".expected") as e:
+        if result.returncode != 1 or errors != e.read():
+            fail(path, f"unexpected errors:\n{errors}")
+
+
+def test_repl_expected(path):
+    # `tests/repl/name.abs` is fed to the REPL line by line, its output must match
+    # `tests/repl/name.expected` and errors must not end the session
+    with open(path) as f:
+        result = absurd("repl", input=f.read())
+    with open(path[: -len(".abs")] + ".expected") as e:
+        if result.returncode != 0 or result.stdout != e.read():
+            fail(path, f"unexpected output:\n{result.stdout}")
+
+
+def test_example_expected(path):
+    # `examples/name.rs` embeds the library, its output must match `examples/name.expected`
+    name = os.path.basename(path)[: -len(".rs")]
+    result = subprocess.run(
+        [os.path.join("target", "debug", "examples", name)], capture_output=True, text=True
+    )
+    with open(path[: -len(".rs")] + ".expected") as e:
+        if result.returncode != 0 or result.stdout != e.read():
+            fail(path, f"unexpected output:\n{result.stdout}{result.stderr}")
+
+
+def test_cli():
+    # commands without their arguments and with unknown ones must exit with a non-zero code
+    cases = [
+        (["error"], 1),
+        (["error", "E0x301"], 0),
+        (["error", "E0x999"], 1),
+        (["check"], 1),
+        (["lint"], 1),
+    ]
+    for args, code in cases:
+        result = absurd(*args)
+        if result.returncode != code:
+            fail(" ".join(["absurd", *args]), f"exited with {result.returncode}, expected {code}")
+
+
+def test_tasks_overlap(tmp):
+    # tasks waiting for timers must interleave, three tasks sleeping twice
+    # for 300ms each take 0.6s together, not 1.8s, on both backends
+    path = os.path.join(tmp, "overlap.abs")
+    with open(path, "w") as f:
+        f.write(
+            "use join_all, sleep from std::core::task;\n"
+            "func async work(n: int) -> int {\n"
+            "    await sleep(300);\n"
+            "    await sleep(300);\n"
+            "    return n;\n"
+            "}\n"
+            "print(await join_all([work(1), work(2), work(3)]));\n"
+        )
+    for flags in [[], ["--vm"]]:
+        start = time.monotonic()
+        result = absurd("run", path, *flags)
+        elapsed = time.monotonic() - start
+        if result.returncode != 0 or result.stdout != "[1, 2, 3]\n" or elapsed > 1.0:
+            fail(" ".join(["overlap", *flags]), f"took {elapsed:.2f}s: {result.stderr.strip()}")
+
+
+def test_error_docs(tmp):
+    # every error code has an explanation in `docs/errors`,
+    # its failing example must fail with the code and its fixed example must pass
+    with open(os.path.join("src", "errors.rs")) as f:
+        codes = sorted(set(re.findall(r"^    (E0x\d+),$", f.read(), re.M)))
+    for code in codes:
+        path = os.path.join("docs", "errors", f"{code}.md")
+        if not os.path.exists(path):
+            fail(code, "missing explanation")
+            continue
+        with open(path) as f:
+            doc = f.read()
+        examples = dict(re.findall(r"^## (failing|fixed)\n\n```abs\n(.*?)```", doc, re.M | re.S))
+        if not examples:
+            if "internal error" not in doc:
+                fail(code, "missing examples")
+            continue
+        for kind, src in examples.items():
+            example = os.path.join(tmp, f"{code}_{kind}.abs")
+            with open(example, "w") as f:
+                f.write(src)
+            result = absurd("run", example)
+            stderr = result.stderr
+            if kind == "failing" and (code not in stderr or result.returncode == 0):
+                fail(code, f"failing example doesn't fail with {code}")
+            elif kind == "fixed" and stderr:
+                fail(code, f"fixed example fails: {stderr.strip()}")
+        if len(examples) != 2:
+            fail(code, "missing failing or fixed example")
+
+
+def main():
+    print(">>> building the binary...")
+    run("cargo build --bins --examples")
+
+    with tempfile.TemporaryDirectory() as tmp:
+        for path in sorted(glob.glob("tests/*.abs")):
+            output = test_run(path)
+            test_vm(path, output)
+            test_check(path)
+            test_format_round_trip(path, output, tmp)
+            test_lint(path)
+        for path in sorted(glob.glob("tests/format/*.abs")):
+            test_format_expected(path, tmp)
+        for path in sorted(glob.glob("tests/lint/*.abs")):
+            test_lint_expected(path)
+        for path in sorted(glob.glob("tests/emit/*.abs")):
+            test_emit_expected(path)
+        for path in sorted(glob.glob("tests/errors/*.abs")):
+            test_errors_expected(path)
+            test_errors_expected(path, "--vm")
+        for path in sorted(glob.glob("tests/repl/*.abs")):
+            test_repl_expected(path)
+        for path in sorted(glob.glob("examples/*.rs")):
+            test_example_expected(path)
+        test_cli()
+        test_tasks_overlap(tmp)
+        test_error_docs(tmp)
+
+    if failures:
+        print(f">>> {len(failures)} test(s) failed")
+        sys.exit(1)
+    print(">>> all tests passed")
+
+
+if __name__ == "__main__":
+    main()
@@ -1,6 +1,6 @@
-use super::{Base, LiteralKind, Token, TokenType::*};
+use super::{Base, LiteralKind, MapKey, RangeType, Token, TokenType::*};
 use crate::ast::LiteralType;
-use std::fmt;
+use std::{fmt, rc::Rc};
 
 impl LiteralType {
     pub fn type_name(&self) -> String {
@@ -8,11 +8,15 @@ impl LiteralType {
             Self::Enum { .. } => "enum".to_string(),
             Self::Tuple(_) => "tuple".to_string(),
             Self::Record(_) => "record".to_string(),
-            Self::Number(_) => "number".to_string(),
+            Self::Int(_) => "int".to_string(),
+            Self::Float(_) => "float".to_string(),
             Self::String(_) => "string".to_string(),
             Self::Char(_) => "char".to_string(),
             Self::Boolean(_) => "bool".to_string(),
             Self::Vec(_) => "vector".to_string(),
+            Self::Map(_) => "map".to_string(),
+            Self::Range(_) => "range".to_string(),
+            Self::Future(_) => "future".to_string(),
             Self::Func(_) => "function".to_string(),
             Self::Void => "void".to_string(),
             Self::DeclrFunc(_) => "declared function".to_string(),
@@ -22,21 +26,30 @@ impl LiteralType {
 
     pub fn to_token(&self) -> Token {
         match self {
-            Self::Number(n) => Token::empty(
+            Self::Int(n) => Token::empty(
                 NumLit,
                 n.to_string().as_str(),
-                Some(LiteralKind::Number {
+                Some(LiteralKind::Int {
                     base: Base::Decimal,
                     value: *n,
                 }),
             ),
-            Self::String(s) => {
-                Token::empty(StrLit, s, Some(LiteralKind::String { value: s.clone() }))
-            }
+            Self::Float(n) => Token::empty(
+                NumLit,
+                self.to_string().as_str(),
+                Some(LiteralKind::Float { value: *n }),
+            ),
+            Self::String(s) => Token::empty(
+                StrLit,
+                s,
+                Some(LiteralKind::String {
+                    value: s.to_string(),
+                }),
+            ),
             Self::Char(c) => Token::empty(
                 StrLit,
                 c.to_string().as_str(),
-                Some(LiteralKind::Char { value: c.clone() }),
+                Some(LiteralKind::Char { value: *c }),
             ),
             Self::Boolean(b) => Token::empty(
                 BoolIdent,
@@ -51,13 +64,16 @@ impl LiteralType {
 
     pub fn is_truthy(&self) -> bool {
         match self {
-            Self::Number(val) => *val != 0.0,
+            Self::Int(val) => *val != 0,
+            Self::Float(val) => *val != 0.0,
             Self::String(val) => !val.is_empty(),
             Self::Char(val) => *val != '\0',
             Self::Boolean(val) => *val,
             Self::Null => false,
             Self::Vec(val) => !val.is_empty(),
             Self::Tuple(val) => !val.is_empty(),
+            Self::Map(val) => !val.is_empty(),
+            Self::Range(range) => range.iter().next().is_some(),
             Self::Record(rec) => !rec.is_empty(),
             _ => false,
         }
@@ -71,6 +87,73 @@ impl LiteralType {
         }
         false
     }
+    /// converts the value to a map key, `None` if it can't be one
+    pub fn to_key(&self) -> Option<MapKey> {
+        match self {
+            Self::Int(n) => Some(MapKey::Int(*n)),
+            Self::String(s) => Some(MapKey::String(s.to_string())),
+            Self::Char(c) => Some(MapKey::Char(*c)),
+            Self::Boolean(b) => Some(MapKey::Boolean(*b)),
+            _ => None,
+        }
+    }
+    /// collects the range into a vector, other values are returned as they are
+    pub fn collect(self) -> LiteralType {
+        match self {
+            Self::Range(range) => Self::Vec(Rc::new(range.items())),
+            value => value,
+        }
+    }
+}
+
+impl RangeType {
+    /// ints of the range, counting down when `lhs` is greater than `rhs`
+    pub fn iter(&self) -> Box<dyn Iterator<Item = i64>> {
+        let (lhs, rhs) = (self.lhs, self.rhs);
+        match (lhs <= rhs, self.is_inclusive) {
+            (true, true) => Box::new((lhs..=rhs).step_by(self.step)),
+            (true, false) => Box::new((lhs..rhs).step_by(self.step)),
+            (false, true) => Box::new((rhs..=lhs).rev().step_by(self.step)),
+            (false, false) => Box::new((rhs + 1..=lhs).rev().step_by(self.step)),
+        }
+    }
+
+    /// count of the ints without producing them
+    pub fn len(&self) -> u64 {
+        let span = self.lhs.abs_diff(self.rhs);
+        let span = match (self.is_inclusive, self.lhs == self.rhs) {
+            (true, _) => span + 1,
+            (false, true) => return 0,
+            (false, false) => span,
+        };
+        span.div_ceil(self.step as u64)
+    }
+
+    pub fn items(&self) -> Vec<LiteralType> {
+        self.iter().map(LiteralType::Int).collect()
+    }
+}
+
+impl fmt::Display for RangeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dots = if self.is_inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.lhs, dots, self.rhs)?;
+        if self.step != 1 {
+            write!(f, " step {}", self.step)?;
+        }
+        Ok(())
+    }
+}
+
+impl MapKey {
+    pub fn to_literal(&self) -> LiteralType {
+        match self {
+            Self::Int(n) => LiteralType::Int(*n),
+            Self::String(s) => LiteralType::String(Rc::new(s.clone())),
+            Self::Char(c) => LiteralType::Char(*c),
+            Self::Boolean(b) => LiteralType::Boolean(*b),
+        }
+    }
 }
 
 impl fmt::Display for LiteralType {
@@ -101,12 +184,15 @@ impl fmt::Display for LiteralType {
             Self::Record(val) => {
                 let n: Vec<String> = val
                     .iter()
-                    .map(|(name, value)| format!("{}:{}", name, value.to_string()))
+                    .map(|(name, value)| format!("{}:{}", name, value))
                     .collect();
                 let c = n.join(", ");
                 write!(f, "{{ {} }}", c)
             }
-            Self::Number(val) => write!(f, "{}", val),
+            Self::Int(val) => write!(f, "{}", val),
+            // whole floats keep their point, so they don't read as ints
+            Self::Float(val) if val.is_finite() && val.fract() == 0.0 => write!(f, "{:.1}", val),
+            Self::Float(val) => write!(f, "{}", val),
             Self::String(val) => write!(f, "{}", val),
             Self::Char(val) => write!(f, "{}", val),
             Self::Boolean(val) => write!(f, "{}", val),
@@ -121,6 +207,16 @@ impl fmt::Display for LiteralType {
                 }
                 write!(f, "[{}]", s)
             }
+            Self::Map(val) if val.is_empty() => write!(f, "[:]"),
+            Self::Map(val) => {
+                let n: Vec<String> = val
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.to_literal(), value))
+                    .collect();
+                write!(f, "[{}]", n.join(", "))
+            }
+            Self::Range(range) => write!(f, "{}", range),
+            Self::Future(_) => write!(f, "future"),
             Self::Void => write!(f, "void"),
             Self::Func(func) => write!(f, "{:?}", func.name),
             Self::DeclrFunc(declr_func) => write!(f, "{}()", declr_func.name),
@@ -1,11 +1,16 @@
 pub mod literals;
+pub mod pattern;
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     fmt::{self, Debug},
     rc::Rc,
 };
 pub mod token;
-use crate::interpreter::{env::Env, expr::Expression, types::TypeKind};
+use crate::engine::native::{report, NativeError};
+use crate::errors::Error;
+use crate::interpreter::{env::Env, expr::Expression, task::TaskId, types::TypeKind};
+use crate::vm::chunk::Function;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
@@ -28,6 +33,8 @@ pub enum TokenType {
     While,
     /// break
     Break,
+    /// continue
+    Continue,
     /// enum
     Enum,
     /// match
@@ -56,16 +63,24 @@ pub enum TokenType {
     TypeStmt,
     /// label
     Label,
+    /// step of the range
+    Step,
     /// record
     RecordStmt,
+    /// methods of the type
+    Impl,
     /// extends
     Extends,
     /// strict
     Strict,
 
     // type keywords
-    /// number
+    /// number, int or float
     NumIdent,
+    /// int
+    IntIdent,
+    /// float
+    FloatIdent,
     /// string
     StrIdent,
     /// char
@@ -81,6 +96,8 @@ pub enum TokenType {
     // compound type keywords
     /// Vec<T>
     VecT,
+    /// Map<K, V>
+    MapT,
     /// Record<T>
     Record,
     /// Tuple<(T, T)>
@@ -95,6 +112,14 @@ pub enum TokenType {
     Prcnt,
     /// &
     And,
+    /// ^
+    Caret,
+    /// ~
+    Tilde,
+    /// <<
+    Shl,
+    /// >>
+    Shr,
     /// &&
     DblAnd,
     /// `*`
@@ -161,6 +186,8 @@ pub enum TokenType {
     Dot,
     /// ..
     DblDot,
+    /// ..=
+    DblDotEq,
     /// /
     Div,
     /// \
@@ -181,7 +208,7 @@ pub enum TokenType {
     CharLit,
     /// string, "hi"
     StrLit,
-    /// number, 5
+    /// number, 5 or 5.0
     NumLit,
     /// true
     TrueLit,
@@ -189,6 +216,8 @@ pub enum TokenType {
     FalseLit,
     /// vector, [3, 3]
     VecLit,
+    /// map, ["a": 3, "b": 3]
+    MapLit,
     /// tuple (1, 2)
     TupleLit,
     /// array
@@ -213,15 +242,19 @@ pub enum Base {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum LiteralType {
-    Number(f32),
-    String(String),
+    Int(i64),
+    Float(f64),
+    String(Rc<String>),
     Char(char),
     Boolean(bool),
     Null,
     Void,
-    Vec(Vec<LiteralType>),
+    Vec(Rc<Vec<LiteralType>>),
     Tuple(Vec<LiteralType>),
-    Record(Vec<(String, Expression)>),
+    Record(Rc<Vec<(String, Expression)>>),
+    Map(Rc<BTreeMap<MapKey, LiteralType>>),
+    Range(RangeType),
+    Future(FutureType),
     Func(FuncImpl),
     DeclrFunc(DeclrFuncType),
     Enum {
@@ -231,6 +264,32 @@ pub enum LiteralType {
     },
 }
 
+/// key of the map, only values with the total order can be keys
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub enum MapKey {
+    Int(i64),
+    String(String),
+    Char(char),
+    Boolean(bool),
+}
+
+/// range of ints, its items are produced while it's iterated
+#[derive(Debug, PartialEq, Clone)]
+pub struct RangeType {
+    pub lhs: i64,
+    pub rhs: i64,
+    pub step: usize,
+    /// `..=` includes `rhs`
+    pub is_inclusive: bool,
+}
+
+/// handle of the task in the executor, its value is known after `await`,
+/// the task is freed when its last handle is dropped and it isn't running
+#[derive(Debug, PartialEq, Clone)]
+pub struct FutureType {
+    pub task: Rc<TaskId>,
+}
+
 #[derive(Debug, Clone)]
 pub struct DeclrFuncType {
     pub name: String,
@@ -240,6 +299,30 @@ pub struct DeclrFuncType {
 
 pub trait FuncValType {
     fn call(&self, args: Vec<Option<LiteralType>>) -> LiteralType;
+
+    /// calls the function from the call at the token, natives report their errors there
+    fn call_at(&self, args: Vec<Option<LiteralType>>, _at: &Token) -> LiteralType {
+        self.call(args)
+    }
+
+    /// natives changing their first argument, calls on `mut` variables change them in place
+    fn is_mut(&self) -> bool {
+        false
+    }
+
+    /// calls the function from the call at the token with the first argument changed in place,
+    /// natives report their errors to the sink
+    fn call_mut(
+        &self,
+        first: &mut LiteralType,
+        args: Vec<Option<LiteralType>>,
+        at: &Token,
+        _err: &Error,
+    ) -> LiteralType {
+        let mut args = args;
+        args.insert(0, Some(first.clone()));
+        self.call_at(args, at)
+    }
 }
 
 impl Debug for dyn FuncValType {
@@ -248,7 +331,7 @@ impl Debug for dyn FuncValType {
     }
 }
 
-impl<'a> PartialEq for DeclrFuncType {
+impl PartialEq for DeclrFuncType {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name && self.arity == other.arity && self.func.rc_eq(&other.func)
     }
@@ -264,7 +347,10 @@ impl RcFuncValType for Rc<dyn FuncValType> {
     }
 }
 
-pub struct Wrapper(pub Box<dyn Fn(&[Option<LiteralType>]) -> LiteralType>);
+/// closure of the native, its arguments are passed as the slice
+pub type WrapperFn = dyn Fn(&[Option<LiteralType>]) -> LiteralType;
+
+pub struct Wrapper(pub Box<WrapperFn>);
 
 impl FuncValType for Wrapper {
     fn call(&self, args: Vec<Option<LiteralType>>) -> LiteralType {
@@ -272,9 +358,39 @@ impl FuncValType for Wrapper {
     }
 }
 
+/// closure of the native changing its first argument, other arguments are passed as the slice
+pub type MutWrapperFn =
+    dyn Fn(&mut LiteralType, &[Option<LiteralType>]) -> Result<LiteralType, NativeError>;
+
+/// native changing its first argument, it checks its arguments itself
+pub struct MutWrapper(pub Box<MutWrapperFn>);
+
+impl FuncValType for MutWrapper {
+    fn call(&self, args: Vec<Option<LiteralType>>) -> LiteralType {
+        let mut args = args.into_iter();
+        let mut first = args.next().flatten().unwrap_or(LiteralType::Null);
+        self.call_mut(&mut first, args.collect(), &Token::null(), &Error::new(""))
+    }
+
+    fn is_mut(&self) -> bool {
+        true
+    }
+
+    fn call_mut(
+        &self,
+        first: &mut LiteralType,
+        args: Vec<Option<LiteralType>>,
+        at: &Token,
+        err: &Error,
+    ) -> LiteralType {
+        report((self.0)(first, &args), err, at)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum LiteralKind {
-    Number { base: Base, value: f32 },
+    Int { base: Base, value: i64 },
+    Float { value: f64 },
     String { value: String },
     Char { value: char },
     Bool { value: bool },
@@ -285,12 +401,19 @@ pub enum LiteralKind {
 #[derive(Clone, PartialEq, Debug)]
 pub struct FuncImpl {
     pub name: String,
+    /// name token of the definition, errors of the call point at it
+    pub token: Token,
+    /// type parameters, bound by the arguments of each call
+    pub generics: Vec<Token>,
     pub value_type: Token,
-    pub body: Box<Statement>,
+    /// shared by the copies of the function value
+    pub body: Rc<Statement>,
     pub params: Vec<(Token, Token)>,
     pub is_async: bool,
     pub is_pub: bool,
     pub env: Rc<RefCell<Env>>,
+    /// bytecode of the body, functions without it run on the tree-walker
+    pub code: Option<Rc<Function>>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -329,12 +452,25 @@ impl Token {
     }
 }
 
+/// source comment, collected by the scanner for the formatter
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comment {
+    pub text: String,
+    pub line: usize,
+    /// column of the comment start
+    pub pos: usize,
+    /// true if the comment follows code on the same line
+    pub is_inline: bool,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum CallType {
     Func,
     Struct,
     Vector,
     Enum,
+    /// `value.method(args)`, the first argument is the name of the method
+    Method,
 }
 
 #[derive(Debug, PartialEq, Clone, Eq)]
@@ -353,10 +489,63 @@ pub struct RecordField {
     pub default_value: Option<Expression>,
 }
 
+/// arm of the `match`, `pattern if guard => body`, arms of the `match` expression
+/// have expression bodies
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchCase<T = Statement> {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: T,
+}
+
+/// pattern of the `match` arm, names in the pattern bind parts of the matched value
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+    /// `_` matches anything
+    Wildcard { token: Token },
+    /// lowercase name matches anything and binds it
+    Bind { name: Token },
+    /// `1`, `-2.5`, `"text"`, `'c'`, `true` or `null`
+    Value {
+        token: Token,
+        value: Box<LiteralType>,
+    },
+    /// `lhs..rhs` or `lhs..=rhs` of ints or chars
+    Range {
+        token: Token,
+        lhs: Box<LiteralType>,
+        rhs: Box<LiteralType>,
+        is_inclusive: bool,
+    },
+    /// `Enum::Item` or `Enum::Item(payload)`
+    Enum {
+        parent: Token,
+        name: Token,
+        payload: Option<Box<Pattern>>,
+    },
+    /// `(a, b)`
+    Tuple { token: Token, items: Vec<Pattern> },
+    /// `[a, b]`, `[a, ..]` matches vectors with at least its items
+    Vec {
+        token: Token,
+        items: Vec<Pattern>,
+        has_rest: bool,
+    },
+    /// `{x, y: 0}`, fields without patterns bind their names
+    Record {
+        token: Token,
+        fields: Vec<(Token, Pattern)>,
+    },
+    /// `a | b`, alternatives are tried in order
+    Or { patterns: Vec<Pattern> },
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Statement {
     Record {
         name: Token,
+        /// type parameters, `record name<T> { ... }`
+        generics: Vec<Token>,
         extends: Vec<Token>,
         is_strict: bool,
         fields: Vec<RecordField>,
@@ -366,11 +555,19 @@ pub enum Statement {
         is_pub: bool,
         items: Vec<(Token, Option<Token>)>,
     },
+    /// `impl name { func ... }`, functions of the block are called as `name::func(..)`,
+    /// the ones taking `self` also as `value.func(..)`
+    Impl {
+        name: Token,
+        funcs: Vec<Statement>,
+    },
     Sh {
         cmd: String,
     },
     Type {
         name: Token,
+        /// type parameters, `type name<T> = ...`
+        generics: Vec<Token>,
         is_pub: bool,
         value: Token,
     },
@@ -392,6 +589,8 @@ pub enum Statement {
     },
     Func {
         name: Token,
+        /// type parameters, `func name<T>(...)`
+        generics: Vec<Token>,
         value_type: Token,
         body: Box<Statement>,
         params: Vec<(Token, Token)>,
@@ -404,6 +603,8 @@ pub enum Statement {
         else_branch: Option<Box<Statement>>,
     },
     Return {
+        /// `return` keyword, errors of the statement point at it
+        keyword: Token,
         expr: Expression,
     },
     For {
@@ -411,16 +612,28 @@ pub enum Statement {
         index: Option<Token>,
         expr: Expression,
         body: Box<Statement>,
+        /// `label name: for ...`
+        label: Option<Token>,
     },
     While {
         cond: Expression,
         body: Box<Statement>,
+        /// `label name: while ...`
+        label: Option<Token>,
+    },
+    /// `break;` or `break label;`
+    Break {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    /// `continue;` or `continue label;`
+    Continue {
+        keyword: Token,
+        label: Option<Token>,
     },
-    Break {},
     Match {
         cond: Expression,
-        cases: Vec<(Expression, Statement)>,
-        def_case: Box<Statement>,
+        cases: Vec<MatchCase>,
     },
     Mod {
         src: String,
@@ -428,7 +641,33 @@ pub enum Statement {
     },
     Use {
         src: String,
+        /// first token of the source, errors of the import point at it
+        from: Token,
         names: Vec<(Token, Option<Token>)>,
         all: bool,
     },
 }
+
+impl Statement {
+    /// first token of the statement, used to locate it in the source
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            Statement::Expression { expr } => expr.token(),
+            Statement::Return { keyword, .. } => Some(keyword),
+            Statement::Block { stmts } => stmts.first().and_then(|s| s.token()),
+            Statement::Var { names, .. } => names.first(),
+            Statement::Func { name, .. }
+            | Statement::Type { name, .. }
+            | Statement::Record { name, .. }
+            | Statement::Enum { name, .. }
+            | Statement::Impl { name, .. } => Some(name),
+            Statement::If { cond, .. }
+            | Statement::While { cond, .. }
+            | Statement::Match { cond, .. } => cond.token(),
+            Statement::For { iterator, .. } => Some(iterator),
+            Statement::Use { names, from, .. } => names.first().map(|(n, _)| n).or(Some(from)),
+            Statement::Break { keyword, .. } | Statement::Continue { keyword, .. } => Some(keyword),
+            Statement::Mod { .. } | Statement::Sh { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::{Pattern, Token};
+
+impl Pattern {
+    /// first token of the pattern, used to locate it in the source
+    pub fn token(&self) -> &Token {
+        match self {
+            Pattern::Wildcard { token }
+            | Pattern::Value { token, .. }
+            | Pattern::Range { token, .. }
+            | Pattern::Tuple { token, .. }
+            | Pattern::Vec { token, .. }
+            | Pattern::Record { token, .. } => token,
+            Pattern::Bind { name } => name,
+            Pattern::Enum { parent, .. } => parent,
+            Pattern::Or { patterns } => patterns[0].token(),
+        }
+    }
+
+    /// names bound by the pattern, alternatives bind names of the first one
+    pub fn names(&self) -> Vec<&Token> {
+        match self {
+            Pattern::Bind { name } => vec![name],
+            Pattern::Enum {
+                payload: Some(payload),
+                ..
+            } => payload.names(),
+            Pattern::Tuple { items, .. } | Pattern::Vec { items, .. } => {
+                items.iter().flat_map(|item| item.names()).collect()
+            }
+            Pattern::Record { fields, .. } => {
+                fields.iter().flat_map(|(_, field)| field.names()).collect()
+            }
+            Pattern::Or { patterns } => patterns[0].names(),
+            _ => vec![],
+        }
+    }
+
+    /// matches any value of its type, `(a, _)` matches every pair
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard { .. } | Pattern::Bind { .. } => true,
+            Pattern::Tuple { items, .. } => items.iter().all(|item| item.is_irrefutable()),
+            Pattern::Vec {
+                items, has_rest, ..
+            } => items.is_empty() && *has_rest,
+            Pattern::Record { fields, .. } => {
+                fields.iter().all(|(_, field)| field.is_irrefutable())
+            }
+            Pattern::Or { patterns } => patterns.iter().any(|p| p.is_irrefutable()),
+            _ => false,
+        }
+    }
+}
@@ -1,12 +1,15 @@
 use super::TokenType;
+use std::fmt;
 
-impl TokenType {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let s = match self {
             Self::Extends => "extends",
             Self::Strict => "strict",
             Self::RecordStmt => "record",
+            Self::Impl => "impl",
             Self::Label => "label",
+            Self::Step => "step",
             Self::In => "in",
             Self::For => "for",
             Self::Enum => "enum",
@@ -14,6 +17,8 @@ impl TokenType {
             Self::TupleLit => "tuple literal",
             Self::VecT => "Vec",
             Self::VecLit => "vector literal",
+            Self::MapT => "Map",
+            Self::MapLit => "map literal",
             Self::Record => "Record",
             Self::Sh => "sh",
             Self::Type | Self::TypeStmt => "type",
@@ -21,6 +26,10 @@ impl TokenType {
             Self::DblBang => "!!",
             Self::Prcnt => "%",
             Self::And => "&",
+            Self::Caret => "^",
+            Self::Tilde => "~",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
             Self::DblAnd => "&&",
             Self::Mul => "*",
             Self::Sqr => "**",
@@ -60,6 +69,7 @@ impl TokenType {
             Self::Comma => ",",
             Self::Dot => ".",
             Self::DblDot => "..",
+            Self::DblDotEq => "..=",
             Self::Div => "/",
             Self::Esc => "\\",
             Self::LParse => "\\{",
@@ -76,6 +86,7 @@ impl TokenType {
             Self::Return => "return keyword",
             Self::While => "while keyword",
             Self::Break => "break keyword",
+            Self::Continue => "continue keyword",
             Self::Match => "match keyword",
             Self::Mod => "mod keyword",
             Self::Use => "use keyword",
@@ -87,6 +98,8 @@ impl TokenType {
             Self::Mut => "mut keyword",
             Self::Func => "function keyword",
             Self::NumIdent => "number",
+            Self::IntIdent => "int",
+            Self::FloatIdent => "float",
             Self::StrIdent => "string",
             Self::CharIdent => "char",
             Self::BoolIdent => "bool",
@@ -96,6 +109,6 @@ impl TokenType {
             Self::AnyIdent => "any",
         };
 
-        s.to_string()
+        write!(f, "{}", s)
     }
 }
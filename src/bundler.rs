@@ -1,16 +1,36 @@
 use crate::{
-    ast::Statement,
-    errors::Error,
-    interpreter::{env::Env, expr::Expression, Interpreter},
+    ast::{LiteralType, Statement, Token},
+    checker::Checker,
+    emit,
+    errors::{stop, Error},
+    formatter::Formatter,
+    interpreter::{env::Env, expr::Expression, task, Interpreter},
+    linter::{
+        rules::{Lint, Rule},
+        Linter,
+    },
     parser::{scanner::Scanner, Parser},
     resolver::Resolver,
-    Config,
+    vm, Config,
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// reports every collected diagnostic and stops with a non-zero code, if the source has errors
+pub fn check(err: &Error) {
+    if err.has_errors() {
+        err.flush();
+        stop();
+    }
+}
 
 /// Function `parser` parses the source into the AST representad as `Vec<Statement>`:
 pub fn parser(src: &str, err: Error) -> Vec<Statement> {
-    let mut lexer = Scanner::new(src, err.clone());
+    parser_from(src, 1, err)
+}
+
+/// parses the source continuing another one, tokens are numbered from the `line`
+pub fn parser_from(src: &str, line: usize, err: Error) -> Vec<Statement> {
+    let mut lexer = Scanner::from_line(src, line, err.clone());
     let tokens = lexer.scan();
     let mut parser = Parser::new(tokens.clone(), err);
     parser.parse()
@@ -18,21 +38,89 @@ pub fn parser(src: &str, err: Error) -> Vec<Statement> {
 
 /// treats source as an single expression, and parses source into the expression
 pub fn parse_expr(src: &str, err: Error) -> Expression {
+    let expr = try_parse_expr(src, err.clone());
+    check(&err);
+    expr.unwrap_or(Expression::Value {
+        id: 0,
+        token: Token::null(),
+        value: LiteralType::Null,
+    })
+}
+
+/// same as `parse_expr`, but errors are left in `err` and give `None`
+pub fn try_parse_expr(src: &str, err: Error) -> Option<Expression> {
     let mut lexer = Scanner::new(src, err.clone());
     let tokens = lexer.scan();
-    let mut parser = Parser::new(tokens.clone(), err);
-    parser.expr()
+    let mut parser = Parser::new(tokens.clone(), err.clone());
+    let expr = parser.catch(Parser::expr);
+    expr.filter(|_| !err.has_errors())
+}
+
+/// formats the source code, comments are kept in their places
+pub fn formatter(src: &str, err: Error) -> String {
+    let mut lexer = Scanner::new(src, err.clone());
+    let tokens = lexer.scan();
+    let mut parser = Parser::new(tokens.clone(), err.clone());
+    let stmts = parser.parse();
+    // broken statements are skipped by the parser, so they can't be formatted
+    check(&err);
+    Formatter::new(src, lexer.comments().clone()).format(&stmts)
 }
 
-/// interpets the source code based on the input configuration
+/// lints the source code with the enabled rules, source is resolved before linting
+pub fn linter(src: &str, err: Error, rules: Vec<Rule>) -> Vec<Lint> {
+    let stmts = parser(src, err.clone());
+    let env = Rc::new(RefCell::new(Env::new(HashMap::new())));
+    let mut resolver = Resolver::new(err.clone());
+    resolver.resolve(&stmts, &env);
+    check(&err);
+    Linter::new(rules).lint(&stmts, resolver.bindings())
+}
+
+/// checks types of the source without running it, mismatches stop with a non-zero code
+pub fn checker(src: &str, err: Error) {
+    let stmts = parser(src, err.clone());
+    check(&err);
+    let env = Rc::new(RefCell::new(Env::new(HashMap::new())));
+    Resolver::new(err.clone()).resolve(&stmts, &env);
+    check(&err);
+    Checker::new(err.clone()).check(&stmts);
+    check(&err);
+}
+
+/// interpets the source code based on the input configuration,
+/// `tokens` and `ast` emits stop before the execution, `env` emit dumps the environment after it
 pub fn interpreter(src: &str, project: Config) {
     let err = Error::new(src);
-    let stmts = parser(src, err.clone());
+    let mut lexer = Scanner::new(src, err.clone());
+    let tokens = lexer.scan();
+    if project.emit == "tokens" {
+        print!("{}", emit::tokens(tokens));
+        return check(&err);
+    }
+    let stmts = Parser::new(tokens.clone(), err.clone()).parse();
+    if project.emit == "ast" {
+        print!("{}", emit::ast(&stmts));
+        return check(&err);
+    }
     let mut int = Interpreter::new(project.clone(), err.clone());
     let mut resolver = Resolver::new(err.clone());
-    let locals = resolver.resolve(&stmts, &mut int.env);
-    int.env.borrow_mut().resolve(locals);
-    int.interpret(stmts.iter().collect(), 0);
+    let locals = resolver.resolve(&stmts, &int.env);
+    check(&err);
+    Checker::new(err.clone()).check(&stmts);
+    check(&err);
+    if project.vm {
+        // locals of the compiled code live in slots of the VM, names of the code running
+        // on the tree-walker are looked up by names
+        vm::run(&mut int, &stmts);
+    } else {
+        int.env.borrow_mut().resolve(locals);
+        int.interpret(stmts.iter().collect(), 0);
+    }
+    task::run();
+    if project.emit == "env" {
+        print!("{}", emit::env(&int.env.borrow()));
+    }
 }
 
 /// interpets the source code as an module
@@ -44,8 +132,11 @@ pub fn interpreter_mod(
     let err = Error::new(src);
     let mut int = Interpreter::new_with_env(env, true, src, mod_src, 0);
     let stmts = parser(src, err.clone());
-    let mut resolver = Resolver::new(err);
-    let locals = resolver.resolve(&stmts, &mut int.env);
+    let mut resolver = Resolver::new(err.clone());
+    let locals = resolver.resolve(&stmts, &int.env);
+    check(&err);
+    Checker::new(err.clone()).check(&stmts);
+    check(&err);
     int.env.borrow_mut().resolve(locals);
     int.interpret(stmts.iter().collect(), 0)
 }
@@ -0,0 +1,1329 @@
+// Absurd type checker, infers types of expressions and reports mismatches before the execution
+use crate::ast::{
+    Base, CallType, LiteralKind, LiteralType, MatchCase, Pattern, Statement, Token, TokenType,
+    TokenType::*,
+};
+use crate::errors::{Diagnostic, Error, ErrorCode, ErrorCode::*};
+use crate::interpreter::expr::{AssignKind, Expression};
+use crate::interpreter::types::{
+    any_type, generic, generic_value, instantiate, literalkind_to_literaltype, TypeKind,
+};
+use std::collections::HashMap;
+
+/// declared function, its calls and returns are checked against it
+#[derive(Debug, Clone)]
+struct Signature {
+    name: Token,
+    generics: Vec<Token>,
+    params: Vec<(Token, Token)>,
+    value_type: Token,
+    /// calls return futures, `value_type` is the type of their awaited values
+    is_async: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Symbol {
+    kind: TypeKind,
+    func: Option<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Checker {
+    scopes: Vec<HashMap<String, Symbol>>,
+    /// type aliases and records
+    types: HashMap<String, Token>,
+    enums: HashMap<String, Vec<(Token, Option<Token>)>>,
+    /// types implementing the method, by the name of the method
+    impls: HashMap<String, Vec<Token>>,
+    /// functions being checked, the last one owns `return` statements
+    funcs: Vec<Signature>,
+    err: Error,
+}
+
+impl Checker {
+    pub fn new(err: Error) -> Self {
+        Checker {
+            scopes: vec![HashMap::new()],
+            types: HashMap::new(),
+            enums: HashMap::new(),
+            impls: HashMap::new(),
+            funcs: vec![],
+            err,
+        }
+    }
+
+    /// sets the error sink of the next checked source, declarations stay
+    pub fn set_err(&mut self, err: Error) {
+        self.err = err;
+    }
+
+    /// entry method, mismatches are collected in the error sink
+    pub fn check(&mut self, stmts: &[Statement]) {
+        self.declare(stmts);
+        stmts.iter().for_each(|stmt| self.stmt(stmt));
+    }
+
+    /// declares functions, enums and types of the block before checking it,
+    /// so they can be used before their declaration
+    fn declare(&mut self, stmts: &[Statement]) {
+        for stmt in stmts {
+            match stmt {
+                Statement::Func {
+                    name,
+                    generics,
+                    value_type,
+                    params,
+                    is_async,
+                    ..
+                } => self.define_func(Signature {
+                    name: name.clone(),
+                    generics: generics.clone(),
+                    params: params.clone(),
+                    value_type: value_type.clone(),
+                    is_async: *is_async,
+                }),
+                Statement::Enum { name, items, .. } => {
+                    self.enums.insert(name.lexeme.clone(), items.clone());
+                }
+                // functions are named `type::func` like at runtime
+                Statement::Impl { name: t, funcs } => {
+                    for func in funcs {
+                        let Statement::Func {
+                            name,
+                            generics,
+                            value_type,
+                            params,
+                            is_async,
+                            ..
+                        } = func
+                        else {
+                            continue;
+                        };
+                        if params.first().is_some_and(|(p, _)| p.lexeme == "self") {
+                            let types = self.impls.entry(name.lexeme.clone()).or_default();
+                            types.push(t.clone());
+                        }
+                        let mut name = name.clone();
+                        name.lexeme = format!("{}::{}", t.lexeme, name.lexeme);
+                        self.define_func(Signature {
+                            name,
+                            generics: generics.clone(),
+                            params: params.clone(),
+                            value_type: value_type.clone(),
+                            is_async: *is_async,
+                        });
+                    }
+                }
+                Statement::Type {
+                    name,
+                    generics,
+                    value,
+                    ..
+                } => {
+                    let value = generic(generics, value.clone());
+                    self.types.insert(name.lexeme.clone(), value);
+                }
+                Statement::Record {
+                    name,
+                    generics,
+                    extends,
+                    fields,
+                    ..
+                } => {
+                    // same shape as the record type of the interpreter
+                    let mut fields: Vec<(Token, TypeKind)> = fields
+                        .iter()
+                        .map(|f| (f.name.clone(), f.value.clone().token_to_typekind()))
+                        .collect();
+                    for extend in extends {
+                        if let Some(Token {
+                            value: Some(LiteralKind::Type(t)),
+                            ..
+                        }) = self.types.get(&extend.lexeme)
+                        {
+                            if let TypeKind::Record { fields: fs } = t.as_ref() {
+                                fields.extend(fs.iter().cloned());
+                            }
+                        }
+                    }
+                    let value = Some(LiteralKind::Type(Box::new(TypeKind::Record { fields })));
+                    let value = Token::empty(TokenType::Type, &name.lexeme, value);
+                    self.types
+                        .insert(name.lexeme.clone(), generic(generics, value));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Expression { expr } => {
+                self.expr(expr);
+            }
+            Statement::Block { stmts } => {
+                self.scope_start();
+                self.check(stmts);
+                self.scope_end();
+            }
+            Statement::Var {
+                names,
+                destruct,
+                value_type,
+                value,
+                is_func,
+                ..
+            } => self.var(names, destruct.is_some(), value_type, value, *is_func),
+            Statement::Func {
+                name,
+                generics,
+                value_type,
+                body,
+                params,
+                ..
+            } => self.func(name, generics, params, value_type, body),
+            Statement::Impl { funcs, .. } => funcs.iter().for_each(|f| self.stmt(f)),
+            Statement::Return { expr, .. } => self.returns(expr),
+            Statement::If {
+                cond,
+                body,
+                else_branch,
+            } => {
+                self.expr(cond);
+                self.stmt(body);
+                if let Some(branch) = else_branch {
+                    self.stmt(branch);
+                }
+            }
+            Statement::While { cond, body, .. } => {
+                self.expr(cond);
+                self.stmt(body);
+            }
+            Statement::For {
+                iterator,
+                index,
+                expr,
+                body,
+                ..
+            } => {
+                // sequences bind their items and indices, records their field names
+                // and values, maps their keys and values
+                let int = named(IntIdent, "int");
+                let (item, second) = match widen(&self.expr(expr)) {
+                    TypeKind::Vec { kind } => (*kind, int),
+                    TypeKind::Tuple { types } => (union(types), int),
+                    TypeKind::Map { key, value } => (*key, *value),
+                    TypeKind::Record { fields } => {
+                        let kinds = fields.into_iter().map(|(_, kind)| kind).collect();
+                        (named(StrIdent, "string"), union(kinds))
+                    }
+                    kind if is(&kind, StrIdent) => (named(CharIdent, "char"), int),
+                    kind if is_scalar(&kind) => {
+                        let at = expr.token().unwrap_or(iterator);
+                        self.report(E0x312, at, vec![type_name(&kind)]);
+                        (any(), any())
+                    }
+                    _ => (any(), any()),
+                };
+                self.scope_start();
+                self.define(iterator, item);
+                if let Some(index) = index {
+                    self.define(index, second);
+                }
+                self.stmt(body);
+                self.scope_end();
+            }
+            Statement::Match { cond, cases } => {
+                let at = cond.token().or(cases.first().map(|c| c.pattern.token()));
+                self.matchs(cond, cases, at.cloned(), |checker, body| {
+                    checker.stmt(body);
+                    any()
+                });
+            }
+            Statement::Record { fields, .. } => {
+                // default values must match their fields
+                for field in fields {
+                    if let Some(default) = &field.default_value {
+                        let actual = self.expr(default);
+                        let expected = self.annotation(&field.value);
+                        if !fits(&expected, &actual) {
+                            self.mismatch(E0x301, &field.name, &field.value, &actual);
+                        }
+                    }
+                }
+            }
+            Statement::Use { names, .. } => {
+                // imported values aren't known before the execution
+                for (old, new) in names {
+                    self.define(new.as_ref().unwrap_or(old), any());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn var(
+        &mut self,
+        names: &[Token],
+        is_destruct: bool,
+        value_type: &Token,
+        value: &Option<Expression>,
+        is_func: bool,
+    ) {
+        let actual = match value {
+            Some(value) => self.expr(value),
+            None => named(Null, "null"),
+        };
+        // types written by the parser have no location, they are inferred from the value
+        let is_annotated = value_type.line != 0;
+        let kind = if is_annotated {
+            let expected = self.annotation(value_type);
+            if value.is_some() && !fits(&expected, &actual) {
+                self.mismatch(E0x301, &names[0], value_type, &actual);
+            }
+            expected
+        } else {
+            widen(&actual)
+        };
+
+        if is_destruct {
+            names.iter().for_each(|name| self.define(name, any()));
+            return;
+        }
+        for name in names {
+            let func = match value {
+                Some(Expression::Func {
+                    value_type,
+                    params,
+                    is_async,
+                    ..
+                }) if is_func => Some(Signature {
+                    name: name.clone(),
+                    generics: vec![],
+                    params: params.clone(),
+                    value_type: value_type.clone(),
+                    is_async: *is_async,
+                }),
+                _ => None,
+            };
+            self.scopes.last_mut().unwrap().insert(
+                name.lexeme.clone(),
+                Symbol {
+                    kind: kind.clone(),
+                    func,
+                },
+            );
+        }
+    }
+
+    fn func(
+        &mut self,
+        name: &Token,
+        generics: &[Token],
+        params: &[(Token, Token)],
+        value_type: &Token,
+        body: &Statement,
+    ) {
+        // values of type parameters aren't known inside of the function
+        let bindings = generics
+            .iter()
+            .map(|g| (g.lexeme.clone(), any_type()))
+            .collect();
+        let params: Vec<(Token, Token)> = params
+            .iter()
+            .map(|(param, t)| (param.clone(), instantiate(t, &bindings)))
+            .collect();
+        let signature = Signature {
+            name: name.clone(),
+            generics: vec![],
+            params: params.clone(),
+            value_type: instantiate(value_type, &bindings),
+            is_async: false,
+        };
+        self.funcs.push(signature);
+        self.scope_start();
+        for (param, param_type) in &params {
+            let kind = self.annotation(param_type);
+            self.define(param, kind);
+        }
+        match body {
+            // short functions return their expression
+            Statement::Expression { expr } => self.returns(expr),
+            Statement::Block { stmts } => self.check(stmts),
+            stmt => self.stmt(stmt),
+        }
+        self.scope_end();
+        self.funcs.pop();
+    }
+
+    fn returns(&mut self, expr: &Expression) {
+        let actual = self.expr(expr);
+        let Some(func) = self.funcs.last().cloned() else {
+            return;
+        };
+        let expected = self.annotation(&func.value_type);
+        // bare `return;` of the void function
+        let is_bare = matches!(
+            expr,
+            Expression::Value {
+                value: LiteralType::Null,
+                ..
+            }
+        );
+        if is_bare && is(&expected, VoidIdent) {
+            return;
+        }
+        if !fits(&expected, &actual) {
+            let at = expr.token().unwrap_or(&func.name).clone();
+            self.mismatch(E0x301, &at, &func.value_type, &actual);
+        }
+    }
+
+    /// infers the type of the expression, `any` if it isn't known before the execution
+    fn expr(&mut self, expr: &Expression) -> TypeKind {
+        match expr {
+            Expression::Value { value, .. } => value_type(value),
+            Expression::Var { name, .. } => match self.lookup(&name.lexeme) {
+                Some(symbol) => symbol.kind,
+                None if self.enums.contains_key(&name.lexeme) => named(Enum, &name.lexeme),
+                None => any(),
+            },
+            Expression::Vec { items, .. } => {
+                let kinds: Vec<TypeKind> = items.iter().map(|i| widen(&self.expr(i))).collect();
+                TypeKind::Vec {
+                    kind: Box::new(union(kinds)),
+                }
+            }
+            Expression::Map { items, .. } => {
+                let (keys, values) = items
+                    .iter()
+                    .map(|(k, v)| (widen(&self.expr(k)), widen(&self.expr(v))))
+                    .unzip();
+                TypeKind::Map {
+                    key: Box::new(union(keys)),
+                    value: Box::new(union(values)),
+                }
+            }
+            Expression::Tuple { items, .. } => TypeKind::Tuple {
+                types: items.iter().map(|i| self.expr(i)).collect(),
+            },
+            Expression::Record { fields, .. } => TypeKind::Record {
+                fields: fields
+                    .iter()
+                    .map(|(name, value)| (Token::empty(Ident, name, None), self.expr(value)))
+                    .collect(),
+            },
+            Expression::Grouping { expression, .. } => self.expr(expression),
+            Expression::Unary { left, operator, .. } => {
+                let left = widen(&self.expr(left));
+                match operator.token {
+                    Bang | DblBang => named(BoolIdent, "bool"),
+                    Min | Sqr | Decr | Incr if is_number(&left) => left,
+                    Tilde if is(&left, IntIdent) => left,
+                    _ => any(),
+                }
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = widen(&self.expr(left));
+                let right = widen(&self.expr(right));
+                binary(&left, &operator.token, &right).unwrap_or_else(|args| {
+                    self.report(E0x301, operator, args);
+                    any()
+                })
+            }
+            Expression::Assign {
+                name, value, kind, ..
+            } => {
+                let actual = self.expr(value);
+                if *kind == AssignKind::Normal {
+                    if let Some(symbol) = self.lookup(&name.lexeme) {
+                        if !fits(&symbol.kind, &actual) {
+                            self.report(E0x412, name, vec![name.lexeme.clone()]);
+                        }
+                    }
+                }
+                actual
+            }
+            Expression::Call {
+                name,
+                args,
+                call_type,
+                ..
+            } => {
+                let kind = self.call(name, args, call_type);
+                // values of async calls are known after `await`
+                if self.is_async(name) {
+                    any()
+                } else {
+                    kind
+                }
+            }
+            Expression::If {
+                cond,
+                body,
+                else_branch,
+                ..
+            } => {
+                self.expr(cond);
+                let body = self.expr(body);
+                match else_branch {
+                    Some(branch) => {
+                        let branch = self.expr(branch);
+                        if same(&body, &branch) {
+                            body
+                        } else {
+                            any()
+                        }
+                    }
+                    None => any(),
+                }
+            }
+            Expression::Match {
+                token, cond, cases, ..
+            } => {
+                let kinds = self.matchs(cond, cases, Some(token.clone()), |checker, body| {
+                    checker.expr(body)
+                });
+                union(kinds.iter().map(widen).collect())
+            }
+            Expression::Func {
+                name,
+                value_type,
+                body,
+                params,
+                ..
+            } => {
+                self.func(name, &[], params, value_type, body);
+                named(FuncIdent, "function")
+            }
+            Expression::Range {
+                token,
+                lhs,
+                rhs,
+                step,
+                ..
+            } => {
+                let bounds = [Some(lhs), Some(rhs), step.as_ref()];
+                for bound in bounds.into_iter().flatten() {
+                    let kind = widen(&self.expr(bound));
+                    let is_int = is(&kind, IntIdent) || is(&kind, NumIdent);
+                    if !is_int && (is_scalar(&kind) || is(&kind, StrIdent)) {
+                        let at = bound.token().unwrap_or(token);
+                        self.report(E0x314, at, vec![type_name(&kind)]);
+                    }
+                }
+                TypeKind::Vec {
+                    kind: Box::new(named(IntIdent, "int")),
+                }
+            }
+            Expression::Await { expr, .. } => match expr.as_ref() {
+                Expression::Call {
+                    name,
+                    args,
+                    call_type,
+                    ..
+                } => self.call(name, args, call_type),
+                // futures stored in variables aren't typed
+                expr => {
+                    self.expr(expr);
+                    any()
+                }
+            },
+        }
+    }
+
+    fn call(&mut self, callee: &Expression, args: &[Expression], call_type: &CallType) -> TypeKind {
+        match (call_type, callee) {
+            (CallType::Enum, Expression::Var { name, .. }) => return self.enum_call(name, args),
+            (CallType::Method, _) => return self.method_call(callee, args),
+            _ => {}
+        }
+        let symbol = match callee {
+            Expression::Var { name, .. } => self.lookup(&name.lexeme),
+            _ => None,
+        };
+        let kind = match &symbol {
+            Some(symbol) => symbol.kind.clone(),
+            None => self.expr(callee),
+        };
+        match (call_type, kind) {
+            (CallType::Func, _) => {
+                let arg_types: Vec<TypeKind> = args.iter().map(|a| self.expr(a)).collect();
+                match symbol.and_then(|s| s.func) {
+                    Some(func) => self.func_call(&func, callee, args, arg_types),
+                    None => any(),
+                }
+            }
+            (CallType::Vector, kind) => {
+                self.expr(&args[0]);
+                match kind {
+                    TypeKind::Vec { kind } => *kind,
+                    TypeKind::Map { value, .. } => *value,
+                    TypeKind::Tuple { types } => match &args[0] {
+                        Expression::Value {
+                            value: LiteralType::Int(n),
+                            ..
+                        } => usize::try_from(*n)
+                            .ok()
+                            .and_then(|n| types.get(n).cloned())
+                            .unwrap_or(any()),
+                        _ => any(),
+                    },
+                    kind if is(&widen(&kind), StrIdent) => named(CharIdent, "char"),
+                    _ => any(),
+                }
+            }
+            (CallType::Struct, TypeKind::Record { fields }) => match &args[0] {
+                Expression::Value {
+                    value: LiteralType::String(field),
+                    ..
+                } => fields
+                    .iter()
+                    .find(|(name, _)| name.lexeme == **field)
+                    .map_or(any(), |(_, kind)| kind.clone()),
+                _ => any(),
+            },
+            _ => any(),
+        }
+    }
+
+    fn is_async(&self, callee: &Expression) -> bool {
+        let Expression::Var { name, .. } = callee else {
+            return false;
+        };
+        self.lookup(&name.lexeme)
+            .and_then(|symbol| symbol.func)
+            .is_some_and(|func| func.is_async)
+    }
+
+    /// checks arguments of the call, errors point at the definition of the function like at runtime
+    fn func_call(
+        &mut self,
+        func: &Signature,
+        callee: &Expression,
+        args: &[Expression],
+        arg_types: Vec<TypeKind>,
+    ) -> TypeKind {
+        let call = callee.token().cloned().unwrap_or(Token::null());
+
+        // type parameters are bound by the arguments, unbound ones are `any`
+        let mut bindings = HashMap::new();
+        for ((_, param_type), actual) in func.params.iter().zip(&arg_types) {
+            bind(param_type, actual, &func.generics, &mut bindings);
+        }
+        for param in &func.generics {
+            if !bindings.contains_key(&param.lexeme) {
+                bindings.insert(param.lexeme.clone(), any_type());
+            }
+        }
+
+        if args.len() != func.params.len() {
+            self.report_call(E0x405, &call, func, vec![]);
+        } else {
+            for ((arg, actual), (_, param_type)) in args.iter().zip(arg_types).zip(&func.params) {
+                let param_type = instantiate(param_type, &bindings);
+                let expected = self.annotation(&param_type);
+                if !fits(&expected, &actual) {
+                    let at = arg.token().unwrap_or(&call).clone();
+                    let args = vec![self.expected_name(&param_type), type_name(&actual)];
+                    self.report_call(E0x301, &at, func, args);
+                }
+            }
+        }
+        self.annotation(&instantiate(&func.value_type, &bindings))
+    }
+
+    /// `value.method(args)`, the method is picked by the type of the value like at runtime
+    fn method_call(&mut self, receiver: &Expression, args: &[Expression]) -> TypeKind {
+        let Expression::Value {
+            value: LiteralType::String(method),
+            ..
+        } = &args[0]
+        else {
+            return any();
+        };
+        let kind = self.expr(receiver);
+        let mut arg_types = vec![kind.clone()];
+        arg_types.extend(args[1..].iter().map(|a| self.expr(a)));
+
+        let types = self.impls.get(method.as_str()).cloned().unwrap_or_default();
+        // values of unknown types may have any of the methods
+        if is(&kind, AnyIdent) && types.len() != 1 {
+            return any();
+        }
+        let found = match &kind {
+            TypeKind::Var { name } if name.token == Enum => {
+                types.iter().find(|t| t.lexeme == name.lexeme)
+            }
+            kind => types.iter().find(|t| fits(&self.annotation(t), kind)),
+        };
+        let func = found
+            .and_then(|t| self.lookup(&format!("{}::{}", t.lexeme, method)))
+            .and_then(|symbol| symbol.func);
+        match func {
+            Some(func) => {
+                let mut args = args.to_vec();
+                args[0] = receiver.clone();
+                self.func_call(&func, receiver, &args, arg_types)
+            }
+            None => {
+                let at = receiver.token().cloned().unwrap_or(Token::null());
+                let args = vec![type_name(&kind), method.to_string()];
+                self.report(E0x317, &at, args);
+                any()
+            }
+        }
+    }
+
+    /// checks the payload of the enum item, or arguments of the static function `type::func(args)`
+    fn enum_call(&mut self, parent: &Token, args: &[Expression]) -> TypeKind {
+        let func = match &args[0] {
+            Expression::Var { name, .. } => self
+                .lookup(&format!("{}::{}", parent.lexeme, name.lexeme))
+                .and_then(|symbol| symbol.func),
+            _ => None,
+        };
+        if let Some(func) = func {
+            let callee = Expression::Var {
+                id: 0,
+                name: parent.clone(),
+            };
+            let arg_types = args[1..].iter().map(|a| self.expr(a)).collect();
+            return self.func_call(&func, &callee, &args[1..], arg_types);
+        }
+        if !self.enums.contains_key(&parent.lexeme) && self.types.contains_key(&parent.lexeme) {
+            let args = vec![parent.lexeme.clone(), args[0].to_string()];
+            self.report(E0x317, parent, args);
+            return any();
+        }
+        let payload = args.get(1).map(|a| (a, self.expr(a)));
+        let item = match (&args[0], self.enums.get(&parent.lexeme)) {
+            (Expression::Var { name, .. }, Some(items)) => {
+                items.iter().find(|(i, _)| i.lexeme == name.lexeme).cloned()
+            }
+            _ => None,
+        };
+        if let (Some((_, Some(item_type))), Some((arg, actual))) = (item, payload) {
+            let expected = self.annotation(&item_type);
+            if !fits(&expected, &actual) {
+                let at = arg.token().unwrap_or(parent).clone();
+                self.mismatch(E0x301, &at, &item_type, &actual);
+            }
+        }
+        named(Enum, &parent.lexeme)
+    }
+
+    /// checks arms of the match, `body` checks their bodies and returns their types,
+    /// uncovered values are reported at `at`
+    fn matchs<T>(
+        &mut self,
+        cond: &Expression,
+        cases: &[MatchCase<T>],
+        at: Option<Token>,
+        mut body: impl FnMut(&mut Self, &T) -> TypeKind,
+    ) -> Vec<TypeKind> {
+        let kind = widen(&self.expr(cond));
+        let mut kinds = vec![];
+        for case in cases {
+            self.scope_start();
+            self.pattern(&case.pattern, &kind);
+            if let Some(guard) = &case.guard {
+                self.expr(guard);
+            }
+            kinds.push(body(self, &case.body));
+            self.scope_end();
+        }
+        if let (Some(missing), Some(at)) = (self.missing(&kind, cases), at) {
+            self.report(E0x316, &at, vec![missing]);
+        }
+        kinds
+    }
+
+    /// defines names of the pattern with types of the matched parts, literals must fit the value
+    fn pattern(&mut self, pattern: &Pattern, kind: &TypeKind) {
+        match pattern {
+            Pattern::Wildcard { .. } => {}
+            Pattern::Bind { name } => self.define(name, kind.clone()),
+            Pattern::Value { token, value }
+            | Pattern::Range {
+                token, lhs: value, ..
+            } => {
+                let actual = widen(&value_type(value));
+                let is_known = is_scalar(kind) || is(kind, StrIdent);
+                // ints and floats are compared by their values
+                if is_known && !fits(kind, &actual) && !(is_number(kind) && is_number(&actual)) {
+                    let args = vec![type_name(kind), type_name(&actual)];
+                    self.report(E0x301, token, args);
+                }
+            }
+            Pattern::Enum {
+                parent,
+                name,
+                payload,
+            } => {
+                if let TypeKind::Var { name: kind_name } = kind {
+                    if kind_name.token == Enum && kind_name.lexeme != parent.lexeme {
+                        let args = vec![type_name(kind), parent.lexeme.clone()];
+                        self.report(E0x301, parent, args);
+                    }
+                }
+                let item_type = self
+                    .enums
+                    .get(&parent.lexeme)
+                    .and_then(|items| items.iter().find(|(i, _)| i.lexeme == name.lexeme))
+                    .and_then(|(_, item_type)| item_type.clone());
+                if let Some(payload) = payload {
+                    let payload_kind = match item_type {
+                        Some(item_type) => widen(&self.annotation(&item_type)),
+                        None => any(),
+                    };
+                    self.pattern(payload, &payload_kind);
+                }
+            }
+            Pattern::Tuple { items, .. } => {
+                for (idx, item) in items.iter().enumerate() {
+                    let item_kind = match kind {
+                        TypeKind::Tuple { types } => types.get(idx).cloned().unwrap_or(any()),
+                        _ => any(),
+                    };
+                    self.pattern(item, &item_kind);
+                }
+            }
+            Pattern::Vec { items, .. } => {
+                let item_kind = match kind {
+                    TypeKind::Vec { kind } => *kind.clone(),
+                    _ => any(),
+                };
+                items.iter().for_each(|item| self.pattern(item, &item_kind));
+            }
+            Pattern::Record { fields, .. } => {
+                for (name, field) in fields {
+                    let field_kind = match kind {
+                        TypeKind::Record { fields } => fields
+                            .iter()
+                            .find(|(n, _)| n.lexeme == name.lexeme)
+                            .map_or(any(), |(_, kind)| kind.clone()),
+                        _ => any(),
+                    };
+                    self.pattern(field, &field_kind);
+                }
+            }
+            Pattern::Or { patterns } => {
+                patterns
+                    .iter()
+                    .for_each(|pattern| self.pattern(pattern, kind));
+            }
+        }
+    }
+
+    /// first value not covered by unguarded arms, only values of known types are checked
+    fn missing<T>(&self, kind: &TypeKind, cases: &[MatchCase<T>]) -> Option<String> {
+        let patterns: Vec<&Pattern> = cases
+            .iter()
+            .filter(|case| case.guard.is_none())
+            .flat_map(|case| alternatives(&case.pattern))
+            .collect();
+        if patterns.iter().any(|pattern| pattern.is_irrefutable()) {
+            return None;
+        }
+        match kind {
+            TypeKind::Var { name } if name.token == Enum => {
+                let items = self.enums.get(&name.lexeme)?;
+                let is_covered = |item: &Token| {
+                    patterns.iter().any(|pattern| {
+                        matches!(pattern, Pattern::Enum { name, payload, .. }
+                            if name.lexeme == item.lexeme
+                                && payload.as_ref().is_none_or(|p| p.is_irrefutable()))
+                    })
+                };
+                items
+                    .iter()
+                    .find(|(item, _)| !is_covered(item))
+                    .map(|(item, _)| format!("{}::{}", name.lexeme, item.lexeme))
+            }
+            kind if is(kind, BoolIdent) => [true, false]
+                .into_iter()
+                .find(|b| {
+                    !patterns.iter().any(|pattern| {
+                        matches!(pattern, Pattern::Value { value, .. } if **value == LiteralType::Boolean(*b))
+                    })
+                })
+                .map(|b| b.to_string()),
+            TypeKind::Vec { .. } => {
+                // `[]` and `[x, ..]` cover vectors of every length
+                let lengths = |is_rest: bool| {
+                    patterns.iter().filter_map(move |pattern| match pattern {
+                        Pattern::Vec {
+                            items, has_rest, ..
+                        } if *has_rest == is_rest && items.iter().all(|i| i.is_irrefutable()) => {
+                            Some(items.len())
+                        }
+                        _ => None,
+                    })
+                };
+                let is_covered = lengths(true)
+                    .min()
+                    .is_some_and(|rest| (0..rest).all(|len| lengths(false).any(|l| l == len)));
+                (!is_covered).then(|| "_".to_string())
+            }
+            TypeKind::Tuple { .. }
+            | TypeKind::Map { .. }
+            | TypeKind::Record { .. } => Some("_".to_string()),
+            kind if is_scalar(kind) || is(kind, StrIdent) => Some("_".to_string()),
+            _ => None,
+        }
+    }
+
+    /// converts the type annotation to its type, aliases and records are replaced with their values
+    fn annotation(&self, token: &Token) -> TypeKind {
+        match &token.value {
+            Some(LiteralKind::Type(t)) => self.kind(t),
+            _ => self.base(token),
+        }
+    }
+
+    fn kind(&self, kind: &TypeKind) -> TypeKind {
+        match kind {
+            TypeKind::Var { name } => match name.value {
+                Some(_) => self.annotation(name),
+                None => self.base(name),
+            },
+            TypeKind::Vec { kind } => TypeKind::Vec {
+                kind: Box::new(self.kind(kind)),
+            },
+            TypeKind::Map { key, value } => TypeKind::Map {
+                key: Box::new(self.kind(key)),
+                value: Box::new(self.kind(value)),
+            },
+            TypeKind::Tuple { types } => TypeKind::Tuple {
+                types: types.iter().map(|t| self.kind(t)).collect(),
+            },
+            TypeKind::Record { fields } => TypeKind::Record {
+                fields: fields
+                    .iter()
+                    .map(|(name, t)| (name.clone(), self.kind(t)))
+                    .collect(),
+            },
+            TypeKind::Either { lhs, rhs } => TypeKind::Either {
+                lhs: Box::new(self.kind(lhs)),
+                rhs: Box::new(self.kind(rhs)),
+            },
+            TypeKind::Maybe { lhs } => TypeKind::Maybe {
+                lhs: Box::new(self.kind(lhs)),
+            },
+            TypeKind::Important { lhs } => self.kind(lhs),
+            TypeKind::Callback { .. } => named(FuncIdent, "function"),
+            TypeKind::Literal { kind } => TypeKind::Literal { kind: kind.clone() },
+            // generic alias without arguments
+            TypeKind::Generic { params, value } => {
+                let bindings = params
+                    .iter()
+                    .map(|p| (p.lexeme.clone(), any_type()))
+                    .collect();
+                self.annotation(&instantiate(value, &bindings))
+            }
+            TypeKind::Instance { name, args } => match self.types.get(&name.lexeme) {
+                Some(alias) => {
+                    let args: Vec<TypeKind> = args
+                        .iter()
+                        .map(|arg| TypeKind::Var {
+                            name: type_token(self.kind(arg)),
+                        })
+                        .collect();
+                    let value = generic_value(alias, &args);
+                    // `type a<T> = a<T>;` can't be resolved
+                    let is_recursive = match &value.value {
+                        Some(LiteralKind::Type(t)) => {
+                            matches!(t.as_ref(), TypeKind::Instance { name: n, .. } if n.lexeme == name.lexeme)
+                        }
+                        _ => false,
+                    };
+                    match is_recursive {
+                        true => any(),
+                        false => self.annotation(&value),
+                    }
+                }
+                None => self.base(name),
+            },
+        }
+    }
+
+    fn base(&self, token: &Token) -> TypeKind {
+        match token.token {
+            NumIdent => named(NumIdent, "number"),
+            IntIdent => named(IntIdent, "int"),
+            FloatIdent => named(FloatIdent, "float"),
+            StrIdent => named(StrIdent, "string"),
+            CharIdent => named(CharIdent, "char"),
+            BoolIdent => named(BoolIdent, "bool"),
+            Null => named(Null, "null"),
+            VoidIdent => named(VoidIdent, "void"),
+            FuncIdent => named(FuncIdent, "function"),
+            Ident if self.enums.contains_key(&token.lexeme) => named(Enum, &token.lexeme),
+            Ident => match self.types.get(&token.lexeme) {
+                // `type a = a;` can't be resolved
+                Some(alias) if !(alias.lexeme == token.lexeme && alias.token == token.token) => {
+                    self.annotation(alias)
+                }
+                _ => any(),
+            },
+            _ => any(),
+        }
+    }
+
+    /// name of the annotation in errors, aliases, records and enums keep their names
+    fn expected_name(&self, token: &Token) -> String {
+        match token.token {
+            Ident => token.lexeme.clone(),
+            _ => display(&self.annotation(token)),
+        }
+    }
+
+    fn report_call(&self, code: ErrorCode, at: &Token, func: &Signature, args: Vec<String>) {
+        let note = format!("function '{}' is defined here", func.name.lexeme);
+        let diagnostic =
+            self.diagnostic(code, at, args)
+                .related(func.name.line, func.name.pos, note);
+        self.err.push(diagnostic);
+    }
+
+    /// diagnostic of the error code, found before the execution
+    fn diagnostic(&self, code: ErrorCode, at: &Token, args: Vec<String>) -> Diagnostic {
+        let mut diagnostic = self.err.diagnostic(code, at.line, at.pos, args);
+        diagnostic.kind = "check";
+        diagnostic
+    }
+
+    fn report(&self, code: ErrorCode, at: &Token, args: Vec<String>) {
+        self.err.push(self.diagnostic(code, at, args));
+    }
+
+    fn mismatch(&self, code: ErrorCode, at: &Token, expected: &Token, actual: &TypeKind) {
+        let args = vec![self.expected_name(expected), type_name(actual)];
+        self.report(code, at, args);
+    }
+
+    fn define_func(&mut self, func: Signature) {
+        let symbol = Symbol {
+            kind: named(FuncIdent, "function"),
+            func: Some(func.clone()),
+        };
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(func.name.lexeme.clone(), symbol);
+    }
+
+    fn define(&mut self, name: &Token, kind: TypeKind) {
+        let symbol = Symbol { kind, func: None };
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.lexeme.clone(), symbol);
+    }
+
+    fn lookup(&self, name: &str) -> Option<Symbol> {
+        self.scopes.iter().rev().find_map(|s| s.get(name)).cloned()
+    }
+
+    fn scope_start(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn scope_end(&mut self) {
+        self.scopes.pop();
+    }
+}
+
+fn named(token: TokenType, name: &str) -> TypeKind {
+    TypeKind::Var {
+        name: Token::empty(token, name, None),
+    }
+}
+
+fn any() -> TypeKind {
+    named(AnyIdent, "any")
+}
+
+/// annotation of the inferred type
+fn type_token(kind: TypeKind) -> Token {
+    Token::empty(
+        Type,
+        &display(&kind),
+        Some(LiteralKind::Type(Box::new(kind))),
+    )
+}
+
+/// binds type parameters of the annotation to the inferred type of the argument,
+/// the first known type of the parameter wins
+fn bind(token: &Token, actual: &TypeKind, params: &[Token], bindings: &mut HashMap<String, Token>) {
+    if token.token == Ident && params.iter().any(|p| p.lexeme == token.lexeme) {
+        if !is(actual, AnyIdent) && !bindings.contains_key(&token.lexeme) {
+            bindings.insert(token.lexeme.clone(), type_token(widen(actual)));
+        }
+        return;
+    }
+    let Some(LiteralKind::Type(t)) = &token.value else {
+        return;
+    };
+    let mut bind_kind = |kind: &TypeKind, actual: &TypeKind| {
+        if let TypeKind::Var { name } = kind {
+            bind(name, actual, params, bindings);
+        }
+    };
+    match (t.as_ref(), actual) {
+        (TypeKind::Vec { kind }, TypeKind::Vec { kind: a }) => bind_kind(kind, a),
+        (TypeKind::Map { key, value }, TypeKind::Map { key: k, value: v }) => {
+            bind_kind(key, k);
+            bind_kind(value, v);
+        }
+        (TypeKind::Tuple { types }, TypeKind::Tuple { types: a }) => {
+            types.iter().zip(a).for_each(|(kind, a)| bind_kind(kind, a))
+        }
+        (TypeKind::Record { fields }, TypeKind::Record { fields: a }) => {
+            for (name, kind) in fields {
+                if let Some((_, a)) = a.iter().find(|(n, _)| n.lexeme == name.lexeme) {
+                    bind_kind(kind, a);
+                }
+            }
+        }
+        (TypeKind::Maybe { lhs }, TypeKind::Maybe { lhs: a }) => bind_kind(lhs, a),
+        (TypeKind::Maybe { lhs }, a) if !is(a, Null) => bind_kind(lhs, a),
+        _ => {}
+    }
+}
+
+fn is(kind: &TypeKind, token: TokenType) -> bool {
+    matches!(kind, TypeKind::Var { name } if name.token == token)
+}
+
+fn is_number(kind: &TypeKind) -> bool {
+    is(kind, NumIdent) || is(kind, IntIdent) || is(kind, FloatIdent)
+}
+
+/// single values, they can't be iterated
+fn is_scalar(kind: &TypeKind) -> bool {
+    let scalars = [BoolIdent, CharIdent, Null, VoidIdent, FuncIdent];
+    is_number(kind) || scalars.into_iter().any(|token| is(kind, token))
+}
+
+/// type of the literal value, strings with interpolations aren't known before the execution
+fn value_type(value: &LiteralType) -> TypeKind {
+    match value {
+        LiteralType::String(s) if s.contains('{') => named(StrIdent, "string"),
+        LiteralType::Int(_)
+        | LiteralType::Float(_)
+        | LiteralType::String(_)
+        | LiteralType::Char(_)
+        | LiteralType::Boolean(_) => TypeKind::Literal {
+            kind: literal_kind(value),
+        },
+        LiteralType::Null => named(Null, "null"),
+        LiteralType::Void => named(VoidIdent, "void"),
+        _ => any(),
+    }
+}
+
+fn literal_kind(value: &LiteralType) -> LiteralKind {
+    match value {
+        LiteralType::Int(n) => LiteralKind::Int {
+            base: Base::Decimal,
+            value: *n,
+        },
+        LiteralType::Float(n) => LiteralKind::Float { value: *n },
+        LiteralType::String(s) => LiteralKind::String {
+            value: s.to_string(),
+        },
+        LiteralType::Char(c) => LiteralKind::Char { value: *c },
+        LiteralType::Boolean(b) => LiteralKind::Bool { value: *b },
+        _ => LiteralKind::Null,
+    }
+}
+
+/// replaces literal types with their base types, `5` is a `number`
+fn widen(kind: &TypeKind) -> TypeKind {
+    match kind {
+        TypeKind::Literal { kind } => match kind {
+            LiteralKind::Int { .. } => named(IntIdent, "int"),
+            LiteralKind::Float { .. } => named(FloatIdent, "float"),
+            LiteralKind::String { .. } => named(StrIdent, "string"),
+            LiteralKind::Char { .. } => named(CharIdent, "char"),
+            LiteralKind::Bool { .. } => named(BoolIdent, "bool"),
+            LiteralKind::Null => named(Null, "null"),
+            LiteralKind::Type(_) => any(),
+        },
+        TypeKind::Vec { kind } => TypeKind::Vec {
+            kind: Box::new(widen(kind)),
+        },
+        TypeKind::Map { key, value } => TypeKind::Map {
+            key: Box::new(widen(key)),
+            value: Box::new(widen(value)),
+        },
+        TypeKind::Tuple { types } => TypeKind::Tuple {
+            types: types.iter().map(widen).collect(),
+        },
+        TypeKind::Record { fields } => TypeKind::Record {
+            fields: fields
+                .iter()
+                .map(|(name, kind)| (name.clone(), widen(kind)))
+                .collect(),
+        },
+        TypeKind::Either { lhs, rhs } => TypeKind::Either {
+            lhs: Box::new(widen(lhs)),
+            rhs: Box::new(widen(rhs)),
+        },
+        kind => kind.clone(),
+    }
+}
+
+/// alternatives of the pattern, other patterns are their only alternative
+fn alternatives(pattern: &Pattern) -> Vec<&Pattern> {
+    match pattern {
+        Pattern::Or { patterns } => patterns.iter().flat_map(alternatives).collect(),
+        pattern => vec![pattern],
+    }
+}
+
+/// items of different types make the type of either
+fn union(kinds: Vec<TypeKind>) -> TypeKind {
+    kinds
+        .into_iter()
+        .reduce(|lhs, rhs| match same(&lhs, &rhs) {
+            true => lhs,
+            false => TypeKind::Either {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+        })
+        .unwrap_or(any())
+}
+
+fn same(lhs: &TypeKind, rhs: &TypeKind) -> bool {
+    fits(lhs, rhs) && fits(rhs, lhs)
+}
+
+/// infers the type of the binary operation, operands are widened; operands that
+/// no operator takes give the expected and actual types of the mismatch
+fn binary(
+    left: &TypeKind,
+    operator: &TokenType,
+    right: &TypeKind,
+) -> Result<TypeKind, Vec<String>> {
+    let is_known = |k: &TypeKind| {
+        !is(k, AnyIdent) && !matches!(k, TypeKind::Either { .. } | TypeKind::Maybe { .. })
+    };
+    let kind = match operator {
+        Eq | BangEq | Gr | GrOrEq | Ls | LsOrEq => named(BoolIdent, "bool"),
+        Or | DblAnd if same(left, right) => left.clone(),
+        Plus | Min | Mul | Div | Prcnt | Sqr if is_number(left) && is_number(right) => {
+            // ints stay ints, floats are contagious
+            if is(left, NumIdent) || is(right, NumIdent) {
+                named(NumIdent, "number")
+            } else if is(left, IntIdent) && is(right, IntIdent) {
+                named(IntIdent, "int")
+            } else {
+                named(FloatIdent, "float")
+            }
+        }
+        And | Pipe | Caret | Shl | Shr if is(left, IntIdent) && is(right, IntIdent) => left.clone(),
+        Plus if is(left, StrIdent) && is(right, StrIdent) => left.clone(),
+        Plus | Min | Mul | Div if is_number(left) && matches!(right, TypeKind::Vec { .. }) => {
+            right.clone()
+        }
+        // no operator takes the operands, the one of the wrong type is reported
+        Plus | Min | Mul | Div | Prcnt | Sqr | And | Pipe | Caret | Shl | Shr
+            if is_known(left) && is_known(right) =>
+        {
+            let (expected, valid): (&str, fn(&TypeKind) -> bool) = match operator {
+                And | Pipe | Caret | Shl | Shr => ("int", |k| is(k, IntIdent)),
+                Plus if is(left, StrIdent) || is(right, StrIdent) => {
+                    ("string", |k| is(k, StrIdent))
+                }
+                _ => ("number", is_number),
+            };
+            let got = if valid(left) { right } else { left };
+            return Err(vec![expected.to_string(), type_name(got)]);
+        }
+        _ => any(),
+    };
+    Ok(kind)
+}
+
+/// true if the value of `actual` type can be used where `expected` is declared,
+/// unknown types always fit
+fn fits(expected: &TypeKind, actual: &TypeKind) -> bool {
+    match (expected, actual) {
+        (e, a) if is(e, AnyIdent) || is(a, AnyIdent) || is(e, FuncIdent) => true,
+        (TypeKind::Either { lhs, rhs }, a) => fits(lhs, a) || fits(rhs, a),
+        (TypeKind::Maybe { lhs }, a) => is(a, Null) || fits(lhs, a),
+        // every type of the value must fit
+        (e, TypeKind::Either { lhs, rhs }) => fits(e, lhs) && fits(e, rhs),
+        (e, TypeKind::Maybe { lhs }) => fits(e, &named(Null, "null")) && fits(e, lhs),
+        (TypeKind::Vec { kind: e }, TypeKind::Vec { kind: a }) => fits(e, a),
+        (TypeKind::Map { key: ek, value: ev }, TypeKind::Map { key: ak, value: av }) => {
+            fits(ek, ak) && fits(ev, av)
+        }
+        (TypeKind::Tuple { types: e }, TypeKind::Tuple { types: a }) => {
+            e.len() == a.len() && e.iter().zip(a).all(|(e, a)| fits(e, a))
+        }
+        (TypeKind::Record { fields: e }, TypeKind::Record { fields: a }) => {
+            e.iter().all(|(name, e)| {
+                a.iter()
+                    .find(|(n, _)| n.lexeme == name.lexeme)
+                    .is_some_and(|(_, a)| fits(e, a))
+            })
+        }
+        (TypeKind::Literal { kind: e }, TypeKind::Literal { kind: a }) => {
+            literalkind_to_literaltype(e.clone()) == literalkind_to_literaltype(a.clone())
+        }
+        // value of the literal type isn't known
+        (TypeKind::Literal { .. }, a) => same(&widen(expected), a),
+        (e, TypeKind::Literal { .. }) => fits(e, &widen(actual)),
+        // `number` takes both ints and floats
+        (e, a) if is(e, NumIdent) && is_number(a) => true,
+        (TypeKind::Var { name: e }, TypeKind::Var { name: a }) => {
+            e.token == a.token && (e.token != Enum || e.lexeme == a.lexeme)
+        }
+        _ => false,
+    }
+}
+
+/// written form of the type, as it's declared
+fn display(kind: &TypeKind) -> String {
+    let list = |kinds: Vec<String>| kinds.join(", ");
+    match kind {
+        TypeKind::Var { name } => name.lexeme.clone(),
+        TypeKind::Vec { kind } => format!("Vec<{}>", display(kind)),
+        TypeKind::Map { key, value } => format!("Map<{}, {}>", display(key), display(value)),
+        TypeKind::Tuple { types } => {
+            format!("Tuple<({})>", list(types.iter().map(display).collect()))
+        }
+        TypeKind::Record { fields } => format!(
+            "Record<{{{}}}>",
+            list(
+                fields
+                    .iter()
+                    .map(|(name, kind)| format!("{}: {}", name.lexeme, display(kind)))
+                    .collect()
+            )
+        ),
+        TypeKind::Either { lhs, rhs } => format!("{} || {}", display(lhs), display(rhs)),
+        TypeKind::Maybe { lhs } => format!("{}?", display(lhs)),
+        TypeKind::Literal { kind } => match kind {
+            LiteralKind::String { value } => format!("\"{}\"", value),
+            LiteralKind::Char { value } => format!("'{}'", value),
+            kind => literalkind_to_literaltype(kind.clone()).to_string(),
+        },
+        _ => "any".to_string(),
+    }
+}
+
+/// name of the inferred type in errors, values of literal types aren't shown
+fn type_name(kind: &TypeKind) -> String {
+    display(&widen(kind))
+}
@@ -0,0 +1,30 @@
+// commands of the `absurd` binary, the binary is built on this API and doesn't reach other modules
+use crate::{bundler, errors::Error, repl::Repl, Config};
+
+pub use crate::errors::{explain, log};
+pub use crate::linter::rules::{Lint, Rule};
+
+/// runs the source, `absurd run`
+pub fn run(src: &str, config: Config) {
+    bundler::interpreter(src, config);
+}
+
+/// checks types of the source without running it, `absurd check`
+pub fn check(src: &str) {
+    bundler::checker(src, Error::new(src));
+}
+
+/// the formatted source, `absurd format`
+pub fn format(src: &str) -> String {
+    bundler::formatter(src, Error::new(src))
+}
+
+/// lints of the source reported by the enabled rules, `absurd lint`
+pub fn lint(src: &str, rules: Vec<Rule>) -> Vec<Lint> {
+    bundler::linter(src, Error::new(src), rules)
+}
+
+/// starts the interactive mode, `absurd repl`
+pub fn repl(config: Config) {
+    Repl::new(config).run();
+}
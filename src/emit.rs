@@ -0,0 +1,547 @@
+// Absurd emitter, it dumps stages of the pipeline for debugging (`--emit tokens|ast|env`)
+use crate::ast::{LiteralKind, LiteralType, MatchCase, Statement, Token, TokenType};
+use crate::formatter::{generics_src, pattern_src, type_src};
+use crate::interpreter::env::{Env, ValueKind, ValueType};
+use crate::interpreter::expr::Expression;
+use std::collections::HashMap;
+
+const INDENT: &str = "  ";
+
+/// dumps scanned tokens, one per line with their position
+pub fn tokens(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    for token in tokens {
+        let value = match &token.value {
+            Some(LiteralKind::Type(_)) => format!(" = {}", type_src(token)),
+            Some(LiteralKind::Int { value, .. }) => format!(" = {}", value),
+            Some(LiteralKind::Float { value }) => format!(" = {}", LiteralType::Float(*value)),
+            Some(LiteralKind::String { value }) => format!(" = {:?}", value),
+            Some(LiteralKind::Char { value }) => format!(" = {:?}", value),
+            Some(LiteralKind::Bool { value }) => format!(" = {}", value),
+            Some(LiteralKind::Null) | None => String::new(),
+        };
+        out.push_str(&format!(
+            "{}:{}-{}\t{:?} {:?}{}\n",
+            token.line, token.pos.0, token.pos.1, token.token, token.lexeme, value
+        ));
+    }
+    out
+}
+
+/// dumps statements as an indented tree
+pub fn ast(stmts: &[Statement]) -> String {
+    let mut tree = Tree { out: String::new() };
+    stmts.iter().for_each(|s| tree.stmt(s, 0));
+    tree.out
+}
+
+/// dumps the global environment after the execution
+pub fn env(env: &Env) -> String {
+    let mut out = String::new();
+    out.push_str(&values("values", &env.values));
+    out.push_str(&values("pub_vals", &env.pub_vals));
+
+    out.push_str("type_values:\n");
+    for (name, value) in sorted(&env.type_values) {
+        out.push_str(&format!("{}type {} = {}\n", INDENT, name, type_src(value)));
+    }
+
+    out.push_str("enums:\n");
+    for (name, items) in sorted(&env.enums) {
+        out.push_str(&format!("{}enum {} {{ {} }}\n", INDENT, name, enum_items(items)));
+    }
+
+    out.push_str("mod_vals:\n");
+    for (src, vals) in sorted(&env.mod_vals) {
+        out.push_str(&format!("{}{}:\n", INDENT, src));
+        for (name, value) in vals {
+            out.push_str(&format!("{}{}{}\n", INDENT, INDENT, value_src(name, value)));
+        }
+    }
+    out
+}
+
+fn values(head: &str, values: &HashMap<String, ValueType>) -> String {
+    let mut out = format!("{}:\n", head);
+    for (name, value) in sorted(values) {
+        out.push_str(&format!("{}{}\n", INDENT, value_src(name, value)));
+    }
+    out
+}
+
+fn value_src(name: &str, value: &ValueType) -> String {
+    match &value.kind {
+        ValueKind::Var(v) => {
+            let modifier = match (v.is_pub, v.is_mut) {
+                (true, _) => "pub ",
+                (_, true) => "mut ",
+                _ => "",
+            };
+            let value_type = format!(": {}", type_name(&v.value_type));
+            format!("let {}{}{} = {}", modifier, name, value_type, literal(&value.value))
+        }
+        ValueKind::Func(f) => {
+            let params: Vec<String> = f
+                .params
+                .iter()
+                .map(|(name, typ)| format!("{}: {}", name, typ))
+                .collect();
+            let modifier = if f.is_async { "async " } else { "" };
+            format!("{}func {}({})", modifier, name, params.join(", "))
+        }
+        ValueKind::Type(t) => format!("type {} = {}", name, type_src(t)),
+        ValueKind::Enum(items) => format!("enum {} {{ {} }}", name, enum_items(items)),
+    }
+}
+
+fn enum_items(items: &[(Token, Option<Token>)]) -> String {
+    let items: Vec<String> = items
+        .iter()
+        .map(|(name, value)| match value {
+            Some(t) => format!("{}({})", name.lexeme, type_src(t)),
+            None => name.lexeme.clone(),
+        })
+        .collect();
+    items.join(", ")
+}
+
+/// literal with quoted strings and chars, so empty strings stay visible
+fn literal(value: &LiteralType) -> String {
+    match value {
+        LiteralType::String(s) => format!("{:?}", s),
+        LiteralType::Char(c) => format!("{:?}", c),
+        LiteralType::Func(f) => format!("func {}", f.name),
+        v => v.to_string(),
+    }
+}
+
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<(&String, &V)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+struct Tree {
+    out: String,
+}
+
+impl Tree {
+    fn node(&mut self, depth: usize, text: String) {
+        self.out.push_str(&INDENT.repeat(depth));
+        self.out.push_str(&text);
+        self.out.push('\n');
+    }
+
+    fn stmt(&mut self, stmt: &Statement, depth: usize) {
+        let d = depth + 1;
+        match stmt {
+            Statement::Expression { expr } => {
+                self.node(depth, "Expression".to_string());
+                self.expr(expr, d);
+            }
+            Statement::Block { stmts } => {
+                self.node(depth, "Block".to_string());
+                stmts.iter().for_each(|s| self.stmt(s, d));
+            }
+            Statement::Var {
+                names,
+                value_type,
+                value,
+                is_mut,
+                is_pub,
+                pub_names,
+                destruct,
+                ..
+            } => {
+                let mut head = format!("Var {}{}", names_src(names), at(names.first()));
+                if *is_mut {
+                    head.push_str(" mut");
+                }
+                if *is_pub {
+                    head.push_str(&format!(" pub({})", names_src(pub_names)));
+                }
+                if let Some(destruct) = destruct {
+                    head.push_str(&format!(" destruct: {:?}", destruct));
+                }
+                head.push_str(&format!(" type: {}", type_name(value_type)));
+                self.node(depth, head);
+                if let Some(value) = value {
+                    self.expr(value, d);
+                }
+            }
+            Statement::Func {
+                name,
+                generics,
+                value_type,
+                body,
+                params,
+                is_async,
+                is_pub,
+            } => {
+                let head = format!(
+                    "Func {}{}({}) -> {}{}{}{}",
+                    name.lexeme,
+                    generics_src(generics),
+                    params_src(params),
+                    type_src(value_type),
+                    if *is_async { " async" } else { "" },
+                    if *is_pub { " pub" } else { "" },
+                    at(Some(name)),
+                );
+                self.node(depth, head);
+                self.stmt(body, d);
+            }
+            Statement::Impl { name, funcs } => {
+                self.node(depth, format!("Impl {}{}", name.lexeme, at(Some(name))));
+                funcs.iter().for_each(|f| self.stmt(f, d));
+            }
+            Statement::If {
+                cond,
+                body,
+                else_branch,
+            } => {
+                self.node(depth, "If".to_string());
+                self.expr(cond, d);
+                self.stmt(body, d);
+                if let Some(branch) = else_branch {
+                    self.node(d, "Else".to_string());
+                    self.stmt(branch, d + 1);
+                }
+            }
+            Statement::Return { expr, .. } => {
+                self.node(depth, "Return".to_string());
+                self.expr(expr, d);
+            }
+            Statement::For {
+                iterator,
+                index,
+                expr,
+                body,
+                label,
+            } => {
+                let index = index
+                    .as_ref()
+                    .map_or(String::new(), |i| format!(", {}", i.lexeme));
+                self.node(
+                    depth,
+                    format!(
+                        "For {}{}{}{}",
+                        iterator.lexeme,
+                        index,
+                        at(Some(iterator)),
+                        loop_label(label)
+                    ),
+                );
+                self.expr(expr, d);
+                self.stmt(body, d);
+            }
+            Statement::While { cond, body, label } => {
+                self.node(depth, format!("While{}", loop_label(label)));
+                self.expr(cond, d);
+                self.stmt(body, d);
+            }
+            Statement::Break { label, .. } => {
+                self.node(depth, format!("Break{}", jump_label(label)))
+            }
+            Statement::Continue { label, .. } => {
+                self.node(depth, format!("Continue{}", jump_label(label)))
+            }
+            Statement::Match { cond, cases } => {
+                self.node(depth, "Match".to_string());
+                self.expr(cond, d);
+                self.cases(cases, d, |emit, body, depth| emit.stmt(body, depth));
+            }
+            Statement::Enum {
+                name,
+                is_pub,
+                items,
+            } => self.node(
+                depth,
+                format!(
+                    "Enum {}{}{} {{ {} }}",
+                    name.lexeme,
+                    at(Some(name)),
+                    if *is_pub { " pub" } else { "" },
+                    enum_items(items)
+                ),
+            ),
+            Statement::Type {
+                name,
+                generics,
+                is_pub,
+                value,
+            } => self.node(
+                depth,
+                format!(
+                    "Type {}{}{}{} = {}",
+                    name.lexeme,
+                    generics_src(generics),
+                    at(Some(name)),
+                    if *is_pub { " pub" } else { "" },
+                    type_src(value)
+                ),
+            ),
+            Statement::Record {
+                name,
+                generics,
+                extends,
+                is_strict,
+                fields,
+            } => {
+                let mut head = format!(
+                    "Record {}{}{}",
+                    name.lexeme,
+                    generics_src(generics),
+                    at(Some(name))
+                );
+                if !extends.is_empty() {
+                    head.push_str(&format!(" extends {}", names_src(extends)));
+                }
+                if *is_strict {
+                    head.push_str(" strict");
+                }
+                self.node(depth, head);
+                for field in fields {
+                    let mut text = format!("Field {}", field.name.lexeme);
+                    if field.is_optional {
+                        text.push('?');
+                    }
+                    if field.is_strict {
+                        text.push('!');
+                    }
+                    text.push_str(&format!(": {}", type_src(&field.value)));
+                    self.node(d, text);
+                    if let Some(value) = &field.default_value {
+                        self.expr(value, d + 1);
+                    }
+                }
+            }
+            Statement::Mod { src, name } => {
+                let name = name.as_ref().map_or(String::new(), |n| format!(" as {}", n));
+                self.node(depth, format!("Mod {}{}", src, name));
+            }
+            Statement::Use {
+                src, names, all, ..
+            } => {
+                let names: Vec<String> = names
+                    .iter()
+                    .map(|(old, new)| match new {
+                        Some(new) => format!("{} as {}", old.lexeme, new.lexeme),
+                        None => old.lexeme.clone(),
+                    })
+                    .collect();
+                let all = if *all { " *" } else { "" };
+                self.node(
+                    depth,
+                    format!("Use {}{} from {}", names.join(", "), all, src),
+                );
+            }
+            Statement::Sh { cmd } => self.node(depth, format!("Sh {:?}", cmd)),
+        }
+    }
+
+    fn cases<T>(
+        &mut self,
+        cases: &[MatchCase<T>],
+        depth: usize,
+        mut body: impl FnMut(&mut Self, &T, usize),
+    ) {
+        for case in cases {
+            self.node(depth, "Case".to_string());
+            self.node(depth + 1, format!("Pattern {}", pattern_src(&case.pattern)));
+            if let Some(guard) = &case.guard {
+                self.node(depth + 1, "Guard".to_string());
+                self.expr(guard, depth + 2);
+            }
+            body(self, &case.body, depth + 1);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression, depth: usize) {
+        let d = depth + 1;
+        match expr {
+            Expression::Value { value, .. } => {
+                self.node(depth, format!("Value {}", literal(value)))
+            }
+            Expression::Var { name, .. } => {
+                self.node(depth, format!("Var {}{}", name.lexeme, at(Some(name))))
+            }
+            Expression::Assign {
+                name, value, kind, ..
+            } => {
+                self.node(
+                    depth,
+                    format!("Assign {} {:?}{}", name.lexeme, kind, at(Some(name))),
+                );
+                self.expr(value, d);
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.node(
+                    depth,
+                    format!("Binary {}{}", operator.lexeme, at(Some(operator))),
+                );
+                self.expr(left, d);
+                self.expr(right, d);
+            }
+            Expression::Unary { left, operator, .. } => {
+                self.node(
+                    depth,
+                    format!("Unary {}{}", operator.lexeme, at(Some(operator))),
+                );
+                self.expr(left, d);
+            }
+            Expression::Grouping { expression, .. } => {
+                self.node(depth, "Grouping".to_string());
+                self.expr(expression, d);
+            }
+            Expression::Call {
+                name,
+                args,
+                call_type,
+                ..
+            } => {
+                self.node(depth, format!("Call {:?}", call_type));
+                self.expr(name, d);
+                args.iter().for_each(|a| self.expr(a, d));
+            }
+            Expression::Vec { items, .. } => {
+                self.node(depth, "Vec".to_string());
+                items.iter().for_each(|i| self.expr(i, d));
+            }
+            Expression::Tuple { items, .. } => {
+                self.node(depth, "Tuple".to_string());
+                items.iter().for_each(|i| self.expr(i, d));
+            }
+            Expression::Map { items, .. } => {
+                self.node(depth, "Map".to_string());
+                for (key, value) in items {
+                    self.node(d, "Entry".to_string());
+                    self.expr(key, d + 1);
+                    self.expr(value, d + 1);
+                }
+            }
+            Expression::Record { fields, .. } => {
+                self.node(depth, "Record".to_string());
+                for (name, value) in fields {
+                    self.node(d, format!("Field {}", name));
+                    self.expr(value, d + 1);
+                }
+            }
+            Expression::If {
+                cond,
+                body,
+                else_branch,
+                ..
+            } => {
+                self.node(depth, "If".to_string());
+                self.expr(cond, d);
+                self.expr(body, d);
+                if let Some(branch) = else_branch {
+                    self.node(d, "Else".to_string());
+                    self.expr(branch, d + 1);
+                }
+            }
+            Expression::Match { cond, cases, .. } => {
+                self.node(depth, "Match".to_string());
+                self.expr(cond, d);
+                self.cases(cases, d, |emit, body, depth| emit.expr(body, depth));
+            }
+            Expression::Func {
+                value_type,
+                body,
+                params,
+                is_async,
+                ..
+            } => {
+                self.node(
+                    depth,
+                    format!(
+                        "Func ({}) -> {}{}",
+                        params_src(params),
+                        type_src(value_type),
+                        if *is_async { " async" } else { "" }
+                    ),
+                );
+                self.stmt(body, d);
+            }
+            Expression::Await { expr, .. } => {
+                self.node(depth, "Await".to_string());
+                self.expr(expr, d);
+            }
+            Expression::Range {
+                lhs,
+                rhs,
+                step,
+                is_inclusive,
+                ..
+            } => {
+                let dots = if *is_inclusive { "..=" } else { ".." };
+                self.node(depth, format!("Range {}", dots));
+                self.expr(lhs, d);
+                self.expr(rhs, d);
+                if let Some(step) = step {
+                    self.node(d, "Step".to_string());
+                    self.expr(step, d + 1);
+                }
+            }
+        }
+    }
+}
+
+/// type of the token, types inferred from literals are printed by their kind
+fn type_name(typ: &Token) -> String {
+    match (&typ.value, typ.line) {
+        (Some(LiteralKind::Int { .. }), 0) => "int".to_string(),
+        (Some(LiteralKind::Float { .. }), 0) => "float".to_string(),
+        (Some(LiteralKind::String { .. }), 0) => "string".to_string(),
+        (Some(LiteralKind::Char { .. }), 0) => "char".to_string(),
+        (Some(LiteralKind::Bool { .. }), 0) => "boolean".to_string(),
+        _ => type_src(typ),
+    }
+}
+
+/// position of the token, empty if it's unknown
+fn at(token: Option<&Token>) -> String {
+    match token {
+        Some(t) if t.line != 0 => format!(" @{}:{}", t.line, t.pos.0),
+        _ => String::new(),
+    }
+}
+
+/// ` label: name` of the labeled loop
+fn loop_label(label: &Option<Token>) -> String {
+    label
+        .as_ref()
+        .map_or(String::new(), |l| format!(" label: {}", l.lexeme))
+}
+
+/// ` name @line:col` of the `break` and `continue` target
+fn jump_label(label: &Option<Token>) -> String {
+    label
+        .as_ref()
+        .map_or(String::new(), |l| format!(" {}{}", l.lexeme, at(Some(l))))
+}
+
+fn names_src(names: &[Token]) -> String {
+    let names: Vec<&str> = names
+        .iter()
+        .map(|n| match n.token {
+            TokenType::Null => "_",
+            _ => n.lexeme.as_str(),
+        })
+        .collect();
+    names.join(", ")
+}
+
+fn params_src(params: &[(Token, Token)]) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .map(|(name, typ)| format!("{}: {}", name.lexeme, type_src(typ)))
+        .collect();
+    params.join(", ")
+}
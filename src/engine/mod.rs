@@ -0,0 +1,272 @@
+// Absurd engine, runs sources inside of the application and calls their functions from Rust
+pub mod native;
+pub mod value;
+
+use crate::{
+    ast::{LiteralType, Statement, Token, TokenType},
+    bundler::{check, parser},
+    checker::Checker,
+    errors::{captured, Error, ErrorCode, ErrorCode::*, Failure},
+    interpreter::{
+        call_func, check_arity,
+        env::{Env, VarKind},
+        expr::{call_native, Expression},
+        task::{self, Executor},
+        Interpreter,
+    },
+    repl::terminated,
+    resolver::Resolver,
+    std::{func, mod_func},
+    vm, Config,
+};
+use native::{typed, Native, NativeError, NativeFn, NativeFunc};
+use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+use value::{FromAbsurd, IntoAbsurd, Mismatch};
+
+/// `Engine` keeps the environment of the evaluated sources, names defined by one source
+/// are seen by the next ones. Errors of the sources are returned as `Failure`,
+/// they aren't printed and they don't exit the process. They are carried back
+/// by unwinding, so the crate can't be built with `panic = "abort"`.
+pub struct Engine {
+    config: Config,
+    env: Rc<RefCell<Env>>,
+    /// futures of the sources run on the executor of the engine
+    executor: Rc<RefCell<Executor>>,
+    /// global names declared by the sources, errors of `call` point at them
+    decls: HashMap<String, Token>,
+    /// modules imported by `use`, `use` takes the module out of the environment,
+    /// so modules are defined again before each source
+    modules: Vec<(String, Module)>,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Engine::with_config(Config::new())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let int = Interpreter::new(config.clone(), Error::new(""));
+        Engine {
+            config,
+            env: int.env,
+            executor: Rc::default(),
+            decls: HashMap::new(),
+            modules: vec![],
+        }
+    }
+
+    /// evaluates the source, returns the value of its trailing expression,
+    /// or `Void` if the source doesn't end with an expression
+    pub fn eval(&mut self, src: &str) -> Result<LiteralType, Failure> {
+        let src = terminated(src);
+        let err = Error::new(&src);
+        self.define_modules();
+        run(&Rc::clone(&self.executor), || {
+            let stmts = parser(&src, err.clone());
+            check(&err);
+            self.declare(&stmts);
+            let locals = Resolver::new(err.clone()).resolve(&stmts, &self.env);
+            check(&err);
+            Checker::new(err.clone()).check(&stmts);
+            check(&err);
+            self.env.borrow_mut().set_err(err.clone());
+            let (stmts, expr) = match stmts.split_last() {
+                Some((Statement::Expression { expr }, stmts))
+                    if !matches!(expr, Expression::Assign { .. }) =>
+                {
+                    (stmts, Some(expr))
+                }
+                _ => (stmts.as_slice(), None),
+            };
+            let mut int = Interpreter::new_with_env(Rc::clone(&self.env), false, "", None, 0);
+            int.project = self.config.clone();
+            // locals of the compiled code live in slots of the VM, as `bundler::run` does
+            if self.config.vm {
+                vm::run(&mut int, stmts);
+            } else {
+                self.env.borrow_mut().resolve(locals);
+                int.interpret(stmts.iter().collect(), 0);
+            }
+            let value = expr.map_or(LiteralType::Void, |expr| expr.eval(Rc::clone(&self.env)));
+            task::run();
+            value
+        })
+    }
+
+    /// reads the file and evaluates it as `eval` does
+    pub fn eval_file(&mut self, path: &str) -> Result<LiteralType, Failure> {
+        match fs::read_to_string(path) {
+            Ok(src) => self.eval(&src),
+            Err(e) => Err(Failure::Io(format!("failed to read '{}': {}", path, e))),
+        }
+    }
+
+    /// value of the global name, values of other types fail with E0x301
+    pub fn get<T: FromAbsurd>(&self, name: &str) -> Result<T, Failure> {
+        match self.env.borrow().get_int(name, None) {
+            Some(value) => T::from_absurd(value.value).map_err(mismatch),
+            None => Err(failure(E0x304, vec![name.to_string()])),
+        }
+    }
+
+    /// defines the global `mut` variable of the type `any`, or changes the defined one
+    pub fn set(&mut self, name: &str, value: impl IntoAbsurd) {
+        let kind = VarKind {
+            is_mut: true,
+            is_pub: false,
+            is_func: false,
+            value_type: Token::empty(TokenType::AnyIdent, "any", None),
+        };
+        self.env
+            .borrow_mut()
+            .define_var(name.to_string(), value.into_absurd(), kind);
+        self.decls.remove(name);
+    }
+
+    /// calls the global function with the arguments, futures of async functions are awaited
+    pub fn call<T: FromAbsurd>(
+        &mut self,
+        name: &str,
+        args: Vec<LiteralType>,
+    ) -> Result<T, Failure> {
+        let value = self.get(name)?;
+        // names defined by `set` aren't declared in the sources
+        let at = match self.decls.get(name) {
+            Some(decl) => decl.clone(),
+            None => Token::empty(TokenType::Ident, name, None),
+        };
+        run(&self.executor, || {
+            let value = match value {
+                LiteralType::Func(func) => {
+                    check_arity(&func, args.len(), &at, &self.env);
+                    call_func(func, args, Rc::clone(&self.env), &at, |_| None)
+                }
+                LiteralType::DeclrFunc(func) => call_native(&func, None, args, &at, &self.env),
+                value => {
+                    let args = vec!["function".to_string(), value.type_name()];
+                    self.env.borrow().err().throw(E0x301, at.line, at.pos, args)
+                }
+            };
+            let value = match value {
+                LiteralType::Future(future) => task::block_on(&future, &self.env, &at),
+                value => value,
+            };
+            task::run();
+            value
+        })
+        .and_then(|value| T::from_absurd(value).map_err(mismatch))
+    }
+
+    /// defines the native function called by sources as `name(args)`,
+    /// `Err(message)` of the function stops the source
+    pub fn register_fn(
+        &mut self,
+        name: &str,
+        arity: usize,
+        f: impl Fn(&[LiteralType]) -> Result<LiteralType, String> + 'static,
+    ) {
+        let native = Native::new(&self.env, arity, untyped(f));
+        func(name, arity, &mut self.env, Rc::new(native));
+    }
+
+    /// defines the Rust function as the native, its arity is the number of its parameters
+    /// and arguments of other types fail with E0x301
+    pub fn register<Args>(&mut self, name: &str, f: impl NativeFunc<Args> + 'static) {
+        let native = Native::typed(&self.env, f);
+        func(name, native.arity(), &mut self.env, Rc::new(native));
+    }
+
+    /// registers the module imported by `use name from "source"`
+    pub fn register_module(&mut self, source: &str, module: Module) {
+        self.modules.retain(|(src, _)| src != source);
+        self.modules.push((source.to_string(), module));
+    }
+
+    fn declare(&mut self, stmts: &[Statement]) {
+        for stmt in stmts {
+            match stmt {
+                Statement::Var { names, .. } => {
+                    for name in names {
+                        self.decls.insert(name.lexeme.clone(), name.clone());
+                    }
+                }
+                Statement::Func { name, .. } => {
+                    self.decls.insert(name.lexeme.clone(), name.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn define_modules(&mut self) {
+        for (source, module) in &self.modules {
+            let source = format!("\"{}\"", source);
+            self.env.borrow_mut().mod_vals.remove(&source);
+            for (name, arity, f) in &module.funcs {
+                let native = Native::new(&self.env, *arity, Rc::clone(f));
+                mod_func(&source, name, *arity, &self.env, Rc::new(native));
+            }
+        }
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine::new()
+    }
+}
+
+/// `Module` is the set of native functions, sources import them by
+/// `use name from "source"` once the module is registered
+#[derive(Clone, Default)]
+pub struct Module {
+    funcs: Vec<(String, usize, NativeFn)>,
+}
+
+impl Module {
+    pub fn new() -> Self {
+        Module::default()
+    }
+
+    /// adds the native function to the module, same as `Engine::register_fn`
+    pub fn func(
+        mut self,
+        name: &str,
+        arity: usize,
+        f: impl Fn(&[LiteralType]) -> Result<LiteralType, String> + 'static,
+    ) -> Self {
+        self.funcs.push((name.to_string(), arity, untyped(f)));
+        self
+    }
+
+    /// adds the Rust function to the module, same as `Engine::register`
+    pub fn native<Args>(mut self, name: &str, f: impl NativeFunc<Args> + 'static) -> Self {
+        let (arity, f) = typed(f);
+        self.funcs.push((name.to_string(), arity, f));
+        self
+    }
+}
+
+/// runs the function on the executor without exiting the process,
+/// tasks left running by the failure are cancelled
+fn run<T>(executor: &Rc<RefCell<Executor>>, f: impl FnOnce() -> T) -> Result<T, Failure> {
+    let result = task::enter(executor, || captured(f));
+    if result.is_err() {
+        task::cancel(executor);
+    }
+    result
+}
+
+fn untyped(f: impl Fn(&[LiteralType]) -> Result<LiteralType, String> + 'static) -> NativeFn {
+    Rc::new(move |args| f(&args).map_err(NativeError::Message))
+}
+
+/// failure of the single diagnostic, for errors found outside of the sources
+fn failure(code: ErrorCode, args: Vec<String>) -> Failure {
+    let diagnostic = Error::new("").diagnostic(code, 0, (0, 0), args);
+    Failure::Diagnostics(vec![diagnostic])
+}
+
+fn mismatch(Mismatch { expected, got }: Mismatch) -> Failure {
+    failure(E0x301, vec![expected, got])
+}
@@ -0,0 +1,145 @@
+// native functions of the application and of the standard library
+use super::value::{FromAbsurd, IntoAbsurd, Mismatch};
+use crate::{
+    ast::{FuncValType, LiteralType, Token},
+    errors::{raw, Error, ErrorCode::*},
+    interpreter::env::Env,
+};
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+/// closure of the native function, `Err` stops the source
+pub type NativeFn = Rc<dyn Fn(Vec<LiteralType>) -> Result<LiteralType, NativeError>>;
+
+pub enum NativeError {
+    /// reported as is, by the closures returning `Err(message)`
+    Message(String),
+    /// argument of the Rust function that can't be converted, reported as E0x301
+    Mismatch(Mismatch),
+    /// number of the arguments checked by the native itself, reported as E0x405
+    Arity,
+}
+
+/// value returned by the Rust function, `Err(message)` stops the source
+/// the same way `Err` of closures does
+pub trait IntoNative {
+    fn into_native(self) -> Result<LiteralType, NativeError>;
+}
+
+impl<T: IntoAbsurd> IntoNative for T {
+    fn into_native(self) -> Result<LiteralType, NativeError> {
+        Ok(self.into_absurd())
+    }
+}
+
+impl<T: IntoAbsurd> IntoNative for Result<T, String> {
+    fn into_native(self) -> Result<LiteralType, NativeError> {
+        self.map(T::into_absurd).map_err(NativeError::Message)
+    }
+}
+
+/// Rust function with arguments taken by `FromAbsurd` and the value returned by `IntoNative`,
+/// `fn(f64, String) -> Vec<String>` is a native of two arguments
+pub trait NativeFunc<Args> {
+    fn arity(&self) -> usize;
+
+    fn call(&self, args: Vec<LiteralType>) -> Result<LiteralType, NativeError>;
+}
+
+/// implements `NativeFunc` for functions taking `$arg` arguments
+macro_rules! native_func {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> NativeFunc<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R,
+            R: IntoNative,
+            $($arg: FromAbsurd),*
+        {
+            fn arity(&self) -> usize {
+                <[&str]>::len(&[$(stringify!($arg)),*])
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn call(&self, args: Vec<LiteralType>) -> Result<LiteralType, NativeError> {
+                let mut args = args.into_iter();
+                let mut arg = || args.next().unwrap();
+                self($($arg::from_absurd(arg()).map_err(NativeError::Mismatch)?),*).into_native()
+            }
+        }
+    };
+}
+
+native_func!();
+native_func!(A);
+native_func!(A, B);
+native_func!(A, B, C);
+native_func!(A, B, C, D);
+
+/// arity and the closure of the Rust function
+pub fn typed<Args>(f: impl NativeFunc<Args> + 'static) -> (usize, NativeFn) {
+    let arity = f.arity();
+    (arity, Rc::new(move |args| f.call(args)))
+}
+
+/// native function, number of the arguments is checked before the call
+pub struct Native {
+    /// environment the native is defined in, errors are reported to the sink of its source
+    env: Weak<RefCell<Env>>,
+    arity: usize,
+    f: NativeFn,
+}
+
+impl Native {
+    pub fn new(env: &Rc<RefCell<Env>>, arity: usize, f: NativeFn) -> Self {
+        Native {
+            env: Rc::downgrade(env),
+            arity,
+            f,
+        }
+    }
+
+    /// native calling the Rust function, arguments of other types fail with E0x301
+    pub fn typed<Args>(env: &Rc<RefCell<Env>>, f: impl NativeFunc<Args> + 'static) -> Self {
+        let (arity, f) = typed(f);
+        Native::new(env, arity, f)
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+}
+
+impl FuncValType for Native {
+    fn call(&self, args: Vec<Option<LiteralType>>) -> LiteralType {
+        self.call_at(args, &Token::null())
+    }
+
+    fn call_at(&self, args: Vec<Option<LiteralType>>, at: &Token) -> LiteralType {
+        let err = self
+            .env
+            .upgrade()
+            .map_or(Error::new(""), |env| env.borrow().err());
+        let args: Vec<LiteralType> = args
+            .into_iter()
+            .map(|arg| arg.unwrap_or(LiteralType::Null))
+            .collect();
+        if args.len() != self.arity {
+            err.throw(E0x405, at.line, at.pos, vec![]);
+        }
+        report((self.f)(args), &err, at)
+    }
+}
+
+/// value of the native, its errors are reported at the call
+pub fn report(result: Result<LiteralType, NativeError>, err: &Error, at: &Token) -> LiteralType {
+    match result {
+        Ok(value) => value,
+        Err(NativeError::Message(msg)) => raw(&msg),
+        Err(NativeError::Mismatch(Mismatch { expected, got })) => {
+            err.throw(E0x301, at.line, at.pos, vec![expected, got])
+        }
+        Err(NativeError::Arity) => err.throw(E0x405, at.line, at.pos, vec![]),
+    }
+}
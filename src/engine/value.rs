@@ -0,0 +1,376 @@
+// conversions between Rust values and values of the sources
+use crate::ast::{LiteralType, MapKey};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    rc::Rc,
+};
+
+/// Rust value passed to the sources
+pub trait IntoAbsurd {
+    fn into_absurd(self) -> LiteralType;
+}
+
+/// Rust value taken from the sources
+pub trait FromAbsurd: Sized {
+    /// name of the type in the sources, it's expected by mismatch errors
+    fn type_name() -> String;
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch>;
+}
+
+/// value that can't be converted, items of collections report their own mismatches
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub expected: String,
+    pub got: String,
+}
+
+impl Mismatch {
+    pub fn new<T: FromAbsurd>(value: &LiteralType) -> Self {
+        Mismatch {
+            expected: T::type_name(),
+            got: value.type_name(),
+        }
+    }
+}
+
+/// Rust value usable as the key of maps, keys of the sources are ints, strings, chars or bools
+pub trait AbsurdKey: FromAbsurd + IntoAbsurd + Eq + Hash {}
+
+impl AbsurdKey for i64 {}
+impl AbsurdKey for String {}
+impl AbsurdKey for char {}
+impl AbsurdKey for bool {}
+
+impl IntoAbsurd for LiteralType {
+    fn into_absurd(self) -> LiteralType {
+        self
+    }
+}
+
+impl FromAbsurd for LiteralType {
+    fn type_name() -> String {
+        "any".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        Ok(value)
+    }
+}
+
+impl IntoAbsurd for () {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Void
+    }
+}
+
+impl IntoAbsurd for i64 {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Int(self)
+    }
+}
+
+impl IntoAbsurd for i32 {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Int(self.into())
+    }
+}
+
+impl IntoAbsurd for u32 {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Int(self.into())
+    }
+}
+
+impl FromAbsurd for i64 {
+    fn type_name() -> String {
+        "int".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Int(n) => Ok(n),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+impl IntoAbsurd for f64 {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Float(self)
+    }
+}
+
+impl IntoAbsurd for f32 {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Float(self.into())
+    }
+}
+
+/// ints are converted to floats, same as `number` of the sources takes both
+impl FromAbsurd for f64 {
+    fn type_name() -> String {
+        "number".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Float(n) => Ok(n),
+            LiteralType::Int(n) => Ok(n as f64),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+/// `number` of the sources, ints aren't converted to floats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// the number as a float, ints are converted
+    pub fn to_float(self) -> f64 {
+        match self {
+            Number::Int(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+}
+
+impl IntoAbsurd for Number {
+    fn into_absurd(self) -> LiteralType {
+        match self {
+            Number::Int(n) => LiteralType::Int(n),
+            Number::Float(n) => LiteralType::Float(n),
+        }
+    }
+}
+
+impl FromAbsurd for Number {
+    fn type_name() -> String {
+        "number".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Int(n) => Ok(Number::Int(n)),
+            LiteralType::Float(n) => Ok(Number::Float(n)),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+impl IntoAbsurd for bool {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Boolean(self)
+    }
+}
+
+impl FromAbsurd for bool {
+    fn type_name() -> String {
+        "bool".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Boolean(b) => Ok(b),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+impl IntoAbsurd for char {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Char(self)
+    }
+}
+
+impl FromAbsurd for char {
+    fn type_name() -> String {
+        "char".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Char(c) => Ok(c),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+impl IntoAbsurd for String {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::String(Rc::new(self))
+    }
+}
+
+impl IntoAbsurd for &str {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::String(Rc::new(self.to_string()))
+    }
+}
+
+impl FromAbsurd for String {
+    fn type_name() -> String {
+        "string".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::String(s) => Ok(Rc::unwrap_or_clone(s)),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+/// `None` is `null`
+impl<T: IntoAbsurd> IntoAbsurd for Option<T> {
+    fn into_absurd(self) -> LiteralType {
+        self.map_or(LiteralType::Null, T::into_absurd)
+    }
+}
+
+impl<T: FromAbsurd> FromAbsurd for Option<T> {
+    fn type_name() -> String {
+        T::type_name()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Null => Ok(None),
+            value => T::from_absurd(value).map(Some),
+        }
+    }
+}
+
+impl<T: IntoAbsurd> IntoAbsurd for Vec<T> {
+    fn into_absurd(self) -> LiteralType {
+        LiteralType::Vec(Rc::new(self.into_iter().map(T::into_absurd).collect()))
+    }
+}
+
+/// ranges are collected into vectors
+impl<T: FromAbsurd> FromAbsurd for Vec<T> {
+    fn type_name() -> String {
+        format!("Vec<{}>", T::type_name())
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value.collect() {
+            LiteralType::Vec(items) => Rc::unwrap_or_clone(items)
+                .into_iter()
+                .map(T::from_absurd)
+                .collect(),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+/// the vector itself, without copying its items
+impl FromAbsurd for Rc<Vec<LiteralType>> {
+    fn type_name() -> String {
+        "Vec<any>".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value.collect() {
+            LiteralType::Vec(items) => Ok(items),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+impl FromAbsurd for MapKey {
+    fn type_name() -> String {
+        "int | string | char | bool".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        value.to_key().ok_or_else(|| Mismatch::new::<Self>(&value))
+    }
+}
+
+/// the map itself, in the order of its keys
+impl FromAbsurd for Rc<BTreeMap<MapKey, LiteralType>> {
+    fn type_name() -> String {
+        "Map<any, any>".to_string()
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Map(map) => Ok(map),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+impl<K: AbsurdKey, V: IntoAbsurd> IntoAbsurd for HashMap<K, V> {
+    fn into_absurd(self) -> LiteralType {
+        let entries = self.into_iter().map(|(key, value)| {
+            // keys are only converted from the types of `AbsurdKey`
+            let key = key.into_absurd().to_key().expect("invalid key of the map");
+            (key, value.into_absurd())
+        });
+        LiteralType::Map(Rc::new(entries.collect()))
+    }
+}
+
+impl<K: AbsurdKey, V: FromAbsurd> FromAbsurd for HashMap<K, V> {
+    fn type_name() -> String {
+        format!("Map<{}, {}>", K::type_name(), V::type_name())
+    }
+
+    fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+        match value {
+            LiteralType::Map(map) => Rc::unwrap_or_clone(map)
+                .into_iter()
+                .map(|(key, value)| Ok((K::from_absurd(key_value(key))?, V::from_absurd(value)?)))
+                .collect(),
+            value => Err(Mismatch::new::<Self>(&value)),
+        }
+    }
+}
+
+fn key_value(key: MapKey) -> LiteralType {
+    match key {
+        MapKey::Int(n) => LiteralType::Int(n),
+        MapKey::String(s) => LiteralType::String(Rc::new(s)),
+        MapKey::Char(c) => LiteralType::Char(c),
+        MapKey::Boolean(b) => LiteralType::Boolean(b),
+    }
+}
+
+/// implements the conversions of tuples, `$index` are indices of their items
+macro_rules! tuple {
+    ($($item:ident $index:tt),+) => {
+        impl<$($item: IntoAbsurd),+> IntoAbsurd for ($($item,)+) {
+            fn into_absurd(self) -> LiteralType {
+                LiteralType::Tuple(vec![$(self.$index.into_absurd()),+])
+            }
+        }
+
+        impl<$($item: FromAbsurd),+> FromAbsurd for ($($item,)+) {
+            fn type_name() -> String {
+                let items: Vec<String> = vec![$($item::type_name()),+];
+                format!("Tuple<({})>", items.join(", "))
+            }
+
+            fn from_absurd(value: LiteralType) -> Result<Self, Mismatch> {
+                match value {
+                    LiteralType::Tuple(items) if items.len() == [$($index),+].len() => {
+                        let mut items = items.into_iter();
+                        Ok(($($item::from_absurd(items.next().unwrap())?,)+))
+                    }
+                    value => Err(Mismatch::new::<Self>(&value)),
+                }
+            }
+        }
+    };
+}
+
+tuple!(A 0, B 1);
+tuple!(A 0, B 1, C 2);
+tuple!(A 0, B 1, C 2, D 3);
@@ -1,9 +1,15 @@
 // handles Absurd errors
 use coloredpp::Colorize;
-use std::process::exit;
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    process::exit,
+    rc::Rc,
+};
 use ErrorCode::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ErrorCode {
     /// `syntax error (E0x101): malformed or unterminated char`
     E0x101,
@@ -24,6 +30,9 @@ pub enum ErrorCode {
     E0x106,
     /// `sybtax error (E0x107): invalid assignment target`
     E0x107,
+    /// `syntax error (E0x108): integer literal '{0}' is too large`
+    /// - {0}: literal
+    E0x108,
     /// `runtime error (E0x301): type mismatch: expected '{0}', got '{1}'`
     /// - {0}: expected type
     /// - {1}: actual type
@@ -40,6 +49,43 @@ pub enum ErrorCode {
     E0x305,
     /// `runtime error (E0x306): stack underflow`
     E0x306,
+    /// `runtime error (E0x307): continue statement not within a loop`
+    E0x307,
+    /// `runtime error (E0x308): undeclared label '{0}'`
+    /// - {0}: label
+    E0x308,
+    /// `runtime error (E0x309): integer overflow in '{0}'`
+    /// - {0}: operator
+    E0x309,
+    /// `runtime error (E0x310): division by zero`
+    E0x310,
+    /// `runtime error (E0x311): '{0}' can't be a map key`
+    /// - {0}: type of the key
+    E0x311,
+    /// `runtime error (E0x312): '{0}' is not iterable`
+    /// - {0}: type of the value
+    E0x312,
+    /// `runtime error (E0x313): range step must be greater than zero`
+    E0x313,
+    /// `runtime error (E0x314): range bound must be an int, got '{0}'`
+    /// - {0}: type of the bound
+    E0x314,
+    /// `runtime error (E0x315): awaited future can't complete`
+    E0x315,
+    /// `runtime error (E0x316): match doesn't cover '{0}'`
+    /// - {0}: missing pattern, or the value at runtime
+    E0x316,
+    /// `runtime error (E0x317): '{0}' has no function '{1}'`
+    /// - {0}: name of the type, or type of the value
+    /// - {1}: name of the function
+    E0x317,
+    /// `runtime error (E0x318): negative power of an int in '{0}'`
+    /// - {0}: operator
+    E0x318,
+    /// `runtime error (E0x319): range '{0}' is too long to be passed to a native, it has more than {1} items`
+    /// - {0}: range
+    /// - {1}: maximal count of the items
+    E0x319,
     /// `runtime error (E0x401): function must have one name`
     E0x401,
     /// `runtime error (E0x402): public variable must have a value`
@@ -70,207 +116,215 @@ pub enum ErrorCode {
     E0x502,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// location of the diagnostic in the source, `line` is 0 if it's unknown
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub pos: (usize, usize),
+}
+
+impl Span {
+    pub fn is_known(&self) -> bool {
+        self.line != 0 || self.pos != (0, 0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: ErrorCode,
+    /// `syntax`, `check`, `runtime` or `environment`
+    pub kind: &'static str,
+    pub severity: Severity,
+    pub span: Span,
+    pub message: String,
+    pub notes: Vec<String>,
+    /// other places of the source related to the diagnostic, printed with their snippets
+    pub related: Vec<(Span, String)>,
+}
+
+impl Diagnostic {
+    /// points the note at another place of the source, e.g. at the definition of the called function
+    pub fn related(mut self, line: usize, pos: (usize, usize), note: String) -> Self {
+        self.related.push((Span { line, pos }, note));
+        self
+    }
+}
+
+/// `Error` is the diagnostics sink, clones share collected diagnostics
 #[derive(Debug, Clone, PartialEq)]
 pub struct Error {
     source: String,
+    diagnostics: Rc<RefCell<Vec<Diagnostic>>>,
 }
 
 impl Error {
     pub fn new(src: &str) -> Self {
         Error {
             source: src.to_string(),
+            diagnostics: Rc::new(RefCell::new(vec![])),
         }
     }
 
-    pub fn throw(&self, code: ErrorCode, line: usize, pos: (usize, usize), args: Vec<String>) {
-        match code {
-            E0x101 => self.error(
-                101,
-                "syntax",
-                "malformed or unterminated char".to_string(),
-                line,
-                pos,
-            ),
-            E0x102 => self.error(102, "syntax", "unterminated string".to_string(), line, pos),
-            E0x103 => self.error(
-                103,
-                "syntax",
-                format!("unexpected token '{}'", args[0]),
-                line,
-                pos,
-            ),
-            E0x104 => self.error(
-                104,
-                "syntax",
-                format!("failed to unwrap a number '{}'", args[0]),
-                line,
-                pos,
-            ),
-            E0x105 => self.error(
-                105,
-                "syntax",
-                format!("failed to parse '{}'", args[0]),
-                line,
-                pos,
-            ),
-            E0x106 => self.error(
-                106,
-                "syntax",
-                format!("expected a token '{}'", args[0]),
-                line,
-                pos,
-            ),
-            E0x107 => self.error(
-                107,
-                "syntax",
-                format!("invalid assignment target"),
-                line,
-                pos,
-            ),
-            E0x301 => self.error(
-                301,
-                "runtime",
-                format!("type mismatch: expected '{}', got '{}'", args[0], args[1]),
-                line,
-                pos,
-            ),
-            E0x302 => self.error(
-                302,
-                "runtime",
-                "break statement not within a loop".to_string(),
-                line,
-                pos,
-            ),
-            E0x303 => self.error(
-                303,
-                "runtime",
-                "return statement not within a function".to_string(),
-                line,
-                pos,
-            ),
-            E0x304 => self.error(
-                306,
-                "runtime",
-                format!("failed to resolve '{}'", args[0]),
-                line,
-                pos,
-            ),
-            E0x305 => self.error(
-                307,
-                "runtime",
-                format!("'{}' is already declared", args[0]),
-                line,
-                pos,
-            ),
-            E0x306 => self.error(308, "runtime", "stack underflow".to_string(), line, pos),
-            E0x401 => self.error(
-                401,
-                "runtime",
-                "function must have one name".to_string(),
-                line,
-                pos,
-            ),
-            E0x402 => self.error(
-                402,
-                "runtime",
-                "public variable must have a value".to_string(),
-                line,
-                pos,
-            ),
-
-            E0x404 => self.error(
-                404,
-                "runtime",
-                "failed to create a function".to_string(),
-                line,
-                pos,
-            ),
-            E0x405 => self.error(
-                405,
-                "runtime",
-                "invalid number of arguments".to_string(),
-                line,
-                pos,
-            ),
-            E0x406 => self.error(
-                406,
-                "runtime",
-                "missing return statement".to_string(),
-                line,
-                pos,
-            ),
-            E0x410 => self.error(
-                410,
-                "runtime",
-                "can not assign to an immutable variable".to_string(),
-                line,
-                pos,
-            ),
-            E0x411 => self.error(
-                411,
-                "runtime",
-                "can not assign to a public variable".to_string(),
-                line,
-                pos,
-            ),
-            E0x412 => self.error(
-                412,
-                "runtime",
-                format!("invalid type, while assigning to a variable '{}'", args[0]),
-                line,
-                pos,
-            ),
-            E0x413 => self.error(
-                413,
-                "runtime",
-                "can not assign to a non-variable".to_string(),
-                line,
-                pos,
-            ),
-            E0x414 => self.error(
-                414,
-                "runtime",
-                "failed to assign a value".to_string(),
-                line,
-                pos,
-            ),
-            E0x416 => self.error(
-                416,
+    /// builds the diagnostic of the error code
+    pub fn diagnostic(
+        &self,
+        code: ErrorCode,
+        line: usize,
+        pos: (usize, usize),
+        args: Vec<String>,
+    ) -> Diagnostic {
+        let (kind, message) = match code {
+            E0x101 => ("syntax", "malformed or unterminated char".to_string()),
+            E0x102 => ("syntax", "unterminated string".to_string()),
+            E0x103 => ("syntax", format!("unexpected token '{}'", args[0])),
+            E0x104 => ("syntax", format!("failed to unwrap a number '{}'", args[0])),
+            E0x105 => ("syntax", format!("failed to parse '{}'", args[0])),
+            E0x106 => ("syntax", format!("expected a token '{}'", args[0])),
+            E0x107 => ("syntax", "invalid assignment target".to_string()),
+            E0x108 => ("syntax", format!("integer literal '{}' is too large", args[0])),
+            E0x301 => ("runtime", format!("type mismatch: expected '{}', got '{}'", args[0], args[1])),
+            E0x302 => ("runtime", "break statement not within a loop".to_string()),
+            E0x303 => ("runtime", "return statement not within a function".to_string()),
+            E0x304 => ("runtime", format!("failed to resolve '{}'", args[0])),
+            E0x305 => ("runtime", format!("'{}' is already declared", args[0])),
+            E0x306 => ("runtime", "stack underflow".to_string()),
+            E0x307 => ("runtime", "continue statement not within a loop".to_string()),
+            E0x308 => ("runtime", format!("undeclared label '{}'", args[0])),
+            E0x309 => ("runtime", format!("integer overflow in '{}'", args[0])),
+            E0x310 => ("runtime", "division by zero".to_string()),
+            E0x311 => ("runtime", format!("'{}' can't be a map key", args[0])),
+            E0x312 => ("runtime", format!("'{}' is not iterable", args[0])),
+            E0x313 => ("runtime", "range step must be greater than zero".to_string()),
+            E0x314 => ("runtime", format!("range bound must be an int, got '{}'", args[0])),
+            E0x315 => ("runtime", "awaited future can't complete".to_string()),
+            E0x316 => ("runtime", format!("match doesn't cover '{}'", args[0])),
+            E0x317 => ("runtime", format!("'{}' has no function '{}'", args[0], args[1])),
+            E0x318 => ("runtime", format!("negative power of an int in '{}'", args[0])),
+            E0x319 => (
                 "runtime",
-                format!("failed to get values from {}", args[0]),
-                line,
-                pos,
-            ),
-            E0x501 => self.error(
-                501,
-                "environment",
-                "failed to get a distance".to_string(),
-                line,
-                pos,
-            ),
-            E0x502 => self.error(
-                502,
-                "environment",
-                "failed to resolve a value".to_string(),
-                line,
-                pos,
+                format!(
+                    "range '{}' is too long to be passed to a native, it has more than {} items",
+                    args[0], args[1]
+                ),
             ),
+            E0x401 => ("runtime", "function must have one name".to_string()),
+            E0x402 => ("runtime", "public variable must have a value".to_string()),
+            E0x404 => ("runtime", "failed to create a function".to_string()),
+            E0x405 => ("runtime", "invalid number of arguments".to_string()),
+            E0x406 => ("runtime", "missing return statement".to_string()),
+            E0x410 => ("runtime", "can not assign to an immutable variable".to_string()),
+            E0x411 => ("runtime", "can not assign to a public variable".to_string()),
+            E0x412 => ("runtime", format!("invalid type, while assigning to a variable '{}'", args[0])),
+            E0x413 => ("runtime", "can not assign to a non-variable".to_string()),
+            E0x414 => ("runtime", "failed to assign a value".to_string()),
+            E0x416 => ("runtime", format!("failed to get values from {}", args[0])),
+            E0x501 => ("environment", "failed to get a distance".to_string()),
+            E0x502 => ("environment", "failed to resolve a value".to_string()),
         };
+        Diagnostic {
+            notes: vec![format!("for more information, run `absurd error {:?}`", code)],
+            code,
+            kind,
+            severity: Severity::Error,
+            span: Span { line, pos },
+            message,
+            related: vec![],
+        }
+    }
+
+    /// collects the diagnostic, the caller keeps going after it
+    pub fn report(&self, code: ErrorCode, line: usize, pos: (usize, usize), args: Vec<String>) {
+        self.push(self.diagnostic(code, line, pos, args));
+    }
+
+    /// collects the built diagnostic
+    pub fn push(&self, diagnostic: Diagnostic) {
+        // enclosing statements may report the same error while recovering
+        if !self.diagnostics.borrow().contains(&diagnostic) {
+            self.diagnostics.borrow_mut().push(diagnostic);
+        }
+    }
+
+    /// reports the diagnostic with the collected ones and stops the execution
+    pub fn throw(&self, code: ErrorCode, line: usize, pos: (usize, usize), args: Vec<String>) -> ! {
+        self.fail(self.diagnostic(code, line, pos, args));
+    }
+
+    /// same as `throw`, but for the built diagnostic
+    pub fn fail(&self, diagnostic: Diagnostic) -> ! {
+        self.push(diagnostic);
+        self.flush();
+        stop();
+    }
+
+    /// true if any error was collected
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .borrow()
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+
+    /// prints and clears the collected diagnostics, they are kept by `captured` if it's running
+    pub fn flush(&self) {
+        let diagnostics: Vec<Diagnostic> = self.diagnostics.borrow_mut().drain(..).collect();
+        let diagnostics = CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+            Some(captured) => {
+                captured.extend(diagnostics);
+                vec![]
+            }
+            None => diagnostics,
+        });
+        for diagnostic in diagnostics {
+            self.print(&diagnostic);
+        }
     }
 }
 
 impl Error {
-    pub fn error(&self, code: usize, head: &str, msg: String, line: usize, pos: (usize, usize)) {
-        let mut is_snippet = false;
-        if line != 0 || pos != (0, 0) {
-            is_snippet = true;
-            self.print_lines(line, pos)
-        }
-        let msg = match is_snippet {
-            true => format!("{}, at line {}:{}-{}", msg, line, pos.0, pos.1),
-            false => msg,
+    /// prints the diagnostic with the source snippet
+    pub fn print(&self, diagnostic: &Diagnostic) {
+        let Span { line, pos } = diagnostic.span;
+        let msg = if diagnostic.span.is_known() {
+            self.print_lines(line, pos);
+            format!("{}, at line {}:{}-{}", diagnostic.message, line, pos.0, pos.1)
+        } else {
+            diagnostic.message.clone()
         };
-        self.panic(head, code, msg)
+        let err_code = format!("{:?}", diagnostic.code).yellow();
+        let head = format!("{} {} {}:", diagnostic.kind, diagnostic.severity, err_code);
+        eprintln!(
+            "{} {}",
+            head.fg_hex("#fc4949").bold(),
+            msg.fg_hex("#fc4949")
+        );
+        for note in &diagnostic.notes {
+            eprintln!("  {} {}", "note:".bold(), note);
+        }
+        for (span, note) in diagnostic.related.iter().filter(|(s, _)| s.is_known()) {
+            self.print_lines(span.line, span.pos);
+            eprintln!(
+                "  {} {}, at line {}:{}-{}",
+                "note:".bold(),
+                note,
+                span.line,
+                span.pos.0,
+                span.pos.1
+            );
+        }
     }
 
     pub fn print_lines(&self, line: usize, pos: (usize, usize)) {
@@ -304,17 +358,6 @@ impl Error {
         }
     }
 
-    pub fn panic(&self, kind: &str, code: usize, msg: String) {
-        let err_code = format!("E0x{}", code).yellow();
-        let head = format!("{} error {}:", kind, err_code);
-        eprintln!(
-            "{} {}",
-            head.fg_hex("#fc4949").bold(),
-            msg.fg_hex("#fc4949")
-        );
-        exit(0);
-    }
-
     fn split_line_at_char_indices(
         &self,
         line: &str,
@@ -332,11 +375,165 @@ impl Error {
     }
 }
 
-pub fn raw(msg: &str) {
+pub fn raw(msg: &str) -> ! {
+    if is_captured() {
+        halt(Failure::Message(msg.to_string()));
+    }
     eprintln!("{}", msg.red());
-    exit(0);
+    stop();
+}
+
+/// failure of the source running in `captured`, it's returned instead of being printed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    /// diagnostics reported before the source stopped, in the reported order
+    Diagnostics(Vec<Diagnostic>),
+    /// message of the failed native function or `panic()`
+    Message(String),
+    /// the source called `exit(code)`
+    Exit(i32),
+    /// the source file can't be read
+    Io(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Diagnostics(diagnostics) => {
+                let messages: Vec<String> = diagnostics
+                    .iter()
+                    .map(|d| match d.span.is_known() {
+                        true => format!(
+                            "{:?}: {}, at line {}:{}-{}",
+                            d.code, d.message, d.span.line, d.span.pos.0, d.span.pos.1
+                        ),
+                        false => format!("{:?}: {}", d.code, d.message),
+                    })
+                    .collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+            Failure::Message(msg) => write!(f, "{}", msg),
+            Failure::Exit(code) => write!(f, "exited with code {}", code),
+            Failure::Io(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
+
+thread_local! {
+    static INTERACTIVE: Cell<bool> = const { Cell::new(false) };
+    /// diagnostics flushed while `captured` runs
+    static CAPTURED: RefCell<Option<Vec<Diagnostic>>> = const { RefCell::new(None) };
+}
+
+/// payload of the unwinding started by `stop`, failures of `captured` sources are carried by it
+struct Stopped(Option<Failure>);
+
+/// stops the execution after a reported error, it exits the process,
+/// or unwinds back to `interactive` if it's running
+pub fn stop() -> ! {
+    if INTERACTIVE.with(|i| i.get()) {
+        panic::resume_unwind(Box::new(Stopped(None)));
+    }
+    exit(1);
+}
+
+/// `exit(code)` of the source, `captured` sources don't exit the process
+pub fn quit(code: i32) -> ! {
+    if is_captured() {
+        halt(Failure::Exit(code));
+    }
+    exit(code);
+}
+
+fn halt(failure: Failure) -> ! {
+    panic::resume_unwind(Box::new(Stopped(Some(failure))));
+}
+
+fn is_captured() -> bool {
+    CAPTURED.with(|c| c.borrow().is_some())
+}
+
+/// runs the function without exiting the process on errors,
+/// returns `None` if the function was stopped by an error
+pub fn interactive<T>(f: impl FnOnce() -> T) -> Option<T> {
+    unwound(f).ok()
+}
+
+/// runs the function without exiting the process and without printing errors,
+/// returns the failure if the function was stopped
+pub fn captured<T>(f: impl FnOnce() -> T) -> Result<T, Failure> {
+    let prev = CAPTURED.with(|c| c.replace(Some(vec![])));
+    let result = unwound(f);
+    let diagnostics = CAPTURED.with(|c| c.replace(prev)).unwrap_or_default();
+    result.map_err(|failure| failure.unwrap_or(Failure::Diagnostics(diagnostics)))
+}
+
+/// runs the function, errors `Err` with the failure carried by the unwinding
+fn unwound<T>(f: impl FnOnce() -> T) -> Result<T, Option<Failure>> {
+    let prev = INTERACTIVE.with(|i| i.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    INTERACTIVE.with(|i| i.set(prev));
+    match result {
+        Ok(value) => Ok(value),
+        Err(e) => match e.downcast::<Stopped>() {
+            Ok(stopped) => Err(stopped.0),
+            Err(e) => panic::resume_unwind(e),
+        },
+    }
 }
 
 pub fn log(msg: &str) {
     eprintln!("{}", msg.red());
 }
+
+/// returns the long-form explanation of the error code from `docs/errors`,
+/// code is accepted as `E0x301`, `0x301` or `301`
+pub fn explain(code: &str) -> Option<&'static str> {
+    let code = code.trim_start_matches(['E', 'e']).trim_start_matches("0x");
+    let doc = match format!("E0x{}", code).as_str() {
+        "E0x101" => include_str!("../docs/errors/E0x101.md"),
+        "E0x102" => include_str!("../docs/errors/E0x102.md"),
+        "E0x103" => include_str!("../docs/errors/E0x103.md"),
+        "E0x104" => include_str!("../docs/errors/E0x104.md"),
+        "E0x105" => include_str!("../docs/errors/E0x105.md"),
+        "E0x106" => include_str!("../docs/errors/E0x106.md"),
+        "E0x107" => include_str!("../docs/errors/E0x107.md"),
+        "E0x108" => include_str!("../docs/errors/E0x108.md"),
+        "E0x301" => include_str!("../docs/errors/E0x301.md"),
+        "E0x302" => include_str!("../docs/errors/E0x302.md"),
+        "E0x303" => include_str!("../docs/errors/E0x303.md"),
+        "E0x304" => include_str!("../docs/errors/E0x304.md"),
+        "E0x305" => include_str!("../docs/errors/E0x305.md"),
+        "E0x306" => include_str!("../docs/errors/E0x306.md"),
+        "E0x307" => include_str!("../docs/errors/E0x307.md"),
+        "E0x308" => include_str!("../docs/errors/E0x308.md"),
+        "E0x309" => include_str!("../docs/errors/E0x309.md"),
+        "E0x310" => include_str!("../docs/errors/E0x310.md"),
+        "E0x311" => include_str!("../docs/errors/E0x311.md"),
+        "E0x312" => include_str!("../docs/errors/E0x312.md"),
+        "E0x313" => include_str!("../docs/errors/E0x313.md"),
+        "E0x314" => include_str!("../docs/errors/E0x314.md"),
+        "E0x315" => include_str!("../docs/errors/E0x315.md"),
+        "E0x316" => include_str!("../docs/errors/E0x316.md"),
+        "E0x317" => include_str!("../docs/errors/E0x317.md"),
+        "E0x318" => include_str!("../docs/errors/E0x318.md"),
+        "E0x319" => include_str!("../docs/errors/E0x319.md"),
+        "E0x401" => include_str!("../docs/errors/E0x401.md"),
+        "E0x402" => include_str!("../docs/errors/E0x402.md"),
+        "E0x404" => include_str!("../docs/errors/E0x404.md"),
+        "E0x405" => include_str!("../docs/errors/E0x405.md"),
+        "E0x406" => include_str!("../docs/errors/E0x406.md"),
+        "E0x410" => include_str!("../docs/errors/E0x410.md"),
+        "E0x411" => include_str!("../docs/errors/E0x411.md"),
+        "E0x412" => include_str!("../docs/errors/E0x412.md"),
+        "E0x413" => include_str!("../docs/errors/E0x413.md"),
+        "E0x414" => include_str!("../docs/errors/E0x414.md"),
+        "E0x416" => include_str!("../docs/errors/E0x416.md"),
+        "E0x501" => include_str!("../docs/errors/E0x501.md"),
+        "E0x502" => include_str!("../docs/errors/E0x502.md"),
+        _ => return None,
+    };
+    Some(doc)
+}
@@ -0,0 +1,816 @@
+// Absurd formatter, prints the AST back into the normalized source
+use crate::ast::{
+    CallType, Comment, Destruct, LiteralKind, LiteralType, MatchCase, Pattern, RecordField,
+    Statement, Token, TokenType,
+};
+use crate::interpreter::expr::{AssignKind, Expression};
+use crate::interpreter::types::TypeKind;
+
+/// indentation used for every nested block
+const INDENT: &str = "    ";
+/// enums and records longer than this are split into multiple lines
+const MAX_WIDTH: usize = 80;
+
+pub struct Formatter<'a> {
+    /// source lines, used to preserve blank lines
+    lines: Vec<&'a str>,
+    /// comments collected by the scanner, in the source order
+    comments: Vec<Comment>,
+    /// index of the next comment to print
+    crnt: usize,
+    /// current indentation level
+    depth: usize,
+    /// functions of `impl` blocks print `self` without the type
+    in_impl: bool,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(src: &'a str, comments: Vec<Comment>) -> Self {
+        Self {
+            lines: src.lines().collect(),
+            comments,
+            crnt: 0,
+            depth: 0,
+            in_impl: false,
+        }
+    }
+
+    /// formatter entry function
+    pub fn format(&mut self, stmts: &[Statement]) -> String {
+        let mut out = self.stmts(stmts);
+        // comments after the last statement
+        while self.crnt < self.comments.len() {
+            let comment = self.comments[self.crnt].clone();
+            if !out.is_empty() && self.is_blank_before(comment.line) {
+                out.push('\n');
+            }
+            out.push_str(&comment.text);
+            out.push('\n');
+            self.crnt += 1;
+        }
+        out
+    }
+
+    /// formats statements of a single block, each one on its own line
+    fn stmts(&mut self, stmts: &[Statement]) -> String {
+        let mut out = String::new();
+        for stmt in stmts {
+            let line = stmt_line(stmt);
+            let comments = self.comments_before(line, out.is_empty());
+            out.push_str(&comments);
+
+            if !out.is_empty() && comments.is_empty() && self.is_blank_before(line) {
+                out.push('\n');
+            }
+            out.push_str(&self.indent());
+            let text = self.stmt(stmt);
+            out.push_str(&text);
+
+            // keep trailing comments of single line statements on their line
+            if let Some(comment) = self.comments.get(self.crnt) {
+                if comment.is_inline && comment.line == line && !text.contains('\n') {
+                    out.push(' ');
+                    out.push_str(&comment.text);
+                    self.crnt += 1;
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// prints comments, which are placed before the line
+    fn comments_before(&mut self, line: usize, is_first: bool) -> String {
+        let mut out = String::new();
+        if line == 0 {
+            return out;
+        }
+        while let Some(comment) = self.comments.get(self.crnt).cloned() {
+            if comment.line >= line {
+                break;
+            }
+            if (!is_first || !out.is_empty()) && self.is_blank_before(comment.line) {
+                out.push('\n');
+            }
+            out.push_str(&self.indent());
+            out.push_str(&comment.text);
+            out.push('\n');
+            self.crnt += 1;
+        }
+        // blank line between the comments and the statement
+        if !out.is_empty() && self.is_blank_before(line) {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// prints block comments placed in the line before the token, `1 /* mid */ + 2`
+    fn comments_in(&mut self, token: &Token) -> String {
+        let mut out = String::new();
+        while let Some(comment) = self.comments.get(self.crnt) {
+            let is_before = comment.line == token.line && comment.pos < token.pos.0;
+            if !comment.is_inline || !is_before || !comment.text.starts_with("/*") {
+                break;
+            }
+            out.push_str(&comment.text);
+            out.push(' ');
+            self.crnt += 1;
+        }
+        out
+    }
+
+    /// checks if the source line above the `line` is empty
+    fn is_blank_before(&self, line: usize) -> bool {
+        line > 1
+            && self
+                .lines
+                .get(line - 2)
+                .is_some_and(|l| l.trim().is_empty())
+    }
+
+    fn indent(&self) -> String {
+        INDENT.repeat(self.depth)
+    }
+
+    fn block(&mut self, stmts: &[Statement]) -> String {
+        if stmts.is_empty() {
+            return "{}".to_string();
+        }
+        self.depth += 1;
+        let body = self.stmts(stmts);
+        self.depth -= 1;
+        format!("{{\n{}{}}}", body, self.indent())
+    }
+
+    /// formats body of control flow statements, `{ ... }` or `: stmt`
+    fn body(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Block { stmts } => format!(" {}", self.block(stmts)),
+            _ => format!(": {}", self.stmt(stmt)),
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Expression { expr } => format!("{};", self.expr(expr)),
+            Statement::Block { stmts } => self.block(stmts),
+            Statement::Var { .. } => self.var(stmt),
+            Statement::Func {
+                name,
+                generics,
+                value_type,
+                body,
+                params,
+                is_async,
+                is_pub,
+            } => {
+                let in_impl = std::mem::take(&mut self.in_impl);
+                let params = match params.split_first() {
+                    Some((first, rest)) if in_impl && first.0.lexeme == "self" => {
+                        match rest.is_empty() {
+                            true => "self".to_string(),
+                            false => format!("self, {}", params_src(rest)),
+                        }
+                    }
+                    _ => params_src(params),
+                };
+                let mut s = "func ".to_string();
+                if *is_pub {
+                    s.push_str("pub ");
+                }
+                if *is_async {
+                    s.push_str("async ");
+                }
+                s.push_str(&format!(
+                    "{}{}({}) -> {}",
+                    name.lexeme,
+                    generics_src(generics),
+                    params,
+                    type_src(value_type)
+                ));
+                let s = match body.as_ref() {
+                    Statement::Block { stmts } => format!("{} {}", s, self.block(stmts)),
+                    Statement::Expression { expr } => format!("{} = {};", s, self.expr(expr)),
+                    stmt => format!("{} {}", s, self.stmt(stmt)),
+                };
+                self.in_impl = in_impl;
+                s
+            }
+            Statement::Impl { name, funcs } => {
+                self.in_impl = true;
+                let body = self.block(funcs);
+                self.in_impl = false;
+                format!("impl {} {}", name.lexeme, body)
+            }
+            Statement::Return { expr, .. } => match expr {
+                Expression::Value {
+                    value: LiteralType::Null,
+                    ..
+                } => "return;".to_string(),
+                _ => format!("return {};", self.expr(expr)),
+            },
+            Statement::If {
+                cond,
+                body,
+                else_branch,
+            } => {
+                let mut s = format!("if {}{}", self.expr(cond), self.body(body));
+                if let Some(branch) = else_branch {
+                    s.push_str(" else ");
+                    s.push_str(&self.stmt(branch));
+                }
+                s
+            }
+            Statement::For {
+                iterator,
+                index,
+                expr,
+                body,
+                label,
+            } => {
+                let index = match index {
+                    Some(index) => format!(", {}", index.lexeme),
+                    None => String::new(),
+                };
+                format!(
+                    "{}for {}{} in {}{}",
+                    label_src(label),
+                    iterator.lexeme,
+                    index,
+                    self.expr(expr),
+                    self.body(body)
+                )
+            }
+            Statement::While { cond, body, label } => format!(
+                "{}while {}{}",
+                label_src(label),
+                self.expr(cond),
+                self.body(body)
+            ),
+            Statement::Break { label, .. } => match label {
+                Some(label) => format!("break {};", label.lexeme),
+                None => "break;".to_string(),
+            },
+            Statement::Continue { label, .. } => match label {
+                Some(label) => format!("continue {};", label.lexeme),
+                None => "continue;".to_string(),
+            },
+            Statement::Match { cond, cases } => {
+                self.matchs(cond, cases, |formatter, body, is_last| match body {
+                    Statement::Block { stmts } => formatter.block(stmts),
+                    Statement::Expression { expr } if is_last => formatter.expr(expr),
+                    Statement::Expression { expr } => format!("{},", formatter.expr(expr)),
+                    stmt => formatter.stmt(stmt),
+                })
+            }
+            Statement::Mod { src, name } => match name {
+                Some(name) => format!("mod {} as {};", src, name),
+                None => format!("mod {};", src),
+            },
+            Statement::Use {
+                src, names, all, ..
+            } => {
+                let names = if *all {
+                    "*".to_string()
+                } else {
+                    names
+                        .iter()
+                        .map(|(name, alias)| match alias {
+                            Some(alias) => format!("{} as {}", name.lexeme, alias.lexeme),
+                            None => name.lexeme.clone(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                };
+                // std modules are imported by path, others by the source string
+                let path = src.trim_matches('"');
+                let src = if path.starts_with("std::") {
+                    path.to_string()
+                } else {
+                    src.clone()
+                };
+                format!("use {} from {};", names, src)
+            }
+            Statement::Sh { cmd } => format!("sh {};", cmd),
+            Statement::Type {
+                name,
+                generics,
+                is_pub,
+                value,
+            } => {
+                let publicity = if *is_pub { "pub " } else { "" };
+                format!(
+                    "type {}{}{} = {};",
+                    publicity,
+                    name.lexeme,
+                    generics_src(generics),
+                    type_src(value)
+                )
+            }
+            Statement::Record {
+                name,
+                generics,
+                extends,
+                fields,
+                ..
+            } => self.record(name, generics, extends, fields),
+            Statement::Enum {
+                name,
+                is_pub,
+                items,
+            } => {
+                let publicity = if *is_pub { " pub" } else { "" };
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|(name, typ)| match typ {
+                        Some(typ) => format!("{}({})", name.lexeme, type_src(typ)),
+                        None => name.lexeme.clone(),
+                    })
+                    .collect();
+                let head = format!("enum {}{}", name.lexeme, publicity);
+                self.list(&head, items)
+            }
+        }
+    }
+
+    fn var(&mut self, stmt: &Statement) -> String {
+        if let Statement::Var {
+            names,
+            destruct,
+            value_type,
+            value,
+            is_mut,
+            is_pub,
+            pub_names,
+            ..
+        } = stmt
+        {
+            let mut s = "let ".to_string();
+            if *is_mut {
+                s.push_str("mut ");
+            }
+            if *is_pub && pub_names == names {
+                s.push_str("pub ");
+            } else if *is_pub {
+                let names: Vec<String> = pub_names.iter().map(|n| name_or(n, "_")).collect();
+                s.push_str(&format!("pub({}) ", names.join(", ")));
+            }
+            let (placeholder, open, close) = match destruct {
+                Some(Destruct::Vector) => ("_", "[", "]"),
+                Some(Destruct::Record) => ("..", "{", "}"),
+                Some(Destruct::Tuple) => ("_", "(", ")"),
+                None => ("_", "", ""),
+            };
+            let names: Vec<String> = names.iter().map(|n| name_or(n, placeholder)).collect();
+            s.push_str(&format!("{}{}{}", open, names.join(", "), close));
+
+            // inferred types aren't written in the source
+            if value_type.line != 0 {
+                s.push_str(&format!(": {}", type_src(value_type)));
+            }
+            match value {
+                None
+                | Some(Expression::Value {
+                    value: LiteralType::Null,
+                    ..
+                }) => {}
+                Some(value) => s.push_str(&format!(" = {}", self.expr(value))),
+            }
+            s.push(';');
+            return s;
+        }
+        String::new()
+    }
+
+    /// prints the match, `body` prints bodies of the arms, the last arm doesn't need the comma
+    fn matchs<T>(
+        &mut self,
+        cond: &Expression,
+        cases: &[MatchCase<T>],
+        mut body: impl FnMut(&mut Self, &T, bool) -> String,
+    ) -> String {
+        let mut s = format!("match {} {{\n", self.expr(cond));
+        self.depth += 1;
+        for (idx, case) in cases.iter().enumerate() {
+            let mut head = pattern_src(&case.pattern);
+            if let Some(guard) = &case.guard {
+                head = format!("{} if {}", head, self.expr(guard));
+            }
+            let body = body(self, &case.body, idx == cases.len() - 1);
+            s.push_str(&format!("{}{} => {}\n", self.indent(), head, body));
+        }
+        self.depth -= 1;
+        s.push_str(&format!("{}}}", self.indent()));
+        s
+    }
+
+    fn record(
+        &mut self,
+        name: &Token,
+        generics: &[Token],
+        extends: &[Token],
+        fields: &[RecordField],
+    ) -> String {
+        let mut head = format!("record {}{}", name.lexeme, generics_src(generics));
+        if !extends.is_empty() {
+            let extends: Vec<String> = extends.iter().map(|e| e.lexeme.clone()).collect();
+            head.push_str(&format!(" extends {}", extends.join(", ")));
+        }
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                let modifier = if field.is_optional {
+                    "?"
+                } else if field.is_strict {
+                    "!"
+                } else {
+                    ""
+                };
+                let mut s = format!(
+                    "{}{}: {}",
+                    field.name.lexeme,
+                    modifier,
+                    type_src(&field.value)
+                );
+                if let Some(value) = &field.default_value {
+                    s.push_str(&format!(" = {}", self.expr(value)));
+                }
+                s
+            })
+            .collect();
+        self.list(&head, fields)
+    }
+
+    /// formats `head { item, item }`, splitting long lists into lines
+    fn list(&self, head: &str, items: Vec<String>) -> String {
+        if items.is_empty() {
+            return format!("{} {{}}", head);
+        }
+        let line = format!("{} {{ {} }}", head, items.join(", "));
+        if line.len() + self.indent().len() <= MAX_WIDTH && !line.contains('\n') {
+            return line;
+        }
+        let inner = format!("{}{}", self.indent(), INDENT);
+        let items: String = items
+            .iter()
+            .map(|item| format!("{}{},\n", inner, item))
+            .collect();
+        format!("{} {{\n{}{}}}", head, items, self.indent())
+    }
+
+    fn expr(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Value { value, token, .. } => {
+                format!("{}{}", self.comments_in(token), literal_src(value))
+            }
+            Expression::Var { name, .. } => format!("{}{}", self.comments_in(name), name.lexeme),
+            Expression::Grouping { expression, .. } => format!("({})", self.expr(expression)),
+            Expression::Unary { left, operator, .. } => {
+                let operand = self.expr(left);
+                // `- -a` must not become `--a`
+                if operand.starts_with(['-', '+', '!']) {
+                    format!("{} {}", operator.lexeme, operand)
+                } else {
+                    format!("{}{}", operator.lexeme, operand)
+                }
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.expr(left);
+                let comments = self.comments_in(operator);
+                format!("{} {}{} {}", left, comments, operator.lexeme, self.expr(right))
+            }
+            Expression::Assign {
+                name, value, kind, ..
+            } => {
+                let op = match kind {
+                    AssignKind::Normal => "=",
+                    AssignKind::Plus => "+=",
+                    AssignKind::Minus => "-=",
+                    AssignKind::Mult => "*=",
+                    AssignKind::Div => "/=",
+                };
+                format!("{} {} {}", name.lexeme, op, self.expr(value))
+            }
+            Expression::If {
+                cond,
+                body,
+                else_branch,
+                ..
+            } => {
+                let mut s = format!("if {}: {}", self.expr(cond), self.expr(body));
+                if let Some(branch) = else_branch {
+                    s.push_str(&format!(" ? {}", self.expr(branch)));
+                }
+                s
+            }
+            Expression::Match { cond, cases, .. } => {
+                self.matchs(cond, cases, |formatter, body, is_last| match is_last {
+                    true => formatter.expr(body),
+                    false => format!("{},", formatter.expr(body)),
+                })
+            }
+            Expression::Record { fields, .. } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, self.expr(value)))
+                    .collect();
+                format!("{{{}}}", fields.join(", "))
+            }
+            Expression::Vec { items, .. } => format!("[{}]", self.exprs(items)),
+            Expression::Map { items, .. } if items.is_empty() => "[:]".to_string(),
+            Expression::Map { items, .. } => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", self.expr(key), self.expr(value)))
+                    .collect();
+                format!("[{}]", items.join(", "))
+            }
+            // single item tuple keeps its comma, so it isn't read as a grouping
+            Expression::Tuple { items, .. } if items.len() == 1 => {
+                format!("({},)", self.exprs(items))
+            }
+            Expression::Tuple { items, .. } => format!("({})", self.exprs(items)),
+            Expression::Call {
+                name,
+                args,
+                call_type,
+                ..
+            } => {
+                let name = self.expr(name);
+                match call_type {
+                    CallType::Func => format!("{}({})", name, self.exprs(args)),
+                    CallType::Vector => format!("{}[{}]", name, self.exprs(args)),
+                    CallType::Struct => match args.first() {
+                        Some(Expression::Value {
+                            value: LiteralType::String(field),
+                            ..
+                        }) => format!("{}.{}", name, field),
+                        _ => name,
+                    },
+                    CallType::Enum => {
+                        let item = args.first().map(|a| self.expr(a)).unwrap_or_default();
+                        match args.get(1..) {
+                            Some(args) if !args.is_empty() => {
+                                format!("{}::{}({})", name, item, self.exprs(args))
+                            }
+                            _ => format!("{}::{}", name, item),
+                        }
+                    }
+                    CallType::Method => match args.split_first() {
+                        Some((
+                            Expression::Value {
+                                value: LiteralType::String(method),
+                                ..
+                            },
+                            args,
+                        )) => format!("{}.{}({})", name, method, self.exprs(args)),
+                        _ => name,
+                    },
+                }
+            }
+            Expression::Func {
+                name,
+                value_type,
+                body,
+                params,
+                ..
+            } => {
+                // inline callbacks declare their own types, `|a: T| T`
+                let is_inline = name.line == 0 && name.lexeme == "func";
+                let head = if params.is_empty() {
+                    "|_|".to_string()
+                } else if is_inline {
+                    format!("|{}| {}", params_src(params), type_src(value_type))
+                } else {
+                    let params: Vec<String> = params.iter().map(|(n, _)| n.lexeme.clone()).collect();
+                    format!("|{}|", params.join(", "))
+                };
+                match body.as_ref() {
+                    Statement::Block { stmts } => format!("{} {}", head, self.block(stmts)),
+                    Statement::Expression { expr } => format!("{}: {}", head, self.expr(expr)),
+                    stmt => format!("{} {}", head, self.stmt(stmt)),
+                }
+            }
+            Expression::Await { expr, .. } => format!("await {}", self.expr(expr)),
+            Expression::Range {
+                lhs,
+                rhs,
+                step,
+                is_inclusive,
+                ..
+            } => {
+                let dots = if *is_inclusive { "..=" } else { ".." };
+                let range = format!("{}{}{}", self.expr(lhs), dots, self.expr(rhs));
+                match step {
+                    Some(step) => format!("{} step {}", range, self.expr(step)),
+                    None => range,
+                }
+            }
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expression]) -> String {
+        exprs
+            .iter()
+            .map(|e| self.expr(e))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// finds the first source line of the statement, 0 if it's unknown
+fn stmt_line(stmt: &Statement) -> usize {
+    stmt.token().map_or(0, |t| t.line)
+}
+
+/// prints name of the token, or placeholder for skipped names
+fn name_or(name: &Token, placeholder: &str) -> String {
+    if name.token == TokenType::Null {
+        placeholder.to_string()
+    } else {
+        name.lexeme.clone()
+    }
+}
+
+/// `label name: ` prefix of the labeled loop
+fn label_src(label: &Option<Token>) -> String {
+    label
+        .as_ref()
+        .map_or(String::new(), |l| format!("label {}: ", l.lexeme))
+}
+
+fn params_src(params: &[(Token, Token)]) -> String {
+    params
+        .iter()
+        .map(|(name, typ)| format!("{}: {}", name.lexeme, type_src(typ)))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// prints type parameters of a declaration, `<T, U>`
+pub fn generics_src(generics: &[Token]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+    let names: Vec<String> = generics.iter().map(|g| g.lexeme.clone()).collect();
+    format!("<{}>", names.join(", "))
+}
+
+/// prints the pattern of the `match` arm, `(a, b)`, `Shape::Circle(r)`, `1..=5`
+pub fn pattern_src(pattern: &Pattern) -> String {
+    let list = |items: &[Pattern]| {
+        items
+            .iter()
+            .map(pattern_src)
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    match pattern {
+        Pattern::Wildcard { .. } => "_".to_string(),
+        Pattern::Bind { name } => name.lexeme.clone(),
+        Pattern::Value { value, .. } => literal_src(value),
+        Pattern::Range {
+            lhs,
+            rhs,
+            is_inclusive,
+            ..
+        } => {
+            let op = if *is_inclusive { "..=" } else { ".." };
+            format!("{}{}{}", literal_src(lhs), op, literal_src(rhs))
+        }
+        Pattern::Enum {
+            parent,
+            name,
+            payload,
+        } => match payload {
+            Some(payload) => format!(
+                "{}::{}({})",
+                parent.lexeme,
+                name.lexeme,
+                pattern_src(payload)
+            ),
+            None => format!("{}::{}", parent.lexeme, name.lexeme),
+        },
+        Pattern::Tuple { items, .. } if items.len() == 1 => format!("({},)", list(items)),
+        Pattern::Tuple { items, .. } => format!("({})", list(items)),
+        Pattern::Vec {
+            items, has_rest, ..
+        } => match (*has_rest, items.is_empty()) {
+            (true, true) => "[..]".to_string(),
+            (true, false) => format!("[{}, ..]", list(items)),
+            (false, _) => format!("[{}]", list(items)),
+        },
+        Pattern::Record { fields, .. } => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(name, field)| match field {
+                    Pattern::Bind { name: bind } if bind.lexeme == name.lexeme => {
+                        name.lexeme.clone()
+                    }
+                    field => format!("{}: {}", name.lexeme, pattern_src(field)),
+                })
+                .collect();
+            format!("{{{}}}", fields.join(", "))
+        }
+        Pattern::Or { patterns } => patterns
+            .iter()
+            .map(pattern_src)
+            .collect::<Vec<String>>()
+            .join(" | "),
+    }
+}
+
+/// prints type token in the same form as `Parser::consume_type` reads it
+pub fn type_src(typ: &Token) -> String {
+    match &typ.value {
+        Some(LiteralKind::Type(kind)) => typekind_src(kind),
+        _ => typ.lexeme.clone(),
+    }
+}
+
+fn typekind_src(kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::Vec { kind } => format!("Vec<{}>", typekind_src(kind)),
+        TypeKind::Map { key, value } => {
+            format!("Map<{}, {}>", typekind_src(key), typekind_src(value))
+        }
+        TypeKind::Tuple { types } => {
+            let types: Vec<String> = types.iter().map(typekind_src).collect();
+            format!("Tuple<({})>", types.join(", "))
+        }
+        TypeKind::Record { fields } => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(name, t)| format!("{}: {}", name.lexeme, typekind_src(t)))
+                .collect();
+            format!("Record<{{{}}}>", fields.join(", "))
+        }
+        TypeKind::Var { name } => type_src(name),
+        TypeKind::Either { lhs, rhs } => {
+            format!("{} || {}", typekind_src(lhs), typekind_src(rhs))
+        }
+        TypeKind::Maybe { lhs } => format!("{}?", typekind_src(lhs)),
+        TypeKind::Important { lhs } => format!("{}!", typekind_src(lhs)),
+        TypeKind::Callback { params, ret } => {
+            let params: Vec<String> = params.iter().map(typekind_src).collect();
+            format!("|{}| {}", params.join(", "), typekind_src(ret))
+        }
+        TypeKind::Generic { value, .. } => type_src(value),
+        TypeKind::Instance { name, args } => {
+            let args: Vec<String> = args.iter().map(typekind_src).collect();
+            format!("{}<{}>", name.lexeme, args.join(", "))
+        }
+        TypeKind::Literal { kind } => match kind {
+            LiteralKind::Int { value, .. } => value.to_string(),
+            LiteralKind::Float { value } => LiteralType::Float(*value).to_string(),
+            LiteralKind::String { value } => string_src(value),
+            LiteralKind::Char { value } => char_src(*value),
+            LiteralKind::Bool { value } => value.to_string(),
+            LiteralKind::Null => "null".to_string(),
+            LiteralKind::Type(t) => typekind_src(t),
+        },
+    }
+}
+
+fn literal_src(value: &LiteralType) -> String {
+    match value {
+        LiteralType::String(s) => string_src(s),
+        LiteralType::Char(c) => char_src(*c),
+        v => v.to_string(),
+    }
+}
+
+/// escapes the string, so the scanner reads the same value back
+fn string_src(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            c => out.push_str(&escape(c)),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn char_src(c: char) -> String {
+    match c {
+        '\'' => "'\\''".to_string(),
+        c => format!("'{}'", escape(c)),
+    }
+}
+
+fn escape(c: char) -> String {
+    match c {
+        '\n' => "\\n".to_string(),
+        '\t' => "\\t".to_string(),
+        '\r' => "\\r".to_string(),
+        '\\' => "\\\\".to_string(),
+        c => c.to_string(),
+    }
+}
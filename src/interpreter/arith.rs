@@ -0,0 +1,89 @@
+// integer and float arithmetic of the interpreter
+use crate::ast::{LiteralType, TokenType, TokenType::*};
+use crate::errors::ErrorCode::{self, E0x309, E0x310, E0x318};
+
+/// applies the binary operator to numbers, `None` if operands aren't numbers or
+/// the operator doesn't support them, ints overflowing 64 bits fail with E0x309
+/// and negative powers of ints with E0x318, mixed ints and floats are computed as floats
+pub fn binary(
+    left: &LiteralType,
+    operator: &TokenType,
+    right: &LiteralType,
+) -> Option<Result<LiteralType, ErrorCode>> {
+    match (left, right) {
+        (LiteralType::Int(a), LiteralType::Int(b)) => int(*a, operator, *b),
+        (LiteralType::Float(a), LiteralType::Float(b)) => float(*a, operator, *b).map(Ok),
+        (LiteralType::Int(a), LiteralType::Float(b)) => float(*a as f64, operator, *b).map(Ok),
+        (LiteralType::Float(a), LiteralType::Int(b)) => float(*a, operator, *b as f64).map(Ok),
+        _ => None,
+    }
+}
+
+/// applies the unary operator to the number
+pub fn unary(operator: &TokenType, value: &LiteralType) -> Option<Result<LiteralType, ErrorCode>> {
+    let overflow = |v: Option<i64>| Some(v.map(LiteralType::Int).ok_or(E0x309));
+    match (operator, value) {
+        (Min, LiteralType::Int(a)) => overflow(a.checked_neg()),
+        (Incr, LiteralType::Int(a)) => overflow(a.checked_add(1)),
+        (Decr, LiteralType::Int(a)) => overflow(a.checked_sub(1)),
+        (Sqr, LiteralType::Int(a)) => overflow(a.checked_mul(*a)),
+        (Tilde, LiteralType::Int(a)) => Some(Ok(LiteralType::Int(!a))),
+        (Min, LiteralType::Float(a)) => Some(Ok(LiteralType::Float(-a))),
+        (Incr, LiteralType::Float(a)) => Some(Ok(LiteralType::Float(a + 1.0))),
+        (Decr, LiteralType::Float(a)) => Some(Ok(LiteralType::Float(a - 1.0))),
+        (Sqr, LiteralType::Float(a)) => Some(Ok(LiteralType::Float(a * a))),
+        _ => None,
+    }
+}
+
+fn int(a: i64, operator: &TokenType, b: i64) -> Option<Result<LiteralType, ErrorCode>> {
+    let checked = |v: Option<i64>| Some(v.map(LiteralType::Int).ok_or(E0x309));
+    match operator {
+        Plus => checked(a.checked_add(b)),
+        Min => checked(a.checked_sub(b)),
+        Mul => checked(a.checked_mul(b)),
+        Div | Prcnt if b == 0 => Some(Err(E0x310)),
+        // integer division truncates toward zero
+        Div => checked(a.checked_div(b)),
+        Prcnt => checked(a.checked_rem(b)),
+        // negative powers of ints aren't ints, `2.0 ** -1` is the float power
+        Sqr if b < 0 => Some(Err(E0x318)),
+        Sqr => checked(u32::try_from(b).ok().and_then(|b| a.checked_pow(b))),
+        And => Some(Ok(LiteralType::Int(a & b))),
+        Pipe => Some(Ok(LiteralType::Int(a | b))),
+        Caret => Some(Ok(LiteralType::Int(a ^ b))),
+        Shl => checked(u32::try_from(b).ok().and_then(|b| a.checked_shl(b))),
+        Shr => checked(u32::try_from(b).ok().and_then(|b| a.checked_shr(b))),
+        _ => compare(&a, operator, &b),
+    }
+}
+
+fn float(a: f64, operator: &TokenType, b: f64) -> Option<LiteralType> {
+    let value = match operator {
+        Plus => a + b,
+        Min => a - b,
+        Mul => a * b,
+        Div => a / b,
+        Prcnt => a % b,
+        Sqr => a.powf(b),
+        _ => return compare(&a, operator, &b)?.ok(),
+    };
+    Some(LiteralType::Float(value))
+}
+
+fn compare<T: PartialOrd>(
+    a: &T,
+    operator: &TokenType,
+    b: &T,
+) -> Option<Result<LiteralType, ErrorCode>> {
+    let value = match operator {
+        Gr => a > b,
+        GrOrEq => a >= b,
+        Ls => a < b,
+        LsOrEq => a <= b,
+        Eq => a == b,
+        BangEq => a != b,
+        _ => return None,
+    };
+    Some(Ok(LiteralType::Boolean(value)))
+}
@@ -2,12 +2,7 @@ use crate::{
     ast::{LiteralType, Token},
     errors::{Error, ErrorCode::*},
 };
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap, process::exit, rc::Rc};
-
-type EnvValueType = Rc<RefCell<HashMap<String, ValueType>>>;
-type EnvTypeValueType = Rc<RefCell<HashMap<String, Token>>>;
-type EnvEnumValueType = Rc<RefCell<HashMap<String, Vec<(Token, Option<Token>)>>>>;
-type ModEnvValueType = Rc<RefCell<HashMap<String, Vec<(String, ValueType)>>>>;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ValueKind {
@@ -39,27 +34,46 @@ pub struct ValueType {
     pub kind: ValueKind,
 }
 
+/// local resolved to the slot of the scope `depth` scopes out of the current one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slot {
+    pub depth: usize,
+    pub index: usize,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Env {
-    pub values: EnvValueType,
-    pub pub_vals: EnvValueType,
-    pub mod_vals: ModEnvValueType,
-    pub type_values: EnvTypeValueType,
-    pub enums: EnvEnumValueType,
+    /// names of the outermost scope and imported names, they aren't resolved
+    pub values: HashMap<String, ValueType>,
+    /// values of the enclosed scope in the order of their definitions, the resolver
+    /// gives the names the same slots
+    pub slots: Vec<ValueType>,
+    /// slots of the names defined in the enclosed scope
+    pub names: HashMap<String, usize>,
+    pub pub_vals: HashMap<String, ValueType>,
+    pub mod_vals: HashMap<String, Vec<(String, ValueType)>>,
+    pub type_values: HashMap<String, Token>,
+    pub enums: HashMap<String, Vec<(Token, Option<Token>)>>,
+    /// names of the methods and types implementing them
+    pub impls: HashMap<String, Vec<Token>>,
     pub mods: Vec<Env>,
-    pub locals: Rc<RefCell<HashMap<usize, usize>>>,
+    /// slots of the resolved expressions by their ids, shared with enclosed environments
+    pub locals: Rc<RefCell<HashMap<usize, Slot>>>,
     pub enclosing: Option<Rc<RefCell<Env>>>,
     err: Error,
 }
 
 impl Env {
-    pub fn new(locals: HashMap<usize, usize>) -> Self {
+    pub fn new(locals: HashMap<usize, Slot>) -> Self {
         Self {
-            values: get_empty_rc(),
-            pub_vals: get_empty_rc(),
-            mod_vals: get_empty_md(),
-            type_values: get_empty_tc(),
-            enums: get_empty_ec(),
+            values: HashMap::new(),
+            slots: Vec::new(),
+            names: HashMap::new(),
+            pub_vals: HashMap::new(),
+            mod_vals: HashMap::new(),
+            type_values: HashMap::new(),
+            enums: HashMap::new(),
+            impls: HashMap::new(),
             mods: Vec::new(),
             locals: Rc::new(RefCell::new(locals)),
             enclosing: None,
@@ -67,30 +81,57 @@ impl Env {
         }
     }
 
-    pub fn enclose(&self) -> Env {
+    /// scope inside of the environment, the environment is shared, so names defined
+    /// or assigned through the scope are seen by the environment and by closures
+    pub fn enclose(env: &Rc<RefCell<Env>>) -> Env {
+        let parent = env.borrow();
         Self {
-            values: get_empty_rc(),
-            pub_vals: get_empty_rc(),
-            mod_vals: get_empty_md(),
-            type_values: get_empty_tc(),
-            enums: get_empty_ec(),
-            mods: self.mods.clone(),
-            locals: Rc::clone(&self.locals),
-            enclosing: Some(Rc::new(RefCell::new(self.clone()))),
-            err: Error::new(""),
+            locals: Rc::clone(&parent.locals),
+            enclosing: Some(Rc::clone(env)),
+            err: parent.err.clone(),
+            ..Self::new(HashMap::new())
         }
     }
 
-    pub fn resolve(&self, locals: HashMap<usize, usize>) {
-        for (k, v) in locals.iter() {
-            self.locals.borrow_mut().insert(*k, *v);
+    /// errors sink of the executed source, shared with enclosed environments
+    pub fn err(&self) -> Error {
+        self.err.clone()
+    }
+
+    pub fn set_err(&mut self, err: Error) {
+        self.err = err;
+    }
+
+    pub fn resolve(&mut self, locals: HashMap<usize, Slot>) {
+        self.locals.borrow_mut().extend(locals);
+    }
+
+    /// defines the name in this scope, names of enclosed scopes take the next slot
+    /// unless they are redefined
+    pub fn define(&mut self, k: String, v: ValueType) {
+        if self.enclosing.is_none() {
+            self.values.insert(k, v);
+            return;
+        }
+        match self.names.get(&k) {
+            Some(&index) => self.slots[index] = v,
+            None => {
+                self.names.insert(k, self.slots.len());
+                self.slots.push(v);
+            }
         }
     }
 
+    /// defines the name imported by `use`, imported names don't take slots,
+    /// they are looked up by name
+    pub fn import(&mut self, k: String, v: ValueType) {
+        self.values.insert(k, v);
+    }
+
     // variable value definitions
 
-    pub fn define_var(&self, k: String, v: LiteralType, f: VarKind) {
-        self.values.borrow_mut().insert(
+    pub fn define_var(&mut self, k: String, v: LiteralType, f: VarKind) {
+        self.define(
             k,
             ValueType {
                 value: v,
@@ -99,8 +140,8 @@ impl Env {
         );
     }
 
-    pub fn define_pub_var(&self, k: String, v: LiteralType, f: VarKind) {
-        self.pub_vals.borrow_mut().insert(
+    pub fn define_pub_var(&mut self, k: String, v: LiteralType, f: VarKind) {
+        self.pub_vals.insert(
             k,
             ValueType {
                 value: v,
@@ -109,9 +150,8 @@ impl Env {
         );
     }
 
-    pub fn define_mod_var(&self, source: String, f: LiteralType, k: String, v: VarKind) {
-        let mut mod_vals = self.mod_vals.borrow_mut();
-        let entry = mod_vals.entry(source).or_insert_with(Vec::new);
+    pub fn define_mod_var(&mut self, source: String, f: LiteralType, k: String, v: VarKind) {
+        let entry = self.mod_vals.entry(source).or_default();
         entry.push((
             k,
             ValueType {
@@ -123,8 +163,8 @@ impl Env {
 
     // function value definitions
 
-    pub fn define_func(&self, k: String, v: LiteralType, f: FuncKind) {
-        self.values.borrow_mut().insert(
+    pub fn define_func(&mut self, k: String, v: LiteralType, f: FuncKind) {
+        self.define(
             k,
             ValueType {
                 value: v,
@@ -133,8 +173,8 @@ impl Env {
         );
     }
 
-    pub fn define_pub_func(&self, k: String, v: LiteralType, f: FuncKind) {
-        self.pub_vals.borrow_mut().insert(
+    pub fn define_pub_func(&mut self, k: String, v: LiteralType, f: FuncKind) {
+        self.pub_vals.insert(
             k,
             ValueType {
                 value: v,
@@ -143,9 +183,8 @@ impl Env {
         );
     }
 
-    pub fn define_mod_func(&self, source: String, f: LiteralType, k: String, v: FuncKind) {
-        let mut mod_vals = self.mod_vals.borrow_mut();
-        let entry = mod_vals.entry(source).or_insert_with(Vec::new);
+    pub fn define_mod_func(&mut self, source: String, f: LiteralType, k: String, v: FuncKind) {
+        let entry = self.mod_vals.entry(source).or_default();
         entry.push((
             k,
             ValueType {
@@ -157,12 +196,12 @@ impl Env {
 
     // type value definitions
 
-    pub fn define_type(&self, k: String, v: Token) {
-        self.type_values.borrow_mut().insert(k, v);
+    pub fn define_type(&mut self, k: String, v: Token) {
+        self.type_values.insert(k, v);
     }
 
-    pub fn define_pub_type(&self, k: String, v: Token) {
-        self.pub_vals.borrow_mut().insert(
+    pub fn define_pub_type(&mut self, k: String, v: Token) {
+        self.pub_vals.insert(
             k,
             ValueType {
                 value: LiteralType::Void,
@@ -171,9 +210,8 @@ impl Env {
         );
     }
 
-    pub fn define_mod_type(&self, source: String, f: LiteralType, k: String, v: Token) {
-        let mut mod_vals = self.mod_vals.borrow_mut();
-        let entry = mod_vals.entry(source).or_insert_with(Vec::new);
+    pub fn define_mod_type(&mut self, source: String, f: LiteralType, k: String, v: Token) {
+        let entry = self.mod_vals.entry(source).or_default();
         entry.push((
             k,
             ValueType {
@@ -183,22 +221,25 @@ impl Env {
         ));
     }
 
+    /// type declared in this or an enclosing scope
     pub fn get_type(&self, k: &str) -> Token {
-        self.type_values
-            .borrow_mut()
-            .get(k)
-            .unwrap_or(&Token::null())
-            .clone()
+        if let Some(t) = self.type_values.get(k) {
+            return t.clone();
+        }
+        match &self.enclosing {
+            Some(env) => env.borrow().get_type(k),
+            None => Token::null(),
+        }
     }
 
     // enum value definitions
 
-    pub fn define_enum(&self, k: String, v: Vec<(Token, Option<Token>)>) {
-        self.enums.borrow_mut().insert(k, v);
+    pub fn define_enum(&mut self, k: String, v: Vec<(Token, Option<Token>)>) {
+        self.enums.insert(k, v);
     }
 
-    pub fn define_pub_enum(&self, k: String, v: Vec<(Token, Option<Token>)>) {
-        self.pub_vals.borrow_mut().insert(
+    pub fn define_pub_enum(&mut self, k: String, v: Vec<(Token, Option<Token>)>) {
+        self.pub_vals.insert(
             k,
             ValueType {
                 value: LiteralType::Void,
@@ -208,14 +249,13 @@ impl Env {
     }
 
     pub fn define_mod_enum(
-        &self,
+        &mut self,
         source: String,
         f: LiteralType,
         k: String,
         v: Vec<(Token, Option<Token>)>,
     ) {
-        let mut mod_vals = self.mod_vals.borrow_mut();
-        let entry = mod_vals.entry(source).or_insert_with(Vec::new);
+        let entry = self.mod_vals.entry(source).or_default();
         entry.push((
             k,
             ValueType {
@@ -226,100 +266,123 @@ impl Env {
     }
 
     pub fn get_enum(&self, k: &str) -> Vec<(Token, Option<Token>)> {
-        self.enums.borrow_mut().get(k).unwrap_or(&vec![]).clone()
+        if let Some(items) = self.enums.get(k) {
+            return items.clone();
+        }
+        match &self.enclosing {
+            Some(env) => env.borrow().get_enum(k),
+            None => vec![],
+        }
+    }
+
+    /// checks if the enum is declared in this or an enclosing scope
+    pub fn is_enum(&self, k: &str) -> bool {
+        self.enums.contains_key(k)
+            || self
+                .enclosing
+                .as_ref()
+                .is_some_and(|env| env.borrow().is_enum(k))
+    }
+
+    // method definitions
+
+    /// methods are functions named `type::method`, `t` is the name of the type
+    pub fn define_impl(&mut self, method: String, t: Token) {
+        self.impls.entry(method).or_default().push(t);
+    }
+
+    /// types implementing the method, from the innermost scope outwards
+    pub fn get_impls(&self, method: &str) -> Vec<Token> {
+        let mut types = self.impls.get(method).cloned().unwrap_or_default();
+        if let Some(env) = &self.enclosing {
+            types.extend(env.borrow().get_impls(method));
+        }
+        types
     }
 
     // global
-    pub fn remove(&self, name: String) {
-        self.values.borrow_mut().remove(&name);
+
+    /// value of the name defined in this scope
+    pub fn local(&self, name: &str) -> Option<&ValueType> {
+        self.names
+            .get(name)
+            .map(|&index| &self.slots[index])
+            .or_else(|| self.values.get(name))
     }
 
     pub fn get(&self, name: String, id: usize) -> Option<ValueType> {
-        let d = self.locals.borrow_mut().get(&id).cloned();
-        self.get_int(name.as_str(), d)
+        let slot = self.locals.borrow().get(&id).cloned();
+        self.get_int(name.as_str(), slot)
     }
 
-    pub fn get_int(&self, name: &str, d: Option<usize>) -> Option<ValueType> {
-        match d {
-            None => match &self.enclosing {
-                Some(env) => env.borrow_mut().get_int(name, None),
-                None => self
-                    .values
-                    .borrow_mut()
-                    .get(name)
-                    .cloned()
-                    .or_else(|| self.pub_vals.borrow_mut().get(name).cloned()),
-            },
-            Some(depth) => {
-                if depth == 0 {
-                    self.values.borrow_mut().get(name).cloned()
-                } else {
-                    match &self.enclosing {
-                        Some(env) => env.borrow_mut().get_int(name, Some(depth - 1)),
-                        None => {
-                            self.err.throw(E0x502, 0, (0, 0), vec![]);
-                            exit(1);
-                        }
-                    }
+    pub fn get_int(&self, name: &str, slot: Option<Slot>) -> Option<ValueType> {
+        match slot {
+            // unresolved names are looked up from the innermost scope outwards
+            None => {
+                let value = self
+                    .local(name)
+                    .or_else(|| self.pub_vals.get(name))
+                    .cloned();
+                match (value, &self.enclosing) {
+                    (Some(value), _) => Some(value),
+                    (None, Some(env)) => env.borrow().get_int(name, None),
+                    (None, None) => None,
                 }
             }
+            // the slot is empty until the statement defining the name runs
+            Some(Slot { depth: 0, index }) => self.slots.get(index).cloned(),
+            Some(Slot { depth, index }) => match &self.enclosing {
+                Some(env) => {
+                    let slot = Slot {
+                        depth: depth - 1,
+                        index,
+                    };
+                    env.borrow().get_int(name, Some(slot))
+                }
+                None => self.err.throw(E0x502, 0, (0, 0), vec![]),
+            },
         }
     }
 
-    pub fn assing(&self, name: String, value: ValueType, id: usize) -> bool {
-        let d = self.locals.borrow_mut().get(&id).cloned();
-        self.set_int(name.as_str(), value, d)
+    pub fn assing(&mut self, name: String, value: ValueType, id: usize) -> bool {
+        let slot = self.locals.borrow().get(&id).cloned();
+        self.set_int(name.as_str(), value, slot)
     }
 
-    pub fn set_int(&self, name: &str, value: ValueType, d: Option<usize>) -> bool {
-        if d.is_none() {
-            match &self.enclosing {
-                Some(env) => env.borrow_mut().set_int(name, value, d),
-                None => self
-                    .borrow()
-                    .values
-                    .borrow_mut()
-                    .insert(name.to_string(), value)
-                    .is_some(),
-            }
-        } else {
-            let d = match d {
-                Some(d) => d,
-                None => {
-                    self.err.throw(E0x501, 0, (0, 0), vec![]);
-                    exit(1)
+    pub fn set_int(&mut self, name: &str, value: ValueType, slot: Option<Slot>) -> bool {
+        match slot {
+            None => {
+                if let Some(&index) = self.names.get(name) {
+                    self.slots[index] = value;
+                    return true;
+                }
+                if self.values.contains_key(name) {
+                    return self.values.insert(name.to_string(), value).is_some();
                 }
-            };
-            if d <= 0 {
-                self.values.borrow_mut().remove(name);
-                true
-            } else {
                 match &self.enclosing {
-                    Some(env) => env.borrow_mut().set_int(name, value, Some(d - 1)),
-                    None => {
-                        self.err.throw(E0x502, 0, (0, 0), vec![]);
-                        exit(1);
-                    }
+                    Some(env) => env.borrow_mut().set_int(name, value, None),
+                    None => self.values.insert(name.to_string(), value).is_some(),
                 }
             }
+            Some(Slot { depth: 0, index }) => match self.slots.get_mut(index) {
+                Some(local) => {
+                    *local = value;
+                    true
+                }
+                None => false,
+            },
+            Some(Slot { depth, index }) => match &self.enclosing {
+                Some(env) => {
+                    let slot = Slot {
+                        depth: depth - 1,
+                        index,
+                    };
+                    env.borrow_mut().set_int(name, value, Some(slot))
+                }
+                None => self.err.throw(E0x501, 0, (0, 0), vec![]),
+            },
         }
     }
 }
 
-fn get_empty_rc() -> EnvValueType {
-    Rc::new(RefCell::new(HashMap::new()))
-}
-
-fn get_empty_tc() -> EnvTypeValueType {
-    Rc::new(RefCell::new(HashMap::new()))
-}
-
-fn get_empty_ec() -> EnvEnumValueType {
-    Rc::new(RefCell::new(HashMap::new()))
-}
-
-fn get_empty_md() -> ModEnvValueType {
-    Rc::new(RefCell::new(HashMap::new()))
-}
-
 // @todo better memory manager
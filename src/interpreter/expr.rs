@@ -1,16 +1,17 @@
 use super::env::{Env, ValueKind, ValueType, VarKind};
 use super::types::TypeKind;
-use crate::ast::{LiteralKind, Statement};
+use crate::ast::{LiteralKind, MatchCase, Statement};
 use crate::bundler::parse_expr;
-use crate::errors::{Error, ErrorCode::*};
+use crate::errors::{Error, ErrorCode, ErrorCode::*};
+use crate::interpreter::arith;
 use crate::interpreter::types::{type_check, typekind_to_literaltype};
 use crate::{
-    ast::{CallType, FuncImpl, LiteralType, Token, TokenType::*},
-    interpreter::run_func,
+    ast::{CallType, DeclrFuncType, FuncImpl, LiteralType, RangeType, Token, TokenType::*},
+    formatter::pattern_src,
+    interpreter::{pattern, run_func, task},
 };
 use core::cmp::Eq;
-use std::process::exit;
-use std::{cell::RefCell, fmt, rc::Rc};
+use std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum AssignKind {
@@ -47,6 +48,12 @@ pub enum Expression {
         id: usize,
         items: Vec<Expression>,
     },
+    Map {
+        id: usize,
+        /// `[` of the literal, invalid keys are reported at it
+        token: Token,
+        items: Vec<(Expression, Expression)>,
+    },
     Var {
         id: usize,
         name: Token,
@@ -74,6 +81,8 @@ pub enum Expression {
     },
     Value {
         id: usize,
+        /// token of the literal, values made by the interpreter have `Token::null()`
+        token: Token,
         value: LiteralType,
     },
     Func {
@@ -87,21 +96,31 @@ pub enum Expression {
     },
     Await {
         id: usize,
+        /// the `await` keyword
+        token: Token,
         expr: Box<Expression>,
     },
+    Match {
+        id: usize,
+        /// `match` keyword, unmatched values are reported at it
+        token: Token,
+        cond: Box<Expression>,
+        cases: Vec<MatchCase<Expression>>,
+    },
     Range {
         id: usize,
-        lhs: usize,
-        rhs: usize,
+        /// `..` or `..=`, errors of the range point at it
+        token: Token,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+        step: Option<Box<Expression>>,
+        /// `..=` includes `rhs`
+        is_inclusive: bool,
     },
 }
 
 impl Eq for Expression {}
 impl Expression {
-    fn err(&self) -> Error {
-        Error::new("")
-    }
-
     pub fn id(&self) -> usize {
         match self {
             Expression::Range { id, .. } => *id,
@@ -111,6 +130,7 @@ impl Expression {
             Expression::Call { id, .. } => *id,
             Expression::Func { id, .. } => *id,
             Expression::Vec { id, .. } => *id,
+            Expression::Map { id, .. } => *id,
             Expression::Await { id, .. } => *id,
             Expression::Binary { id, .. } => *id,
             Expression::Unary { id, .. } => *id,
@@ -118,6 +138,28 @@ impl Expression {
             Expression::Grouping { id, .. } => *id,
             Expression::Assign { id, .. } => *id,
             Expression::If { id, .. } => *id,
+            Expression::Match { id, .. } => *id,
+        }
+    }
+
+    /// first token of the expression, used to locate it in the source
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            Expression::Var { name, .. } | Expression::Assign { name, .. } => Some(name),
+            Expression::Map { token, .. } => Some(token),
+            Expression::Call { name, .. } => name.token(),
+            Expression::Binary { left, .. } => left.token(),
+            Expression::Unary { operator, .. } => Some(operator),
+            Expression::Grouping { expression, .. } => expression.token(),
+            Expression::If { cond, .. } => cond.token(),
+            Expression::Await { token, .. } | Expression::Match { token, .. } => Some(token),
+            Expression::Vec { items, .. } | Expression::Tuple { items, .. } => {
+                items.first().and_then(|i| i.token())
+            }
+            Expression::Record { fields, .. } => fields.first().and_then(|(_, v)| v.token()),
+            Expression::Range { lhs, .. } => lhs.token(),
+            Expression::Value { token, .. } => Some(token).filter(|t| t.line != 0),
+            Expression::Func { .. } => None,
         }
     }
 
@@ -130,10 +172,25 @@ impl Expression {
 
     pub fn eval(&self, env: Rc<RefCell<Env>>) -> LiteralType {
         match self {
-            Expression::Range { lhs, rhs, .. } => {
-                let range = lhs.clone()..rhs.clone();
-                let vec: Vec<LiteralType> = range.map(|x| LiteralType::Number(x as f32)).collect();
-                LiteralType::Vec(vec)
+            Expression::Range {
+                token,
+                lhs,
+                rhs,
+                step,
+                is_inclusive,
+                ..
+            } => {
+                let step = match step {
+                    Some(step) => range_bound(step, step.eval(Rc::clone(&env)), token, &env),
+                    None => 1,
+                };
+                let step = range_step(step, token, &env);
+                LiteralType::Range(RangeType {
+                    lhs: range_bound(lhs, lhs.eval(Rc::clone(&env)), token, &env),
+                    rhs: range_bound(rhs, rhs.eval(Rc::clone(&env)), token, &env),
+                    step,
+                    is_inclusive: *is_inclusive,
+                })
             }
             Expression::If {
                 cond,
@@ -150,148 +207,31 @@ impl Expression {
 
                 LiteralType::Null
             }
-            Expression::Record { fields, .. } => LiteralType::Record(fields.clone()),
-            Expression::Assign {
-                name, value, kind, ..
+            Expression::Match {
+                token, cond, cases, ..
             } => {
-                let mut val = (*value).eval(Rc::clone(&env));
-                let mut is_mut = false;
-                match env.borrow().get(name.lexeme.clone(), self.id()) {
-                    Some(v) => match v.clone().kind {
-                        ValueKind::Var(s) => {
-                            if !s.is_mut {
-                                self.err().throw(E0x410, name.line, name.pos, vec![]);
-                            }
-                            is_mut = true;
-                            if s.is_pub {
-                                self.err().throw(E0x411, name.line, name.pos, vec![]);
-                            }
-                            match v.value {
-                                LiteralType::Number(x) => match kind {
-                                    AssignKind::Plus => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n + x);
-                                        }
-                                        _ => {}
-                                    },
-                                    AssignKind::Minus => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n - x);
-                                        }
-                                        _ => {}
-                                    },
-                                    AssignKind::Mult => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n * x);
-                                        }
-                                        _ => {}
-                                    },
-                                    AssignKind::Div => match val {
-                                        LiteralType::Number(n) => {
-                                            val = LiteralType::Number(n / x);
-                                        }
-                                        _ => {}
-                                    },
-                                    _ => {}
-                                },
-                                _ => {
-                                    if kind.clone() != AssignKind::Normal {
-                                        self.err().throw(E0x414, name.line, name.pos, vec![]);
-                                        exit(1);
-                                    }
-                                }
-                            }
-
-                            if v.value.type_name() != val.type_name() {
-                                if let ValueKind::Var(s) = v.kind {
-                                    if let Some(LiteralKind::Type(c)) = s.value_type.value.clone() {
-                                        if let TypeKind::Either { lhs, rhs } = *c {
-                                            let left_true = if let TypeKind::Var { name } = *lhs {
-                                                type_check(&name, &val, &env)
-                                            } else {
-                                                false
-                                            };
-
-                                            let right_true = if let TypeKind::Var { name } = *rhs {
-                                                type_check(&name, &val, &env)
-                                            } else {
-                                                false
-                                            };
-
-                                            if !left_true && !right_true {
-                                                self.err().throw(
-                                                    E0x412,
-                                                    name.line,
-                                                    name.pos,
-                                                    vec![name.clone().lexeme],
-                                                );
-                                            }
-                                        } else {
-                                            let expected_type = typekind_to_literaltype(*c);
-                                            if val != expected_type {
-                                                self.err().throw(
-                                                    E0x412,
-                                                    name.line,
-                                                    name.pos,
-                                                    vec![name.clone().lexeme],
-                                                );
-                                            }
-                                        }
-                                    } else {
-                                        self.err().throw(
-                                            E0x412,
-                                            name.line,
-                                            name.pos,
-                                            vec![name.clone().lexeme],
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                        _ => {
-                            self.err().throw(E0x413, name.line, name.pos, vec![]);
-                        }
-                    },
-                    None => {}
-                }
-                let ass_val = ValueType {
-                    kind: ValueKind::Var(VarKind {
-                        is_mut,
-                        is_pub: false,
-                        is_func: false,
-                        value_type: name.clone(),
-                    }),
-                    value: val.clone(),
-                };
-                let assigned = env
-                    .borrow_mut()
-                    .assing(name.lexeme.clone(), ass_val, self.id());
-
-                if assigned {
-                    val
-                } else {
-                    self.err().throw(E0x414, name.line, name.pos, vec![]);
-                    exit(1);
-                }
-            }
-            Expression::Var { name, .. } => {
-                let lexeme = name.lexeme.as_str();
-                let env_borrow = env.borrow();
-
-                if let Some(v) = env_borrow.get(name.lexeme.clone(), self.id()) {
-                    v.value.clone()
-                } else if let Some(v) = env_borrow.values.borrow().get(lexeme) {
-                    v.value.clone()
-                } else if env_borrow.enums.borrow().contains_key(lexeme) {
-                    LiteralType::Enum {
-                        parent: name.clone(),
-                        name: Token::null(),
-                        value: None,
-                    }
-                } else {
-                    LiteralType::Null
-                }
+                let value = cond.eval(Rc::clone(&env));
+                pattern::run(cases, &value, &env, |body, arm_env| body.eval(Rc::clone(arm_env)))
+                    .unwrap_or_else(|| pattern::unmatched(&value, token, &env))
             }
+            // fields are evaluated in the scope of the literal
+            Expression::Record { fields, .. } => LiteralType::Record(Rc::new(
+                fields
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = Expression::Value {
+                            id: value.id(),
+                            token: Token::null(),
+                            value: value.eval(Rc::clone(&env)),
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect(),
+            )),
+            Expression::Assign {
+                name, value, kind, ..
+            } => assign(name, self.id(), kind, value.eval(Rc::clone(&env)), &env),
+            Expression::Var { name, .. } => variable(name, self.id(), &env),
 
             Expression::Call {
                 name,
@@ -299,7 +239,23 @@ impl Expression {
                 call_type,
                 ..
             } => {
+                if let CallType::Method = call_type {
+                    return method_call(name, args, &env);
+                }
                 let call: LiteralType = name.eval(Rc::clone(&env));
+                // `type::func(args)` of the `impl` block, enums without the function are items
+                if let (CallType::Enum, Expression::Var { name: parent, .. }) =
+                    (call_type, name.as_ref())
+                {
+                    if let Some(func) = static_func(parent, &args[0], &env) {
+                        return run_func(func, &args[1..], env, name);
+                    }
+                    if !matches!(call, LiteralType::Enum { .. }) {
+                        let err = env.borrow().err();
+                        let args = vec![parent.lexeme.clone(), args[0].to_string()];
+                        err.fail(err.diagnostic(E0x317, parent.line, parent.pos, args))
+                    }
+                }
                 match call {
                     LiteralType::Enum { .. } => {
                         if let CallType::Enum = call_type {
@@ -308,17 +264,13 @@ impl Expression {
                             } else {
                                 Token::null()
                             };
-                            let name = if let Expression::Var { name, .. } = args.get(0).unwrap() {
+                            let name = if let Expression::Var { name, .. } = &args[0] {
                                 name.clone()
                             } else {
                                 Token::null()
                             };
 
-                            let value = if let Some(v) = args.get(1) {
-                                Some(Box::new(v.eval(env)))
-                            } else {
-                                None
-                            };
+                            let value = args.get(1).map(|v| Box::new(v.eval(env)));
 
                             return LiteralType::Enum {
                                 parent,
@@ -329,101 +281,41 @@ impl Expression {
 
                         LiteralType::Null
                     }
-                    LiteralType::Func(func) => run_func(func, args, env),
+                    LiteralType::Func(func) => run_func(func, args, env, name),
                     LiteralType::DeclrFunc(func) => {
-                        let evals = args
-                            .iter()
-                            .map(|arg| Some(arg.eval(Rc::clone(&env))))
-                            .collect();
-
-                        (*func.func).call(evals)
+                        let evals = args.iter().map(|arg| arg.eval(Rc::clone(&env))).collect();
+                        let at = name.token().cloned().unwrap_or(Token::null());
+                        call_native(&func, args.first(), evals, &at, &env)
                     }
-                    LiteralType::Vec(res) => match args.get(0).unwrap() {
-                        Expression::Value { value, .. } => {
-                            if let LiteralType::Number(n) = value {
-                                res.get(*n as usize).unwrap().clone()
-                            } else {
-                                LiteralType::Null
-                            }
-                        }
-                        _ => LiteralType::Null,
-                    },
-                    LiteralType::Tuple(res) => match args.get(0).unwrap() {
-                        Expression::Value { value, .. } => {
-                            if let LiteralType::Number(n) = value {
-                                res.get(*n as usize).unwrap().clone()
-                            } else {
-                                LiteralType::Null
-                            }
-                        }
-                        _ => LiteralType::Null,
-                    },
-                    LiteralType::String(s) => match args.get(0).unwrap().eval(env) {
-                        LiteralType::Number(n) => {
-                            let mut res = LiteralType::Null;
-                            for (i, c) in s.chars().enumerate() {
-                                if i == n as usize {
-                                    res = LiteralType::Char(c);
-                                }
-                            }
-                            res
-                        }
-                        _ => LiteralType::Null,
-                    },
-                    LiteralType::Record(rec) => match args.get(0).unwrap() {
-                        Expression::Value { value, .. } => {
-                            if let LiteralType::String(s) = value {
-                                let mut res = LiteralType::Null;
-                                for (k, v) in rec {
-                                    if k == *s {
-                                        res = v.eval(Rc::clone(&env));
-                                    }
-                                }
-                                res
-                            } else {
-                                LiteralType::Null
-                            }
-                        }
+                    // fields of records are named by the literals
+                    LiteralType::Record(_) => match &args[0] {
+                        Expression::Value { value, .. } => item(&call, value, &env),
                         _ => LiteralType::Null,
                     },
+                    value @ (LiteralType::Vec(_)
+                    | LiteralType::Tuple(_)
+                    | LiteralType::Range(_)
+                    | LiteralType::Map(_)
+                    | LiteralType::String(_)) => item(&value, &args[0].eval(Rc::clone(&env)), &env),
                     _ => LiteralType::Null,
                 }
             }
             Expression::Grouping { expression, .. } => expression.eval(env),
-            Expression::Value { value, .. } => {
-                let v = match value.clone() {
-                    LiteralType::String(s) => {
-                        let mut result = String::new();
-                        let mut idx = 0;
-
-                        while let Some(start) = s[idx..].find('{') {
-                            result.push_str(&s[idx..idx + start]);
-                            let start_idx = idx + start + 1;
-
-                            if let Some(end) = s[start_idx..].find('}') {
-                                let expr = &s[start_idx..start_idx + end];
-                                let eval_result =
-                                    match parse_expr(expr, self.err()).eval(Rc::clone(&env)) {
-                                        LiteralType::String(eval_s) => eval_s,
-                                        LiteralType::Number(eval_n) => eval_n.to_string(),
-                                        LiteralType::Boolean(eval_b) => eval_b.to_string(),
-                                        _ => "null".to_string(),
-                                    };
-
-                                result.push_str(&eval_result);
-                                idx = start_idx + end + 1;
-                            } else {
-                                break;
-                            }
-                        }
-
-                        result.push_str(&s[idx..]);
-                        LiteralType::String(result)
+            Expression::Value { value, .. } => match value {
+                // `{expr}` placeholders are evaluated in the scope of the string
+                LiteralType::String(s) if s.contains('{') => {
+                    let (parts, rest) = placeholders(s);
+                    let mut result = String::new();
+                    for (text, expr) in parts {
+                        result.push_str(&text);
+                        let value = parse_expr(&expr, Error::new(&expr)).eval(Rc::clone(&env));
+                        result.push_str(&placeholder(value));
                     }
-                    c => c,
-                };
-                v.clone()
-            }
+                    result.push_str(&rest);
+                    LiteralType::String(Rc::new(result))
+                }
+                value => value.clone(),
+            },
 
             Expression::Func {
                 name,
@@ -436,38 +328,11 @@ impl Expression {
             } => {
                 let call = FuncImpl {
                     name: name.lexeme.clone(),
+                    token: name.clone(),
+                    generics: vec![],
                     value_type: value_type.clone(),
-                    body: Box::new(match *body.clone() {
-                        Statement::Block { stmts } => {
-                            stmts.iter().for_each(|stmt| {
-                                if let Statement::Return { expr } = stmt {
-                                    let v = &(*expr).eval(Rc::clone(&env));
-                                    if !type_check(value_type, v, &env) {
-                                        self.err().throw(
-                                            E0x301,
-                                            name.line,
-                                            name.pos,
-                                            vec![value_type.clone().lexeme, v.to_string()],
-                                        );
-                                    }
-                                }
-                            });
-                            *body.clone()
-                        }
-                        Statement::Expression { expr } => {
-                            let v = &(expr).eval(Rc::clone(&env));
-                            if !type_check(value_type, v, &env) {
-                                self.err().throw(
-                                    E0x301,
-                                    name.line,
-                                    name.pos,
-                                    vec![value_type.clone().lexeme, v.to_string()],
-                                );
-                            }
-                            *body.clone()
-                        }
-                        _ => Statement::Break {},
-                    }),
+                    // return values are type checked when the callback is called
+                    body: Rc::from(body.clone()),
                     params: params
                         .iter()
                         .map(|(name, value_type)| (name.clone(), value_type.clone()))
@@ -475,17 +340,16 @@ impl Expression {
                     is_pub: *is_pub,
                     is_async: *is_async,
                     env: Rc::clone(&env),
+                    code: None,
                 };
-                let func = LiteralType::Func(call);
-                func
+                LiteralType::Func(call)
             }
-            Expression::Vec { items, .. } => LiteralType::Vec(
+            Expression::Vec { items, .. } => LiteralType::Vec(Rc::new(
                 items
                     .iter()
                     .map(|f| f.eval(Rc::clone(&env)))
-                    .collect::<Vec<LiteralType>>()
-                    .clone(),
-            ),
+                    .collect::<Vec<LiteralType>>(),
+            )),
             Expression::Tuple { items, .. } => LiteralType::Tuple(
                 items
                     .iter()
@@ -493,177 +357,511 @@ impl Expression {
                     .collect::<Vec<LiteralType>>()
                     .clone(),
             ),
-            Expression::Await { .. } => LiteralType::Null,
+            Expression::Map { token, items, .. } => {
+                let mut map = BTreeMap::new();
+                for (key, value) in items {
+                    let key = key.eval(Rc::clone(&env));
+                    match key.to_key() {
+                        Some(k) => map.insert(k, value.eval(Rc::clone(&env))),
+                        None => {
+                            let err = env.borrow().err();
+                            let args = vec![key.type_name()];
+                            err.fail(err.diagnostic(E0x311, token.line, token.pos, args))
+                        }
+                    };
+                }
+                LiteralType::Map(Rc::new(map))
+            }
+            // other values are awaited as they are
+            Expression::Await { token, expr, .. } => match expr.eval(Rc::clone(&env)) {
+                LiteralType::Future(future) => task::block_on(&future, &env, token),
+                value => value,
+            },
             Expression::Binary {
                 left,
                 operator,
                 right,
                 ..
-            } => self.eval_binary(left, operator, right, env),
-            Expression::Unary { operator, left, .. } => self.eval_unary(operator, left, env),
+            } => {
+                let left = left.eval(Rc::clone(&env));
+                binary(left, operator, right.eval(Rc::clone(&env)), &env)
+            }
+            Expression::Unary { operator, left, .. } => {
+                unary(operator, left.eval(Rc::clone(&env)), &env)
+            }
         }
     }
+}
+
+/// applies the unary operator to the value, errors point at the operator
+pub fn unary(operator: &Token, left: LiteralType, env: &Rc<RefCell<Env>>) -> LiteralType {
+    if let Some(result) = arith::unary(&operator.token, &left) {
+        return number(result, operator, env);
+    }
+    match (operator.clone().token, left.clone()) {
+        (Bang, _) => LiteralType::Boolean(!left.is_truthy()),
+        (DblBang, _) => LiteralType::Boolean(left.is_truthy()),
+        _ => LiteralType::Null,
+    }
+}
 
-    fn eval_unary(
-        &self,
-        operator: &Token,
-        left: &Expression,
-        env: Rc<RefCell<Env>>,
-    ) -> LiteralType {
-        let left = left.eval(Rc::clone(&env));
-        match (operator.clone().token, left.clone()) {
-            (Min, LiteralType::Number(a)) => LiteralType::Number(-a),
-            (Bang, _) => LiteralType::Boolean(!left.is_truthy()),
-            (DblBang, _) => LiteralType::Boolean(!!left.is_truthy()),
-            (Sqr, LiteralType::Number(a)) => LiteralType::Number(a * a),
-            (Decr, LiteralType::Number(a)) => LiteralType::Number(a - 1.0),
-            (Incr, LiteralType::Number(a)) => LiteralType::Number(a + 1.0),
-            _ => LiteralType::Null,
+/// applies the binary operator to the values, errors point at the operator
+pub fn binary(
+    left: LiteralType,
+    operator: &Token,
+    right: LiteralType,
+    env: &Rc<RefCell<Env>>,
+) -> LiteralType {
+    if let Some(result) = arith::binary(&left, &operator.token, &right) {
+        return number(result, operator, env);
+    }
+    match (left.clone(), operator.token.clone(), right.clone()) {
+        (_, Or, _) if left.is_truthy() => left,
+        (_, Or, _) => right,
+        (_, DblAnd, _) if !left.is_truthy() => left.is_truthy_literal(),
+        (_, DblAnd, _) => right,
+        // operations of the number and the vector apply to every item
+        (
+            LiteralType::Int(_) | LiteralType::Float(_),
+            Plus | Min | Mul | Div,
+            LiteralType::Vec(v),
+        ) => {
+            let items = v
+                .iter()
+                .map(|item| match arith::binary(item, &operator.token, &left) {
+                    Some(result) => number(result, operator, env),
+                    None => LiteralType::Null,
+                })
+                .collect();
+            LiteralType::Vec(Rc::new(items))
         }
+        (LiteralType::String(a), Plus, LiteralType::String(b)) => {
+            LiteralType::String(Rc::new(format!("{}{}", a, b)))
+        }
+        (LiteralType::String(a), Eq, LiteralType::String(b)) => LiteralType::Boolean(a == b),
+        (LiteralType::String(a), BangEq, LiteralType::String(b)) => LiteralType::Boolean(a != b),
+        (LiteralType::Char(a), Eq, LiteralType::Char(b)) => LiteralType::Boolean(a == b),
+        (LiteralType::Char(a), BangEq, LiteralType::Char(b)) => LiteralType::Boolean(a != b),
+        (LiteralType::Boolean(a), Eq, LiteralType::Boolean(b)) => LiteralType::Boolean(a == b),
+        (LiteralType::Boolean(a), BangEq, LiteralType::Boolean(b)) => LiteralType::Boolean(a != b),
+        (LiteralType::Map(a), Eq, LiteralType::Map(b)) => LiteralType::Boolean(a == b),
+        (LiteralType::Map(a), BangEq, LiteralType::Map(b)) => LiteralType::Boolean(a != b),
+        (LiteralType::Null, Eq, LiteralType::Null) => LiteralType::Boolean(true),
+        (LiteralType::Null, BangEq, LiteralType::Null) => LiteralType::Boolean(false),
+        (_, Eq, _) => LiteralType::Boolean(false),
+        (_, BangEq, _) => LiteralType::Boolean(false),
+        _ => LiteralType::Null,
     }
+}
 
-    fn eval_binary(
-        &self,
-        left: &Expression,
-        operator: &Token,
-        right: &Expression,
-        env: Rc<RefCell<Env>>,
-    ) -> LiteralType {
-        let left = left.eval(Rc::clone(&env));
-        let right = right.eval(Rc::clone(&env));
-        match (left.clone(), operator.token.clone(), right.clone()) {
-            (_, Or, _) => {
-                if left.is_truthy() {
-                    return left;
-                }
-                return right;
-            }
-            (_, DblAnd, _) => {
-                if !left.is_truthy() {
-                    return left.is_truthy_literal();
-                }
-                return right;
-            }
-            (LiteralType::Number(a), Prcnt, LiteralType::Number(b)) => {
-                return LiteralType::Number(a % b);
-            }
-            (LiteralType::Number(a), Mul, LiteralType::Number(b)) => {
-                return LiteralType::Number(a * b);
-            }
-            (LiteralType::Number(a), Min, LiteralType::Number(b)) => {
-                return LiteralType::Number(a - b);
-            }
-            (LiteralType::Number(a), Div, LiteralType::Number(b)) => {
-                return LiteralType::Number(a / b);
-            }
-            (LiteralType::Number(a), Plus, LiteralType::Number(b)) => {
-                return LiteralType::Number(a + b);
-            }
-            (LiteralType::Number(a), Gr, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a > b);
-            }
-            (LiteralType::Number(a), GrOrEq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a >= b);
-            }
-            (LiteralType::Number(a), Ls, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a < b);
-            }
-            (LiteralType::Number(a), LsOrEq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a <= b);
-            }
-            (LiteralType::Number(a), Eq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::Number(a), BangEq, LiteralType::Number(b)) => {
-                return LiteralType::Boolean(a != b);
-            }
-            (LiteralType::Number(a), Plus, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() + a)
-                        } else {
-                            LiteralType::Null
-                        }
-                    })
-                    .collect();
+/// value of the variable, unknown names are enums or null
+pub fn variable(name: &Token, id: usize, env: &Rc<RefCell<Env>>) -> LiteralType {
+    let lexeme = name.lexeme.as_str();
+    let env_borrow = env.borrow();
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::Number(a), Min, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() - a)
-                        } else {
-                            LiteralType::Null
-                        }
-                    })
-                    .collect();
+    if let Some(v) = env_borrow.get(name.lexeme.clone(), id) {
+        v.value
+    } else if let Some(v) = env_borrow.values.get(lexeme) {
+        v.value.clone()
+    } else if env_borrow.is_enum(lexeme) {
+        LiteralType::Enum {
+            parent: name.clone(),
+            name: Token::null(),
+            value: None,
+        }
+    } else {
+        LiteralType::Null
+    }
+}
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::Number(a), Mul, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() * a)
-                        } else {
-                            LiteralType::Null
-                        }
-                    })
-                    .collect();
+/// ranges passed to natives are collected into vectors, longer ones would take
+/// the whole memory
+const MAX_COLLECTED: u64 = 10_000_000;
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::Number(a), Div, LiteralType::Vec(v)) => {
-                let nums = v
-                    .iter()
-                    .map(|p| {
-                        if let LiteralType::Number(c) = p {
-                            LiteralType::Number(c.clone() / a)
-                        } else {
-                            LiteralType::Null
-                        }
-                    })
-                    .collect();
+/// collects the range passed to the native, ranges longer than `MAX_COLLECTED`
+/// fail with E0x319 at the call
+pub fn native_arg(value: LiteralType, at: &Token, err: &Error) -> LiteralType {
+    match value {
+        LiteralType::Range(range) if range.len() > MAX_COLLECTED => err.throw(
+            E0x319,
+            at.line,
+            at.pos,
+            vec![range.to_string(), MAX_COLLECTED.to_string()],
+        ),
+        value => value.collect(),
+    }
+}
 
-                return LiteralType::Vec(nums);
-            }
-            (LiteralType::String(a), Eq, LiteralType::String(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::String(a), BangEq, LiteralType::String(b)) => {
-                return LiteralType::Boolean(a != b);
-            }
-            (LiteralType::Char(a), Eq, LiteralType::Char(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::Char(a), BangEq, LiteralType::Char(b)) => {
-                return LiteralType::Boolean(a != b);
-            }
-            (LiteralType::Boolean(a), Eq, LiteralType::Boolean(b)) => {
-                return LiteralType::Boolean(a == b);
-            }
-            (LiteralType::Boolean(a), BangEq, LiteralType::Boolean(b)) => {
-                return LiteralType::Boolean(a != b);
-            }
-            (LiteralType::Null, Eq, LiteralType::Null) => {
-                return LiteralType::Boolean(true);
-            }
-            (LiteralType::Null, BangEq, LiteralType::Null) => {
-                return LiteralType::Boolean(false);
-            }
-            (_, Eq, _) => {
-                return LiteralType::Boolean(false);
+/// calls the native function with the evaluated arguments, natives changing their first
+/// argument change `mut` variables passed as it in place
+pub fn call_native(
+    func: &DeclrFuncType,
+    first: Option<&Expression>,
+    values: Vec<LiteralType>,
+    at: &Token,
+    env: &Rc<RefCell<Env>>,
+) -> LiteralType {
+    let err = env.borrow().err();
+    let mut values: Vec<Option<LiteralType>> = values
+        .into_iter()
+        .map(|v| Some(native_arg(v, at, &err)))
+        .collect();
+    if !func.func.is_mut() {
+        return func.func.call_at(values, at);
+    }
+    let mut value = match values.is_empty() {
+        true => LiteralType::Null,
+        false => values.remove(0).unwrap_or(LiteralType::Null),
+    };
+    let Some(Expression::Var { name, id }) = first else {
+        return func.func.call_mut(&mut value, values, at, &err);
+    };
+    let kind = match env.borrow().get(name.lexeme.clone(), *id) {
+        Some(ValueType {
+            kind: kind @ ValueKind::Var(VarKind { is_mut: true, .. }),
+            ..
+        }) => kind,
+        _ => return func.func.call_mut(&mut value, values, at, &err),
+    };
+    // the variable gives its value up while the function runs, so the value isn't shared
+    // and isn't copied when it changes
+    let taken = ValueType {
+        value: LiteralType::Null,
+        kind: kind.clone(),
+    };
+    env.borrow_mut().assing(name.lexeme.clone(), taken, *id);
+    let result = func.func.call_mut(&mut value, values, at, &err);
+    env.borrow_mut()
+        .assing(name.lexeme.clone(), ValueType { value, kind }, *id);
+    result
+}
+
+/// assigns the value to the variable in the environment, returns the assigned value
+pub fn assign(
+    name: &Token,
+    id: usize,
+    kind: &AssignKind,
+    val: LiteralType,
+    env: &Rc<RefCell<Env>>,
+) -> LiteralType {
+    let mut val = val;
+    let old = env.borrow().get(name.lexeme.clone(), id);
+    let is_mut = old.is_some();
+    if let Some(old) = old {
+        val = assigned(name, &old.value, &old.kind, kind, val, env);
+    }
+    let ass_val = ValueType {
+        kind: ValueKind::Var(VarKind {
+            is_mut,
+            is_pub: false,
+            is_func: false,
+            value_type: name.clone(),
+        }),
+        value: val.clone(),
+    };
+    let assigned = env.borrow_mut().assing(name.lexeme.clone(), ass_val, id);
+
+    if assigned {
+        val
+    } else {
+        env.borrow().err().throw(E0x414, name.line, name.pos, vec![])
+    }
+}
+
+/// value assigned to the variable by `name = val` or the compound assignment,
+/// fails if the variable can't take it
+pub fn assigned(
+    name: &Token,
+    old: &LiteralType,
+    old_kind: &ValueKind,
+    kind: &AssignKind,
+    val: LiteralType,
+    env: &Rc<RefCell<Env>>,
+) -> LiteralType {
+    let mut val = val;
+    let ValueKind::Var(s) = old_kind else {
+        let err = env.borrow().err();
+        err.fail(err.diagnostic(E0x413, name.line, name.pos, vec![]))
+    };
+    if s.is_pub {
+        env.borrow().err().throw(E0x411, name.line, name.pos, vec![]);
+    }
+    if !s.is_mut {
+        env.borrow().err().throw(E0x410, name.line, name.pos, vec![]);
+    }
+    // compound assignments compute `name op value`
+    let operator = match kind {
+        AssignKind::Normal => None,
+        AssignKind::Plus => Some(Plus),
+        AssignKind::Minus => Some(Min),
+        AssignKind::Mult => Some(Mul),
+        AssignKind::Div => Some(Div),
+    };
+    if let Some(operator) = operator {
+        match arith::binary(old, &operator, &val) {
+            Some(result) => {
+                let at = Token {
+                    lexeme: format!("{}=", operator),
+                    ..name.clone()
+                };
+                val = number(result, &at, env);
             }
-            (_, BangEq, _) => {
-                return LiteralType::Boolean(false);
+            None => env
+                .borrow()
+                .err()
+                .throw(E0x414, name.line, name.pos, vec![]),
+        }
+    }
+
+    // `number` variables take both ints and floats
+    let is_number = s.value_type.token == NumIdent
+        && matches!(val, LiteralType::Int(_) | LiteralType::Float(_));
+    if old.type_name() != val.type_name() && !is_number {
+        if let Some(LiteralKind::Type(c)) = s.value_type.value.clone() {
+            if let TypeKind::Either { lhs, rhs } = *c {
+                let left_true = if let TypeKind::Var { name } = *lhs {
+                    type_check(&name, &val, env)
+                } else {
+                    false
+                };
+
+                let right_true = if let TypeKind::Var { name } = *rhs {
+                    type_check(&name, &val, env)
+                } else {
+                    false
+                };
+
+                if !left_true && !right_true {
+                    env.borrow().err().throw(
+                        E0x412,
+                        name.line,
+                        name.pos,
+                        vec![name.clone().lexeme],
+                    );
+                }
+            } else {
+                let expected_type = typekind_to_literaltype(*c);
+                if val != expected_type {
+                    env.borrow().err().throw(
+                        E0x412,
+                        name.line,
+                        name.pos,
+                        vec![name.clone().lexeme],
+                    );
+                }
             }
+        } else {
+            env.borrow().err().throw(
+                E0x412,
+                name.line,
+                name.pos,
+                vec![name.clone().lexeme],
+            );
+        }
+    }
+    val
+}
+
+/// `value(key)`, indices are ints, items out of range and missing keys are null
+pub fn item(value: &LiteralType, key: &LiteralType, env: &Rc<RefCell<Env>>) -> LiteralType {
+    match value {
+        LiteralType::Vec(res) => match index(key) {
+            Some(i) => res.get(i).cloned().unwrap_or(LiteralType::Null),
+            None => LiteralType::Null,
+        },
+        LiteralType::Tuple(res) => match index(key) {
+            Some(i) => res.get(i).cloned().unwrap_or(LiteralType::Null),
+            None => LiteralType::Null,
+        },
+        LiteralType::Range(range) => match index(key) {
+            Some(i) => range
+                .iter()
+                .nth(i)
+                .map_or(LiteralType::Null, LiteralType::Int),
+            None => LiteralType::Null,
+        },
+        LiteralType::Map(map) => match key.to_key() {
+            Some(key) => map.get(&key).cloned().unwrap_or(LiteralType::Null),
+            None => LiteralType::Null,
+        },
+        LiteralType::String(s) => match index(key) {
+            Some(i) => s
+                .chars()
+                .nth(i)
+                .map_or(LiteralType::Null, LiteralType::Char),
+            None => LiteralType::Null,
+        },
+        LiteralType::Record(rec) => match key {
+            LiteralType::String(key) => rec
+                .iter()
+                .rfind(|(k, _)| *k == **key)
+                .map_or(LiteralType::Null, |(_, v)| v.eval(Rc::clone(env))),
             _ => LiteralType::Null,
+        },
+        _ => LiteralType::Null,
+    }
+}
+
+/// text between the `{expr}` placeholders of the string paired with sources of the
+/// placeholders, and the text after the last one
+pub fn placeholders(s: &str) -> (Vec<(String, String)>, String) {
+    let mut parts = vec![];
+    let mut idx = 0;
+    while let Some(start) = s[idx..].find('{') {
+        let start_idx = idx + start + 1;
+        match s[start_idx..].find('}') {
+            Some(end) => {
+                let text = s[idx..idx + start].to_string();
+                parts.push((text, s[start_idx..start_idx + end].to_string()));
+                idx = start_idx + end + 1;
+            }
+            None => break,
+        }
+    }
+    (parts, s[idx..].to_string())
+}
+
+/// text of the placeholder value
+pub fn placeholder(value: LiteralType) -> String {
+    match value {
+        LiteralType::String(eval_s) => eval_s.to_string(),
+        LiteralType::Int(eval_n) => eval_n.to_string(),
+        eval_f @ LiteralType::Float(_) => eval_f.to_string(),
+        LiteralType::Boolean(eval_b) => eval_b.to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+/// checks the evaluated bound or step of the range, they must be ints
+pub fn range_bound(
+    bound: &Expression,
+    value: LiteralType,
+    token: &Token,
+    env: &Rc<RefCell<Env>>,
+) -> i64 {
+    match value {
+        LiteralType::Int(n) => n,
+        value => {
+            let at = bound.token().unwrap_or(token);
+            let err = env.borrow().err();
+            err.fail(err.diagnostic(E0x314, at.line, at.pos, vec![value.type_name()]))
+        }
+    }
+}
+
+/// the step of the range must be positive
+pub fn range_step(step: i64, token: &Token, env: &Rc<RefCell<Env>>) -> usize {
+    if step <= 0 {
+        let err = env.borrow().err();
+        err.fail(err.diagnostic(E0x313, token.line, token.pos, vec![]))
+    }
+    step as usize
+}
+
+/// function `type::func` of the `impl` block, `name` is the first argument of the enum call
+pub fn static_func(parent: &Token, name: &Expression, env: &Rc<RefCell<Env>>) -> Option<FuncImpl> {
+    let Expression::Var { name, .. } = name else {
+        return None;
+    };
+    let path = format!("{}::{}", parent.lexeme, name.lexeme);
+    let func = env.borrow().get_int(&path, None);
+    match func.map(|f| f.value) {
+        Some(LiteralType::Func(func)) => Some(func),
+        _ => None,
+    }
+}
+
+/// `value.method(args)`, the value is passed as `self`. the method is looked up by the
+/// declared type of the variable, enums by their names, other values by their shape
+fn method_call(receiver: &Expression, args: &[Expression], env: &Rc<RefCell<Env>>) -> LiteralType {
+    let method = match &args[0] {
+        Expression::Value {
+            value: LiteralType::String(method),
+            ..
+        } => method.to_string(),
+        _ => unreachable!(),
+    };
+    let value = receiver.eval(Rc::clone(env));
+    let declared = match receiver {
+        Expression::Var { name, id } => declared_type(env.borrow().get(name.lexeme.clone(), *id)),
+        _ => None,
+    };
+    match find_method(&value, declared, &method, env) {
+        Some(func) => {
+            let mut args = args.to_vec();
+            args[0] = receiver.clone();
+            run_func(func, &args, Rc::clone(env), receiver)
+        }
+        None => {
+            let at = receiver.token().cloned().unwrap_or(Token::null());
+            no_method(&value, method, &at, env)
+        }
+    }
+}
+
+/// declared type of the variable
+pub fn declared_type(var: Option<ValueType>) -> Option<String> {
+    match var {
+        Some(ValueType {
+            kind: ValueKind::Var(var),
+            ..
+        }) => Some(var.value_type.lexeme),
+        _ => None,
+    }
+}
+
+/// function implementing the method for the value
+pub fn find_method(
+    value: &LiteralType,
+    declared: Option<String>,
+    method: &str,
+    env: &Rc<RefCell<Env>>,
+) -> Option<FuncImpl> {
+    let types = env.borrow().get_impls(method);
+    let found = match value {
+        LiteralType::Enum { parent, .. } => types.iter().find(|t| t.lexeme == parent.lexeme),
+        value => types
+            .iter()
+            .find(|t| declared.as_ref() == Some(&t.lexeme))
+            .or_else(|| types.iter().find(|t| type_check(t, value, env))),
+    };
+    let func = found.and_then(|t| {
+        let path = format!("{}::{}", t.lexeme, method);
+        env.borrow().get_int(&path, None)
+    });
+    match func.map(|f| f.value) {
+        Some(LiteralType::Func(func)) => Some(func),
+        _ => None,
+    }
+}
+
+/// fails with the method the value doesn't have
+pub fn no_method(value: &LiteralType, method: String, at: &Token, env: &Rc<RefCell<Env>>) -> ! {
+    let err = env.borrow().err();
+    let args = vec![value.type_name(), method];
+    err.fail(err.diagnostic(E0x317, at.line, at.pos, args))
+}
+
+/// converts the int to an index
+fn index(value: &LiteralType) -> Option<usize> {
+    match value {
+        LiteralType::Int(n) => usize::try_from(*n).ok(),
+        _ => None,
+    }
+}
+
+/// unwraps the result of the arithmetic, errors point at the operator
+fn number(
+    result: Result<LiteralType, ErrorCode>,
+    operator: &Token,
+    env: &Rc<RefCell<Env>>,
+) -> LiteralType {
+    match result {
+        Ok(value) => value,
+        Err(code) => {
+            let err = env.borrow().err();
+            let args = vec![operator.lexeme.clone()];
+            err.fail(err.diagnostic(code, operator.line, operator.pos, args))
         }
     }
 }
@@ -671,8 +869,19 @@ impl Expression {
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Expression::Range { lhs, rhs, .. } => {
-                write!(f, "{}..{}", lhs, rhs)
+            Expression::Range {
+                lhs,
+                rhs,
+                step,
+                is_inclusive,
+                ..
+            } => {
+                let dots = if *is_inclusive { "..=" } else { ".." };
+                write!(f, "{}{}{}", lhs, dots, rhs)?;
+                match step {
+                    Some(step) => write!(f, " step {}", step),
+                    None => Ok(()),
+                }
             }
             Expression::If {
                 cond,
@@ -692,6 +901,23 @@ impl fmt::Display for Expression {
 
                 write!(f, "if {}: {}", cond, body)
             }
+            Expression::Match { cond, cases, .. } => {
+                let cases: Vec<String> = cases
+                    .iter()
+                    .map(|case| match &case.guard {
+                        Some(guard) => {
+                            format!(
+                                "{} if {} => {}",
+                                pattern_src(&case.pattern),
+                                guard,
+                                case.body
+                            )
+                        }
+                        None => format!("{} => {}", pattern_src(&case.pattern), case.body),
+                    })
+                    .collect();
+                write!(f, "match {} {{ {} }}", cond, cases.join(", "))
+            }
             Expression::Record { fields, .. } => {
                 let mut fields_str = String::new();
                 for (name, value) in fields {
@@ -725,6 +951,13 @@ impl fmt::Display for Expression {
                 }
                 write!(f, "({})", items_str)
             }
+            Expression::Map { items, .. } => {
+                let mut items_str = String::new();
+                for (key, value) in items {
+                    items_str.push_str(&format!("{}: {}, ", key, value));
+                }
+                write!(f, "[{}]", items_str)
+            }
             Expression::Await { expr, .. } => write!(f, "await {}", expr),
             Expression::Binary {
                 left,
@@ -1,16 +1,19 @@
+pub mod arith;
 pub mod env;
 pub mod expr;
+pub mod pattern;
+pub mod task;
 pub mod types;
 use crate::ast::{
-    Destruct, FuncImpl, LiteralKind, LiteralType,
+    Destruct, FuncImpl, LiteralKind, LiteralType, MatchCase,
     Statement::{self, *},
     Token, TokenType,
 };
-pub mod texp;
 use crate::bundler::interpreter_mod;
-use crate::errors::{raw, Error, ErrorCode::*};
-use crate::interpreter::types::type_check;
+use crate::errors::{raw, Error, ErrorCode, ErrorCode::*};
+use crate::interpreter::types::{any_type, bind, generic, instantiate, type_check};
 use crate::std::StdFunc;
+use crate::vm;
 use crate::Config;
 use env::{Env, FuncKind, ValueKind, VarKind};
 use expr::Expression;
@@ -19,18 +22,51 @@ use std::collections::HashMap;
 use std::env::current_dir;
 use std::fs::File;
 use std::io::Read;
-use std::process::{exit, Command, Stdio};
+use std::process::{Command, Stdio};
 use std::rc::Rc;
 use types::TypeKind;
 
+/// result of the executed statement, anything but `Next` skips the rest of the enclosing
+/// statements until a loop or a function call handles it
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlFlow {
+    /// execution continues with the next statement
+    Next,
+    /// `break`, with the label of the target loop
+    Break(Option<String>),
+    /// `continue`, with the label of the target loop
+    Continue(Option<String>),
+    /// `return` with its value
+    Return(Box<LiteralType>),
+}
+
+impl ControlFlow {
+    /// handles the flow of the loop body, returns the flow the loop stops with,
+    /// or `None` if the loop goes on
+    fn after_iteration(self, label: &Option<Token>) -> Option<ControlFlow> {
+        let is_target = |target: &Option<String>| match target {
+            Some(target) => label.as_ref().is_some_and(|l| &l.lexeme == target),
+            None => true,
+        };
+        match self {
+            ControlFlow::Next => None,
+            ControlFlow::Continue(ref target) if is_target(target) => None,
+            ControlFlow::Break(ref target) if is_target(target) => Some(ControlFlow::Next),
+            // jumps to the outer loops and returns go through
+            flow => Some(flow),
+        }
+    }
+}
+
+/// iterated values paired with their indices or keys
+pub type Iteration = Box<dyn Iterator<Item = (LiteralType, LiteralType)>>;
+
 #[derive(Debug)]
 pub struct Interpreter {
     /// interpreter envrironment
     pub env: Rc<RefCell<Env>>,
     /// project settings, will be moved to the env
     pub project: Config,
-    /// specials like "return" and "breal"
-    specs: Rc<RefCell<HashMap<String, LiteralType>>>,
     /// if interpreter is a module
     is_mod: bool,
     /// module source
@@ -44,10 +80,11 @@ pub struct Interpreter {
 impl Interpreter {
     /// initialize the Interpreter
     pub fn new(project: Config, error: Error) -> Self {
+        let mut env = Env::new(HashMap::new());
+        env.set_err(error.clone());
         let int = Self {
-            env: Rc::new(RefCell::new(Env::new(HashMap::new()))),
+            env: Rc::new(RefCell::new(env)),
             project: project.clone(),
-            specs: Rc::new(RefCell::new(HashMap::new())),
             is_mod: false,
             mod_src: None,
             error,
@@ -70,10 +107,14 @@ impl Interpreter {
     ) -> Self {
         let int = Self {
             env: Rc::clone(&env),
-            specs: Rc::new(RefCell::new(HashMap::new())),
             is_mod,
             mod_src,
-            error: Error::new(src),
+            // function bodies report errors to the sink of their definition
+            error: if is_mod {
+                Error::new(src)
+            } else {
+                env.borrow().err()
+            },
             project: Config::new(),
             order,
         };
@@ -89,35 +130,44 @@ impl Interpreter {
     /// set order to 0 if statement is first class
     /// set order to 1 if statement is inside the block
     pub fn interpret(&mut self, stmts: Vec<&Statement>, order: usize) -> Rc<RefCell<Env>> {
-        self.order = order;
-        stmts.iter().for_each(|s| self.stmt(s));
+        self.exec(stmts, order);
         Rc::clone(&self.env)
     }
-    fn stmt(&mut self, stmt: &Statement) {
+
+    /// executes statements until one of them interrupts the flow, returns that flow
+    pub fn exec(&mut self, stmts: Vec<&Statement>, order: usize) -> ControlFlow {
+        self.order = order;
+        for stmt in stmts {
+            let flow = self.stmt(stmt);
+            if flow != ControlFlow::Next {
+                return flow;
+            }
+        }
+        ControlFlow::Next
+    }
+
+    fn stmt(&mut self, stmt: &Statement) -> ControlFlow {
         match stmt {
+            Block { stmts } => return self.block(stmts.clone()),
+            Return { expr, .. } => {
+                return ControlFlow::Return(Box::new(expr.eval(Rc::clone(&self.env))))
+            }
+            If { .. } => return self.ifs(stmt),
+            While { cond, body, label } => return self.whiles(cond, *body.clone(), label),
+            For { .. } => return self.fors(stmt),
+            Break { label, .. } => {
+                return ControlFlow::Break(label.as_ref().map(|l| l.lexeme.clone()))
+            }
+            Continue { label, .. } => {
+                return ControlFlow::Continue(label.as_ref().map(|l| l.lexeme.clone()))
+            }
+            Match { cond, cases } => return self.matchs(cond, cases),
             Statement::Expression { expr } => {
                 expr.eval(Rc::clone(&self.env));
             }
-            Block { stmts } => self.block(stmts.clone()),
             Var { .. } => self.variable(stmt),
             Func { .. } => self.func(stmt),
-            Return { expr } => {
-                let value = expr.eval(Rc::clone(&self.env));
-                self.specs.borrow_mut().insert("return".to_string(), value);
-            }
-            If { .. } => self.ifs(stmt),
-            While { cond, body } => self.whiles(cond, *body.clone()),
-            For { .. } => self.fors(stmt),
-            Break {} => {
-                self.specs
-                    .borrow_mut()
-                    .insert("break".to_string(), LiteralType::Null);
-            }
-            Match {
-                cond,
-                cases,
-                def_case,
-            } => self.matchs(cond, cases.clone(), def_case),
+            Impl { name, funcs } => self.impls(name, funcs),
             Enum {
                 name,
                 is_pub,
@@ -125,219 +175,58 @@ impl Interpreter {
             } => self.enums(name, *is_pub, items),
             Type {
                 name,
+                generics,
                 value,
                 is_pub,
-            } => self.types(name, value, *is_pub),
+            } => self.types(name, &generic(generics, value.clone()), *is_pub),
             Statement::Record { .. } => self.record(stmt),
             Mod { src, name } => self.mods(src, name.clone()),
-            Use { src, names, all } => self.uses(src, names.clone(), *all),
+            Use {
+                src,
+                from,
+                names,
+                all,
+            } => self.uses(src, from, names.clone(), *all),
             Sh { cmd } => self.sh(cmd),
         }
+        ControlFlow::Next
     }
 
-    fn block(&mut self, stmts: Vec<Statement>) {
-        let new_env = self.env.borrow_mut().enclose();
+    fn block(&mut self, stmts: Vec<Statement>) -> ControlFlow {
+        let new_env = Env::enclose(&self.env);
         let prev_env = Rc::clone(&self.env);
         self.env = Rc::new(RefCell::new(new_env));
-        self.interpret(stmts.iter().map(|x| x).collect(), 1);
+        let flow = self.exec(stmts.iter().collect(), 1);
         self.env = prev_env;
+        flow
+    }
+
+    /// runs the body of the branch or the loop in its own scope, as the resolver does
+    fn branch(&mut self, body: &Statement) -> ControlFlow {
+        match body {
+            Block { .. } => self.stmt(body),
+            body => self.block(vec![body.clone()]),
+        }
     }
 
     fn variable(&mut self, stmt: &Statement) {
         if let Statement::Var {
             names,
-            destruct,
-            value_type,
             value,
+            value_type,
             is_mut,
             is_pub,
-            pub_names,
-            is_func,
+            ..
         } = stmt
         {
-            if value.is_some() {
-                let vl = value.clone().unwrap().eval(Rc::clone(&self.env));
-                if !type_check(&value_type, &vl, &self.env) {
-                    self.error.throw(
-                        E0x301,
-                        names[0].line,
-                        names[0].pos,
-                        vec![value_type.clone().lexeme, vl.to_string()],
-                    );
-                }
-
-                // hande variables in modules
-                if self.is_mod && self.order == 0 && *is_pub {
-                    // define variables in the module
-                    let val = value.as_ref().unwrap().eval(Rc::clone(&self.env));
-                    pub_names.iter().for_each(|name| {
-                        self.env.borrow_mut().define_mod_var(
-                            self.mod_src.clone().unwrap(),
-                            val.clone(),
-                            name.lexeme.clone(),
-                            VarKind {
-                                is_pub: true,
-                                is_mut: *is_mut,
-                                is_func: false,
-                                value_type: value_type.clone(),
-                            },
-                        )
-                    });
-                }
-
-                // handle callbacks in the normal env
-                if !self.is_mod && *is_func {
-                    // callbacks must have one name
-                    if names.len() != 1 {
-                        self.error
-                            .throw(E0x401, names[0].line, names[0].pos, vec![]);
-                    }
-
-                    // callbacks can't mutate
-                    if *is_mut {
-                        raw("functions can't be mutable");
-                    }
-
-                    // create and define the function
-                    let call = self.create_func(stmt);
-                    let func = LiteralType::Func(call.clone());
-                    let params = call
-                        .params
-                        .iter()
-                        .map(|(a, b)| (a.clone().lexeme, b.clone().lexeme))
-                        .collect();
-                    self.env.borrow_mut().define_func(
-                        names[0].lexeme.clone(),
-                        func,
-                        FuncKind {
-                            params,
-                            is_async: call.is_async,
-                            is_pub: is_pub.clone(),
-                        },
-                    );
-                }
-                let var_kind = VarKind {
-                    is_pub: is_pub.clone(),
-                    is_mut: *is_mut,
-                    is_func: *is_func,
-                    value_type: value_type.clone(),
-                };
-                // hande normal variable
-                if !self.is_mod {
-                    let val = value.clone().unwrap().eval(Rc::clone(&self.env));
-
-                    // handle the name based on the value type for destructuring
-                    for (index, name) in names.clone().iter().enumerate() {
-                        match val.clone() {
-                            LiteralType::Vec(entries) => {
-                                if destruct.is_some()
-                                    && destruct.clone().unwrap() == Destruct::Vector
-                                {
-                                    // get the nth entry of the vector for the each name
-                                    let entry = entries
-                                        .get(index)
-                                        .expect("failed to destructure a vector")
-                                        .clone();
-
-                                    // hadnel publicty
-                                    if *is_pub {
-                                        // @todo destructure pub(names) as well
-                                        self.env.borrow_mut().define_pub_var(
-                                            name.lexeme.clone(),
-                                            entry,
-                                            var_kind.clone(),
-                                        );
-                                    } else {
-                                        self.env.borrow_mut().define_var(
-                                            name.lexeme.clone(),
-                                            entry,
-                                            var_kind.clone(),
-                                        );
-                                    }
-                                }
-                            }
-                            LiteralType::Tuple(entries) => {
-                                if destruct.is_some()
-                                    && Destruct::Tuple == destruct.clone().unwrap()
-                                {
-                                    // get the nth entry of the vector for each name
-                                    let entry = entries
-                                        .get(index)
-                                        .expect("failed to destructure a tuple")
-                                        .clone();
-
-                                    // handle publicty
-                                    if *is_pub {
-                                        self.env.borrow_mut().define_pub_var(
-                                            name.lexeme.clone(),
-                                            entry,
-                                            var_kind.clone(),
-                                        );
-                                    } else {
-                                        self.env.borrow_mut().define_var(
-                                            name.lexeme.clone(),
-                                            entry,
-                                            var_kind.clone(),
-                                        );
-                                    }
-                                }
-                            }
-                            LiteralType::Record(entries) => {
-                                if destruct.is_some() {
-                                    if let Destruct::Record = destruct.clone().unwrap() {
-                                        // get the nth entry of the vector for each name
-                                        // @todo get the entry based on the name
-                                        let entry = entries
-                                            .get(index)
-                                            .expect("failed to destructure an array")
-                                            .clone();
-                                        let entry = entry.1.eval(Rc::clone(&self.env));
-                                        // handle publicty
-                                        if *is_pub {
-                                            self.env.borrow_mut().define_pub_var(
-                                                name.lexeme.clone(),
-                                                entry,
-                                                var_kind.clone(),
-                                            );
-                                        } else {
-                                            self.env.borrow_mut().define_var(
-                                                name.lexeme.clone(),
-                                                entry,
-                                                var_kind.clone(),
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                            _ => {}
-                        }
-
-                        // normal definition
-                        if destruct.is_none() {
-                            self.env.borrow_mut().define_var(
-                                name.lexeme.clone(),
-                                val.clone(),
-                                var_kind.clone(),
-                            );
-                        }
-                    }
-
-                    // public definition
-                    if *is_pub {
-                        for name in pub_names {
-                            self.env.borrow_mut().define_pub_var(
-                                name.lexeme.clone(),
-                                val.clone(),
-                                var_kind.clone(),
-                            );
-                        }
-                    }
-                }
+            if let Some(value) = value {
+                let vl = value.eval(Rc::clone(&self.env));
+                self.define(stmt, vl);
             } else {
                 // handle empty variables
 
                 // can't publish empty variables
-                if is_pub.clone() {
+                if *is_pub {
                     self.error
                         .throw(E0x402, names[0].line, names[0].pos, vec![]);
                 }
@@ -359,10 +248,173 @@ impl Interpreter {
         }
     }
 
+    /// defines names of the variable statement with its evaluated value
+    pub fn define(&mut self, stmt: &Statement, vl: LiteralType) {
+        if let Statement::Var {
+            names,
+            destruct,
+            value_type,
+            is_mut,
+            is_pub,
+            pub_names,
+            is_func,
+            ..
+        } = stmt
+        {
+            if !type_check(value_type, &vl, &self.env) {
+                self.error.throw(
+                    E0x301,
+                    names[0].line,
+                    names[0].pos,
+                    vec![value_type.clone().lexeme, vl.type_name()],
+                );
+            }
+
+            // hande variables in modules
+            if self.is_mod && self.order == 0 && *is_pub {
+                // define variables in the module
+                let val = vl.clone();
+                pub_names.iter().for_each(|name| {
+                    self.env.borrow_mut().define_mod_var(
+                        self.mod_src.clone().unwrap(),
+                        val.clone(),
+                        name.lexeme.clone(),
+                        VarKind {
+                            is_pub: true,
+                            is_mut: *is_mut,
+                            is_func: false,
+                            value_type: value_type.clone(),
+                        },
+                    )
+                });
+            }
+
+            // handle callbacks in the normal env
+            if !self.is_mod && *is_func {
+                // callbacks must have one name
+                if names.len() != 1 {
+                    self.error
+                        .throw(E0x401, names[0].line, names[0].pos, vec![]);
+                }
+
+                // callbacks can't mutate
+                if *is_mut {
+                    raw("functions can't be mutable");
+                }
+
+                // create and define the function
+                let call = self.create_func(stmt);
+                let func = LiteralType::Func(call.clone());
+                let params = call
+                    .params
+                    .iter()
+                    .map(|(a, b)| (a.clone().lexeme, b.clone().lexeme))
+                    .collect();
+                self.env.borrow_mut().define_func(
+                    names[0].lexeme.clone(),
+                    func,
+                    FuncKind {
+                        params,
+                        is_async: call.is_async,
+                        is_pub: *is_pub,
+                    },
+                );
+            }
+            let var_kind = VarKind {
+                is_pub: *is_pub,
+                is_mut: *is_mut,
+                is_func: *is_func,
+                value_type: value_type.clone(),
+            };
+            // hande normal variable
+            if !self.is_mod {
+                let val = vl.clone();
+
+                // handle the name based on the value type for destructuring
+                for (index, name) in names.iter().enumerate() {
+                    let entry = match destruct {
+                        Some(destruct) => self.destructure(destruct, &val, names, index),
+                        None => val.clone(),
+                    };
+
+                    // hadnel publicty
+                    if destruct.is_some() && *is_pub {
+                        // @todo destructure pub(names) as well
+                        self.env.borrow_mut().define_pub_var(
+                            name.lexeme.clone(),
+                            entry,
+                            var_kind.clone(),
+                        );
+                    } else {
+                        self.env.borrow_mut().define_var(
+                            name.lexeme.clone(),
+                            entry,
+                            var_kind.clone(),
+                        );
+                    }
+                }
+
+                // public definition
+                if *is_pub {
+                    for name in pub_names {
+                        self.env.borrow_mut().define_pub_var(
+                            name.lexeme.clone(),
+                            val.clone(),
+                            var_kind.clone(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// the entry of the destructured value for the nth name, vectors and tuples are taken by
+    /// position and records by the field names
+    fn destructure(
+        &self,
+        destruct: &Destruct,
+        val: &LiteralType,
+        names: &[Token],
+        index: usize,
+    ) -> LiteralType {
+        let name = &names[index];
+        let entry = match (destruct, val) {
+            (Destruct::Vector, LiteralType::Vec(entries)) => item(entries, val, names.len(), index),
+            (Destruct::Tuple, LiteralType::Tuple(entries)) => {
+                item(entries, val, names.len(), index)
+            }
+            (Destruct::Record, LiteralType::Record(fields)) => fields
+                .iter()
+                .find(|(field, _)| *field == name.lexeme)
+                .map(|(_, value)| value.eval(Rc::clone(&self.env)))
+                .ok_or_else(|| {
+                    let expected = format!("record with field {}", name.lexeme);
+                    vec![expected, val.type_name()]
+                }),
+            (destruct, val) => {
+                let expected = match destruct {
+                    Destruct::Vector => "vector",
+                    Destruct::Tuple => "tuple",
+                    Destruct::Record => "record",
+                };
+                Err(vec![expected.to_string(), val.type_name()])
+            }
+        };
+        match entry {
+            Ok(entry) => entry,
+            Err(args) => self.error.throw(E0x301, name.line, name.pos, args),
+        }
+    }
+
     fn func(&mut self, stmt: &Statement) {
+        let call = self.create_func(stmt);
+        self.define_func(stmt, call);
+    }
+
+    /// defines the function of the statement, the function is created by the caller
+    pub fn define_func(&mut self, stmt: &Statement, call: FuncImpl) {
         if let Statement::Func {
             name,
-            body,
             params,
             is_async,
             is_pub,
@@ -371,22 +423,6 @@ impl Interpreter {
         {
             let is_async = *is_async;
             let is_pub = *is_pub;
-
-            // evaluate return statement
-            match *body.clone() {
-                Statement::Expression { expr } => {
-                    expr.eval(Rc::clone(&self.env));
-                }
-                Statement::Block { stmts } => stmts.iter().for_each(|stmt| {
-                    if let Statement::Return { expr } = stmt {
-                        expr.eval(Rc::clone(&self.env));
-                    }
-                }),
-                _ => {}
-            }
-
-            // create a function
-            let call = self.create_func(stmt);
             let func = LiteralType::Func(call);
 
             // convert param Tokens to the strings
@@ -426,7 +462,18 @@ impl Interpreter {
         }
     }
 
-    fn ifs(&mut self, stmt: &Statement) {
+    /// functions of the block are named `type::func`, the ones taking `self` are methods
+    fn impls(&mut self, name: &Token, funcs: &[Statement]) {
+        for func in funcs {
+            let (func, method) = impl_func(name, func);
+            if let Some(method) = method.filter(|_| !self.is_mod) {
+                self.env.borrow_mut().define_impl(method, name.clone());
+            }
+            self.func(&func);
+        }
+    }
+
+    fn ifs(&mut self, stmt: &Statement) -> ControlFlow {
         if let Statement::If {
             cond,
             body,
@@ -437,54 +484,66 @@ impl Interpreter {
                 let val = cond.eval(Rc::clone(&self.env));
                 // if condition is true, execute the body
                 if val.is_truthy() {
-                    self.stmt(body);
+                    return self.branch(body);
                 } else {
                     // if non of the elif branches were executed, execute else branch if there
                     if let Some(body) = else_branch {
-                        self.stmt(body);
+                        return self.branch(body);
                     }
                 }
             }
         }
+        ControlFlow::Next
     }
 
-    fn whiles(&mut self, cond: &Expression, body: Statement) {
+    fn whiles(&mut self, cond: &Expression, body: Statement, label: &Option<Token>) -> ControlFlow {
         if !self.is_mod {
             // execute code while the condition is truthy
             while cond.eval(Rc::clone(&self.env)).is_truthy() {
-                self.stmt(&body);
-                if self.specs.borrow_mut().get("break").is_some() {
-                    self.specs.borrow_mut().remove("break");
-                    break;
+                if let Some(flow) = self.branch(&body).after_iteration(label) {
+                    return flow;
                 }
             }
         }
+        ControlFlow::Next
     }
 
-    fn fors(&mut self, stmt: &Statement) {
+    fn fors(&mut self, stmt: &Statement) -> ControlFlow {
+        let mut flow = ControlFlow::Next;
         if let Statement::For {
             iterator,
             index,
             expr,
             body,
+            label,
         } = stmt
         {
             if !self.is_mod {
-                // transform expression into the literal
-                // iterations over vectors are supported yet
-                // @todo: tupple, record
-                let values = if let LiteralType::Vec(items) = expr.eval(Rc::clone(&self.env)) {
-                    items.clone()
-                } else {
-                    vec![]
-                };
+                // values are produced one by one, ranges aren't collected
+                let at = expr.token().unwrap_or(iterator);
+                let values = iterate(expr.eval(Rc::clone(&self.env)), at, &self.env);
+
+                // the iterator and the index live in the scope of the loop
+                let prev_env = Rc::clone(&self.env);
+                self.env = Rc::new(RefCell::new(Env::enclose(&prev_env)));
 
                 // iterate between values and define arguments
-                for (id, iter) in values.iter().enumerate() {
+                for (iter, second) in values {
+                    self.env.borrow_mut().define_var(
+                        iterator.clone().lexeme,
+                        iter,
+                        VarKind {
+                            is_pub: false,
+                            is_mut: false,
+                            is_func: false,
+                            value_type: iterator.clone(),
+                        },
+                    );
+
                     if let Some(token) = index {
                         self.env.borrow_mut().define_var(
                             token.clone().lexeme,
-                            LiteralType::Number(id as f32),
+                            second,
                             VarKind {
                                 is_pub: false,
                                 is_mut: false,
@@ -494,144 +553,63 @@ impl Interpreter {
                         );
                     }
 
-                    self.env.borrow_mut().define_var(
-                        iterator.clone().lexeme,
-                        iter.clone(),
-                        VarKind {
-                            is_pub: false,
-                            is_mut: false,
-                            is_func: false,
-                            value_type: iterator.clone(),
-                        },
-                    );
-
-                    self.stmt(body);
-                    if self.specs.borrow_mut().get("break").is_some() {
-                        self.specs.borrow_mut().remove("break");
+                    if let Some(stop) = self.stmt(body).after_iteration(label) {
+                        flow = stop;
                         break;
                     }
                 }
-                // destroy arguments after the iteration is over
-                if let Some(token) = index {
-                    self.env.borrow_mut().remove(token.clone().lexeme);
-                }
-                self.env.borrow_mut().remove(iterator.clone().lexeme);
+                self.env = prev_env;
             }
         }
+        flow
     }
 
-    fn matchs(
-        &mut self,
-        cond: &Expression,
-        cases: Vec<(Expression, Statement)>,
-        def_case: &Statement,
-    ) {
-        if !self.is_mod {
-            // if case has been executed
-            let mut exec = false;
-            let condition = cond.eval(Rc::clone(&self.env));
-
-            // number, string, char matching:
-            // - require def case
-            // - check if case_cond equals condition
-
-            // bool matching:
-            // - require true and false cases
-            // - if one of them or none of them are there, require def_case
-
-            // null, void, any matching:
-            // - require def case
-            // - don't allow any other case
-
-            // vector, tuple, record, .., other matching:
-            // - don't allow pattern matching
-            match condition.clone() {
-                LiteralType::Enum { name, .. } => {
-                    // enum collections aren't allowed
-                    if name.token == TokenType::Null {
-                        raw("please specify the enum name, you are trying to match");
-                    }
-
-                    for (expr, body) in cases {
-                        let body = match body {
-                            Statement::Expression { expr } => {
-                                vec![Statement::Expression { expr }]
-                            }
-                            Statement::Block { stmts } => stmts.clone(),
-                            _ => vec![],
-                        };
-                        let body = body.iter().collect();
-                        let expr_lit = expr.eval(Rc::clone(&self.env));
-                        // check if expression is enum
-                        if let LiteralType::Enum { .. } = expr_lit {
-                            // execute the body if case matches
-                            if self
-                                .enum_equality(expr.eval(Rc::clone(&self.env)), condition.clone())
-                            {
-                                self.interpret(body, 1);
-                                exec = true;
-                                break;
-                            }
-                        } else {
-                            raw(format!(
-                                "expected enum in the match condition, but received {}",
-                                expr.eval(Rc::clone(&self.env)).type_name()
-                            )
-                            .as_str())
-                        }
-                    }
-                }
-                _ => raw(format!("pattern matching for '{:?}' isn't allowed", condition).as_str()),
-            }
-
-            if !exec {
-                self.stmt(def_case);
-            }
+    fn matchs(&mut self, cond: &Expression, cases: &[MatchCase]) -> ControlFlow {
+        if self.is_mod {
+            return ControlFlow::Next;
         }
-    }
-
-    fn enum_equality(&mut self, lhs: LiteralType, rhs: LiteralType) -> bool {
-        if let LiteralType::Enum {
-            parent: lhs_par,
-            name: lhs_name,
-            ..
-        } = lhs
-        {
-            if let LiteralType::Enum {
-                parent: rhs_par,
-                name: rhs_name,
-                ..
-            } = rhs
-            {
-                if lhs_par.lexeme == rhs_par.lexeme && lhs_name.lexeme == rhs_name.lexeme {
-                    return true;
-                }
+        let value = cond.eval(Rc::clone(&self.env));
+        let env = Rc::clone(&self.env);
+        let flow = pattern::run(cases, &value, &env, |body, arm_env| {
+            let body = match body {
+                Statement::Block { stmts } => stmts.iter().collect(),
+                body => vec![body],
+            };
+            self.env = Rc::clone(arm_env);
+            let flow = self.exec(body, 1);
+            self.env = Rc::clone(&env);
+            flow
+        });
+        match flow {
+            Some(flow) => flow,
+            None => {
+                let at = cond.token().cloned().unwrap_or(Token::null());
+                pattern::unmatched(&value, &at, &env)
             }
         }
-        false
     }
 
-    fn enums(&mut self, name: &Token, is_pub: bool, items: &Vec<(Token, Option<Token>)>) {
+    fn enums(&mut self, name: &Token, is_pub: bool, items: &[(Token, Option<Token>)]) {
         // handle public enums in the module
         if is_pub && self.is_mod && self.order == 0 {
             self.env.borrow_mut().define_mod_enum(
                 self.mod_src.clone().unwrap(),
                 LiteralType::Void,
                 name.clone().lexeme,
-                items.clone(),
+                items.to_vec(),
             );
         } else
         // handle public enums
         if is_pub {
             self.env
                 .borrow_mut()
-                .define_pub_enum(name.clone().lexeme, items.clone());
+                .define_pub_enum(name.clone().lexeme, items.to_vec());
         } else
         // handle normal enums
         {
             self.env
                 .borrow_mut()
-                .define_enum(name.clone().lexeme, items.clone());
+                .define_enum(name.clone().lexeme, items.to_vec());
         }
     }
 
@@ -662,6 +640,7 @@ impl Interpreter {
     fn record(&mut self, stmt: &Statement) {
         if let Statement::Record {
             name,
+            generics,
             extends,
             is_strict: _,
             fields,
@@ -694,13 +673,16 @@ impl Interpreter {
                 .map(|(i, v)| format!("{}: {}, ", i.lexeme.clone(), v.clone()))
                 .collect();
 
-            let value = Token {
-                token: TokenType::Type,
-                lexeme: format!("{{ {}}}", s),
-                value,
-                line: 0,
-                pos: (0, 0),
-            };
+            let value = generic(
+                generics,
+                Token {
+                    token: TokenType::Type,
+                    lexeme: format!("{{ {}}}", s),
+                    value,
+                    line: 0,
+                    pos: (0, 0),
+                },
+            );
 
             self.env
                 .borrow_mut()
@@ -708,7 +690,7 @@ impl Interpreter {
         }
     }
 
-    fn sh(&mut self, cmd: &String) {
+    fn sh(&mut self, cmd: &str) {
         let cmd = cmd.trim_matches('"');
         let output = match Command::new("sh")
             .arg("-c")
@@ -718,10 +700,7 @@ impl Interpreter {
             .output()
         {
             Ok(c) => c,
-            Err(e) => {
-                raw(format!("sh error: {}", e).as_str());
-                exit(1)
-            }
+            Err(e) => raw(format!("sh error: {}", e).as_str()),
         };
 
         if output.status.success() {
@@ -734,9 +713,11 @@ impl Interpreter {
     }
 
     /// creates FuncImpl from function statement
-    fn create_func(&self, stmt: &Statement) -> FuncImpl {
+    pub fn create_func(&self, stmt: &Statement) -> FuncImpl {
+        let at = stmt.token().cloned().unwrap_or(Token::null());
         if let Func {
             name,
+            generics,
             value_type,
             body,
             params,
@@ -748,19 +729,21 @@ impl Interpreter {
                 .iter()
                 .map(|(name, value_type)| (name.clone(), value_type.clone()))
                 .collect();
-            FuncImpl {
+            self.compiled(FuncImpl {
                 name: name.lexeme.clone(),
+                token: name.clone(),
+                generics: generics.clone(),
                 value_type: value_type.clone(),
-                body: body.clone(),
+                body: Rc::from(body.clone()),
                 params,
                 is_async: *is_async,
                 is_pub: *is_pub,
                 env: Rc::clone(&self.env),
-            }
+                code: None,
+            })
         } else if let Var { value, is_func, .. } = stmt {
-            if !is_func.clone() {
-                self.error.throw(E0x404, 0, (0, 0), vec![]);
-                exit(1);
+            if !*is_func {
+                self.error.throw(E0x404, at.line, at.pos, vec![]);
             }
             let func = value.clone().unwrap();
             if let Expression::Func {
@@ -778,22 +761,32 @@ impl Interpreter {
                     .map(|(name, value_type)| (name.clone(), value_type.clone()))
                     .collect();
 
-                return FuncImpl {
+                return self.compiled(FuncImpl {
                     name: name.lexeme.clone(),
+                    token: name.clone(),
+                    generics: vec![],
                     value_type: value_type.clone(),
-                    body,
+                    body: Rc::from(body),
                     params,
                     is_async,
                     is_pub,
                     env: Rc::clone(&self.env),
-                };
+                    code: None,
+                });
             }
-            self.error.throw(E0x404, 0, (0, 0), vec![]);
-            exit(1);
+            self.error.throw(E0x404, at.line, at.pos, vec![])
         } else {
-            self.error.throw(E0x404, 0, (0, 0), vec![]);
-            exit(1);
+            self.error.throw(E0x404, at.line, at.pos, vec![])
+        }
+    }
+
+    /// compiles the body of the function if the code runs on the VM
+    fn compiled(&self, func: FuncImpl) -> FuncImpl {
+        if !self.project.vm {
+            return func;
         }
+        let code = vm::compile(&func);
+        FuncImpl { code, ..func }
     }
 
     fn mods(&mut self, src: &String, name: Option<String>) {
@@ -801,10 +794,7 @@ impl Interpreter {
         path.push(src.trim_matches('"'));
         let mut file = match File::open(path) {
             Ok(f) => f,
-            Err(f) => {
-                raw(format!("failed to opan a file: {}", f).as_str());
-                exit(0);
-            }
+            Err(f) => raw(format!("failed to opan a file: {}", f).as_str()),
         };
         let mut contents = String::new();
         file.read_to_string(&mut contents)
@@ -817,20 +807,19 @@ impl Interpreter {
         interpreter_mod(contents.as_str(), Some(name), Rc::clone(&self.env));
     }
 
-    fn uses(&mut self, src: &String, names: Vec<(Token, Option<Token>)>, all: bool) {
+    fn uses(&mut self, src: &String, from: &Token, names: Vec<(Token, Option<Token>)>, all: bool) {
         if src.clone().contains("::") {
-            self.load_std(src.trim_matches('"').to_string().clone(), names.clone());
+            self.load_std(src.trim_matches('"').to_string(), from, names.clone());
         } else {
-            let mod_vals = self.env.borrow_mut().mod_vals.borrow_mut().clone();
+            let mod_vals = self.env.borrow().mod_vals.clone();
             let vals = match mod_vals.get(src) {
                 Some(c) => c,
-                None => {
-                    self.error.throw(E0x416, 0, (0, 0), vec![src.clone()]);
-                    exit(1);
-                }
+                None => self
+                    .error
+                    .throw(E0x416, from.line, from.pos, vec![src.clone()]),
             };
 
-            self.env.borrow_mut().mod_vals.borrow_mut().remove(src);
+            self.env.borrow_mut().mod_vals.remove(src);
 
             if all {
                 for val in vals {
@@ -838,29 +827,17 @@ impl Interpreter {
 
                     if let LiteralType::Void = v.value {
                         if let ValueKind::Type(t) = v.kind.clone() {
-                            self.env
-                                .borrow_mut()
-                                .type_values
-                                .borrow_mut()
-                                .insert(name.clone(), t);
+                            self.env.borrow_mut().define_type(name.clone(), t);
                         }
                     } else {
-                        self.env
-                            .borrow_mut()
-                            .values
-                            .borrow_mut()
-                            .insert(name.clone(), v.clone());
+                        self.env.borrow_mut().import(name.clone(), v.clone());
                     }
                 }
             } else {
                 for (name, alias) in names {
                     if let Some((_, v)) = vals.iter().find(|(n, _)| n == &name.lexeme) {
                         let new_name = alias.as_ref().map_or(&name.lexeme, |t| &t.lexeme);
-                        self.env
-                            .borrow_mut()
-                            .values
-                            .borrow_mut()
-                            .insert(new_name.clone(), v.clone());
+                        self.env.borrow_mut().import(new_name.clone(), v.clone());
                     }
                 }
             }
@@ -868,105 +845,244 @@ impl Interpreter {
     }
 }
 
-pub fn run_func(func: FuncImpl, args: &[Expression], env: Rc<RefCell<Env>>) -> LiteralType {
-    let error = Error::new("");
-    if args.len() != func.params.len() {
-        error.throw(E0x405, 0, (0, 0), vec![]);
-    }
-
-    let mut arg_values = vec![];
-    for (i, arg) in args.iter().enumerate() {
-        let arg_lit = arg.eval(Rc::clone(&env));
-        if !type_check(&func.params.iter().nth(i).unwrap().1, &arg_lit, &env) {
-            error.throw(
-                E0x301,
-                0,
-                (0, 0),
-                vec![
-                    func.params.iter().nth(i).unwrap().1.lexeme.clone(),
-                    arg_lit.to_string(),
-                ],
-            );
+/// function of the `impl` block named `type::func`, paired with the name of the method
+/// if the function takes `self`
+pub fn impl_func(name: &Token, func: &Statement) -> (Statement, Option<String>) {
+    let mut func = func.clone();
+    let mut method = None;
+    if let Func {
+        name: func_name,
+        params,
+        ..
+    } = &mut func
+    {
+        if params.first().is_some_and(|(p, _)| p.lexeme == "self") {
+            method = Some(func_name.lexeme.clone());
         }
-        arg_values.push(arg_lit);
+        func_name.lexeme = format!("{}::{}", name.lexeme, func_name.lexeme);
     }
-    let func_env = func.env.borrow_mut().enclose();
-    let func_env = Rc::new(RefCell::new(func_env));
+    (func, method)
+}
 
-    for (i, val) in arg_values.iter().enumerate() {
-        if i < func.params.len() {
-            if !type_check(&func.value_type, &val, &env) {
-                error.throw(
-                    E0x301,
-                    0,
-                    (0, 0),
-                    vec![val.to_string(), arg_values[i].to_string()],
-                );
-            }
-            let params = func
-                .params
+/// pairs of values bound by the iterations of `for`, sequences pair their items
+/// with indices, records pair field names with values and maps pair keys with values
+pub fn iterate(value: LiteralType, at: &Token, env: &Rc<RefCell<Env>>) -> Iteration {
+    fn indexed(items: impl Iterator<Item = LiteralType> + 'static) -> Iteration {
+        Box::new(
+            items
+                .enumerate()
+                .map(|(id, item)| (item, LiteralType::Int(id as i64))),
+        )
+    }
+    match value {
+        // items of shared vectors are cloned one by one while iterating
+        LiteralType::Vec(items) => indexed((0..items.len()).map(move |i| items[i].clone())),
+        LiteralType::Tuple(items) => indexed(items.into_iter()),
+        // ranges produce their items while iterating
+        LiteralType::Range(range) => indexed(range.iter().map(LiteralType::Int)),
+        LiteralType::String(s) => {
+            let chars: Vec<char> = s.chars().collect();
+            indexed(chars.into_iter().map(LiteralType::Char))
+        }
+        LiteralType::Map(map) => Box::new(
+            Rc::unwrap_or_clone(map)
+                .into_iter()
+                .map(|(key, value)| (key.to_literal(), value)),
+        ),
+        LiteralType::Record(fields) => {
+            let values: Vec<(LiteralType, LiteralType)> = fields
                 .iter()
-                .map(|(a, b)| (a.clone().lexeme, b.clone().lexeme))
+                .map(|(name, value)| {
+                    let value = value.eval(Rc::clone(env));
+                    (LiteralType::String(Rc::new(name.clone())), value)
+                })
                 .collect();
-            func_env.borrow_mut().define_func(
-                func.params[i].0.lexeme.clone(),
-                val.clone(),
-                FuncKind {
-                    params,
-                    is_async: func.is_async,
-                    is_pub: func.is_pub,
-                },
-            );
-        } else {
-            error.throw(E0x405, 0, (0, 0), vec![]);
+            Box::new(values.into_iter())
+        }
+        value => {
+            let err = env.borrow().err();
+            let args = vec![value.type_name()];
+            err.fail(err.diagnostic(E0x312, at.line, at.pos, args))
+        }
+    }
+}
+
+/// calls the function, `callee` locates the call site for errors
+pub fn run_func(
+    func: FuncImpl,
+    args: &[Expression],
+    env: Rc<RefCell<Env>>,
+    callee: &Expression,
+) -> LiteralType {
+    let call = callee.token().cloned().unwrap_or(Token::null());
+    check_arity(&func, args.len(), &call, &env);
+    let arg_values: Vec<LiteralType> = args.iter().map(|a| a.eval(Rc::clone(&env))).collect();
+    call_func(func, arg_values, env, &call, |i| args[i].token().cloned())
+}
+
+/// fails if the number of the arguments doesn't match the parameters of the function
+pub fn check_arity(func: &FuncImpl, len: usize, call: &Token, env: &Rc<RefCell<Env>>) {
+    if len != func.params.len() {
+        throw_call(&env.borrow().err(), E0x405, call, func, vec![]);
+    }
+}
+
+/// calls the function with evaluated arguments, `arg_at` locates the arguments for errors
+pub fn call_func(
+    func: FuncImpl,
+    arg_values: Vec<LiteralType>,
+    env: Rc<RefCell<Env>>,
+    call: &Token,
+    arg_at: impl Fn(usize) -> Option<Token>,
+) -> LiteralType {
+    let func = bind_args(func, &arg_values, &env, call, arg_at);
+    // async functions run when their future is awaited or spawned
+    if func.is_async {
+        return task::call(func, arg_values, env, call.clone());
+    }
+    exec_func(func, arg_values, &env, call)
+}
+
+/// binds type parameters of the function by the arguments and checks types of the arguments,
+/// returns the function with the bound types
+pub fn bind_args(
+    func: FuncImpl,
+    arg_values: &[LiteralType],
+    env: &Rc<RefCell<Env>>,
+    call: &Token,
+    arg_at: impl Fn(usize) -> Option<Token>,
+) -> FuncImpl {
+    // type parameters are bound by the arguments, unbound ones are `any`
+    let mut func = func;
+    if !func.generics.is_empty() {
+        let mut bindings = HashMap::new();
+        for ((_, param_type), val) in func.params.iter().zip(arg_values) {
+            bind(param_type, val, &func.generics, &mut bindings);
+        }
+        for param in &func.generics {
+            if !bindings.contains_key(&param.lexeme) {
+                bindings.insert(param.lexeme.clone(), any_type());
+            }
+        }
+        for (_, param_type) in func.params.iter_mut() {
+            *param_type = instantiate(param_type, &bindings);
         }
+        func.value_type = instantiate(&func.value_type, &bindings);
+    }
+
+    for (i, arg_lit) in arg_values.iter().enumerate() {
+        if !type_check(&func.params[i].1, arg_lit, env) {
+            let at = arg_at(i).unwrap_or(call.clone());
+            let args = vec![func.params[i].1.lexeme.clone(), arg_lit.type_name()];
+            throw_call(&env.borrow().err(), E0x301, &at, &func, args);
+        }
+    }
+    func
+}
+
+/// runs the body of the function with checked arguments
+fn exec_func(
+    func: FuncImpl,
+    arg_values: Vec<LiteralType>,
+    env: &Rc<RefCell<Env>>,
+    call: &Token,
+) -> LiteralType {
+    if let Some(code) = func.code.clone() {
+        return vm::call(func, &code, arg_values, env, call);
+    }
+    let func_env = Env::enclose(&func.env);
+    let func_env = Rc::new(RefCell::new(func_env));
+
+    for (i, val) in arg_values.iter().enumerate() {
+        let params = func
+            .params
+            .iter()
+            .map(|(a, b)| (a.clone().lexeme, b.clone().lexeme))
+            .collect();
+        func_env.borrow_mut().define_func(
+            func.params[i].0.lexeme.clone(),
+            val.clone(),
+            FuncKind {
+                params,
+                is_async: func.is_async,
+                is_pub: func.is_pub,
+            },
+        );
     }
 
     let mut int = Interpreter::new_with_env(Rc::clone(&func_env), false, "", None, 1);
     match *func.body {
-        Statement::Block { stmts } => {
-            for stmt in stmts.clone() {
-                int.stmt(&stmt);
-                let mut val = {
-                    let specs = int.specs.borrow_mut();
-                    specs.get("return").cloned()
-                };
-                if let Statement::Expression { expr } = stmts.first().unwrap() {
-                    val = Some(expr.eval(Rc::clone(&env)));
-                }
-
-                if val.is_some() {
-                    let v = val.clone().unwrap().clone();
-                    if !type_check(&func.value_type, &v, &env) {
-                        // error.throw(
-                        //     E0x301,
-                        //     0,
-                        //     (0, 0),
-                        //     vec![func.value_type.clone().lexeme, v.to_string()],
-                        // );
-                    }
-                    return v;
-                }
+        Statement::Block { ref stmts } => {
+            // `return` stops the body at any depth
+            if let ControlFlow::Return(v) = int.exec(stmts.iter().collect(), 1) {
+                return returned(&func, *v, env, call);
             }
         }
-        Statement::Expression { expr } => {
+        Statement::Expression { ref expr } => {
             let val = expr.eval(Rc::clone(&func_env));
-            if !type_check(&func.value_type, &val, &env) {
-                error.throw(
-                    E0x301,
-                    0,
-                    (0, 0),
-                    vec![func.value_type.clone().lexeme, val.to_string()],
-                );
-            }
-            return val;
+            return check_return(&func, val, env, call);
         }
         _ => {}
     }
+    no_return(&func, env, call)
+}
+
+/// checks the value of `return`, bare `return;` of the void function returns null
+pub fn returned(
+    func: &FuncImpl,
+    value: LiteralType,
+    env: &Rc<RefCell<Env>>,
+    call: &Token,
+) -> LiteralType {
+    if func.value_type.lexeme == "void" && value == LiteralType::Null {
+        return value;
+    }
+    check_return(func, value, env, call)
+}
+
+/// checks the returned value against the return type of the function
+pub fn check_return(
+    func: &FuncImpl,
+    value: LiteralType,
+    env: &Rc<RefCell<Env>>,
+    call: &Token,
+) -> LiteralType {
+    if !type_check(&func.value_type, &value, env) {
+        let args = vec![func.value_type.lexeme.clone(), value.type_name()];
+        throw_call(&env.borrow().err(), E0x301, call, func, args);
+    }
+    value
+}
+
+/// value of the function whose body ends without `return`, only void functions can do it
+pub fn no_return(func: &FuncImpl, env: &Rc<RefCell<Env>>, call: &Token) -> LiteralType {
     if func.value_type.lexeme != "void" {
-        error.throw(E0x406, 0, (0, 0), vec![]);
+        throw_call(&env.borrow().err(), E0x406, call, func, vec![]);
     }
     LiteralType::Null
 }
 
+/// throws the error at the call site and shows the definition of the called function
+fn throw_call(error: &Error, code: ErrorCode, at: &Token, func: &FuncImpl, args: Vec<String>) {
+    let note = format!("function '{}' is defined here", func.name);
+    let diagnostic = error
+        .diagnostic(code, at.line, at.pos, args)
+        .related(func.token.line, func.token.pos, note);
+    error.fail(diagnostic);
+}
+
+/// the nth entry of the destructured vector or tuple, or the arguments of the mismatch
+/// if it has fewer entries than names
+fn item(
+    entries: &[LiteralType],
+    val: &LiteralType,
+    count: usize,
+    index: usize,
+) -> Result<LiteralType, Vec<String>> {
+    entries.get(index).cloned().ok_or_else(|| {
+        let items = |len: usize| format!("{} of {} items", val.type_name(), len);
+        vec![items(count), items(entries.len())]
+    })
+}
+
 // @todo better organized statements
@@ -0,0 +1,182 @@
+// matches values against patterns of `match` arms
+use super::{
+    arith,
+    env::{Env, VarKind},
+};
+use crate::ast::{LiteralType, MatchCase, Pattern, Token, TokenType::Eq};
+use crate::errors::ErrorCode::E0x316;
+use std::{cell::RefCell, rc::Rc};
+
+/// runs the body of the first arm matching the value, `None` if no arm matches. every arm
+/// runs in its own scope with the names of its pattern, the scope is passed to `body`
+pub fn run<T, R>(
+    cases: &[MatchCase<T>],
+    value: &LiteralType,
+    env: &Rc<RefCell<Env>>,
+    mut body: impl FnMut(&T, &Rc<RefCell<Env>>) -> R,
+) -> Option<R> {
+    for case in cases {
+        let mut bindings = vec![];
+        if !matches(&case.pattern, value, env, &mut bindings) {
+            continue;
+        }
+        // names take the slots the resolver gave them, in the order of the first alternative
+        let names = case.pattern.names();
+        bindings.sort_by_key(|(name, _)| names.iter().position(|n| n.lexeme == name.lexeme));
+        let arm_env = Rc::new(RefCell::new(Env::enclose(env)));
+        for (name, value) in bindings {
+            arm_env.borrow_mut().define_var(
+                name.lexeme.clone(),
+                value,
+                VarKind {
+                    is_pub: false,
+                    is_mut: false,
+                    is_func: false,
+                    value_type: name,
+                },
+            );
+        }
+        let is_guarded = match &case.guard {
+            Some(guard) => guard.eval(Rc::clone(&arm_env)).is_truthy(),
+            None => true,
+        };
+        if is_guarded {
+            return Some(body(&case.body, &arm_env));
+        }
+    }
+    None
+}
+
+/// fails with the value no arm covers, values of unknown types aren't checked for
+/// exhaustiveness before the execution
+pub fn unmatched(value: &LiteralType, at: &Token, env: &Rc<RefCell<Env>>) -> ! {
+    let err = env.borrow().err();
+    err.fail(err.diagnostic(E0x316, at.line, at.pos, vec![value.to_string()]))
+}
+
+/// checks if the value matches the pattern, names of the pattern are pushed to `bindings`
+pub fn matches(
+    pattern: &Pattern,
+    value: &LiteralType,
+    env: &Rc<RefCell<Env>>,
+    bindings: &mut Vec<(Token, LiteralType)>,
+) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard { .. }, _) => true,
+        (Pattern::Bind { name }, value) => {
+            bindings.push((name.clone(), value.clone()));
+            true
+        }
+        (
+            Pattern::Value {
+                value: expected, ..
+            },
+            value,
+        ) => equals(expected, value),
+        (
+            Pattern::Range {
+                lhs,
+                rhs,
+                is_inclusive,
+                ..
+            },
+            value,
+        ) => in_range(lhs, rhs, *is_inclusive, value),
+        (
+            Pattern::Enum {
+                parent,
+                name,
+                payload,
+            },
+            LiteralType::Enum {
+                parent: value_parent,
+                name: value_name,
+                value,
+            },
+        ) => {
+            if parent.lexeme != value_parent.lexeme || name.lexeme != value_name.lexeme {
+                return false;
+            }
+            match (payload, value) {
+                (None, _) => true,
+                (Some(payload), Some(value)) => matches(payload, value, env, bindings),
+                (Some(_), None) => false,
+            }
+        }
+        (Pattern::Tuple { items, .. }, LiteralType::Tuple(values)) => {
+            items.len() == values.len() && all(items, values, env, bindings)
+        }
+        (
+            Pattern::Vec {
+                items, has_rest, ..
+            },
+            LiteralType::Vec(values),
+        ) => {
+            let fits = if *has_rest {
+                values.len() >= items.len()
+            } else {
+                values.len() == items.len()
+            };
+            fits && all(items, values, env, bindings)
+        }
+        (Pattern::Vec { .. }, LiteralType::Range(range)) => matches(
+            pattern,
+            &LiteralType::Vec(Rc::new(range.items())),
+            env,
+            bindings,
+        ),
+        (Pattern::Record { fields, .. }, LiteralType::Record(values)) => {
+            fields.iter().all(|(name, field)| {
+                match values.iter().find(|(n, _)| *n == name.lexeme) {
+                    Some((_, value)) => matches(field, &value.eval(Rc::clone(env)), env, bindings),
+                    None => false,
+                }
+            })
+        }
+        // names of the failed alternative aren't bound
+        (Pattern::Or { patterns }, value) => patterns.iter().any(|pattern| {
+            let len = bindings.len();
+            let is_match = matches(pattern, value, env, bindings);
+            if !is_match {
+                bindings.truncate(len);
+            }
+            is_match
+        }),
+        _ => false,
+    }
+}
+
+fn all(
+    items: &[Pattern],
+    values: &[LiteralType],
+    env: &Rc<RefCell<Env>>,
+    bindings: &mut Vec<(Token, LiteralType)>,
+) -> bool {
+    items
+        .iter()
+        .zip(values)
+        .all(|(item, value)| matches(item, value, env, bindings))
+}
+
+/// numbers are equal by their value, `1` matches `1.0`
+fn equals(expected: &LiteralType, value: &LiteralType) -> bool {
+    match arith::binary(expected, &Eq, value) {
+        Some(Ok(LiteralType::Boolean(is_eq))) => is_eq,
+        _ => expected == value,
+    }
+}
+
+fn in_range(lhs: &LiteralType, rhs: &LiteralType, is_inclusive: bool, value: &LiteralType) -> bool {
+    fn contains<T: PartialOrd>(lhs: T, rhs: T, is_inclusive: bool, value: T) -> bool {
+        lhs <= value && (value < rhs || is_inclusive && value == rhs)
+    }
+    match (lhs, rhs, value) {
+        (LiteralType::Int(a), LiteralType::Int(b), LiteralType::Int(v)) => {
+            contains(a, b, is_inclusive, v)
+        }
+        (LiteralType::Char(a), LiteralType::Char(b), LiteralType::Char(v)) => {
+            contains(a, b, is_inclusive, v)
+        }
+        _ => false,
+    }
+}
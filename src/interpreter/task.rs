@@ -0,0 +1,401 @@
+// single-threaded cooperative executor of async functions
+//
+// calls of async functions return futures, `await` drives them to the end.
+// spawned tasks run on their own stacks, a task awaiting a pending future
+// suspends and the executor resumes the others from its run queue, so their
+// timers overlap. the program waits the same way, it drives the run queue
+// until its future completes. every `Engine` has its own executor, `enter`
+// switches to it while its sources run
+use super::{env::Env, exec_func};
+use crate::ast::{FuncImpl, FutureType, LiteralType, Token};
+use crate::errors::ErrorCode::E0x315;
+use corosensei::{stack::DefaultStack, Coroutine, CoroutineResult, Yielder};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet, VecDeque},
+    panic::{self, AssertUnwindSafe},
+    rc::{Rc, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// size of the stack of every task, the memory is only committed when it's used
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// id of the task in its executor, the finished task is freed when its last future is dropped
+#[derive(Debug)]
+pub struct TaskId {
+    id: usize,
+    executor: Weak<RefCell<Executor>>,
+}
+
+impl PartialEq for TaskId {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && Weak::ptr_eq(&self.executor, &other.executor)
+    }
+}
+
+impl Drop for TaskId {
+    fn drop(&mut self) {
+        // futures may outlive their executor
+        if let Some(executor) = self.executor.upgrade() {
+            release(&executor, self.id);
+        }
+    }
+}
+
+/// task running on its own stack, it yields the id of the future it waits for
+type Routine = Coroutine<(), usize, LiteralType, DefaultStack>;
+
+enum Task {
+    /// call of the async function, it starts when it's awaited or spawned
+    Call {
+        func: FuncImpl,
+        args: Vec<LiteralType>,
+        env: Rc<RefCell<Env>>,
+        call: Token,
+    },
+    /// the function is running right now
+    Running,
+    /// the spawned function waits for the future with the id
+    Suspended(Routine, usize),
+    /// completes after the deadline
+    Timer(Instant),
+    /// completes with values of all of its futures
+    All(Vec<FutureType>),
+    Done(LiteralType),
+}
+
+/// tasks of the program, or of the sources of one `Engine`
+#[derive(Default)]
+pub struct Executor {
+    tasks: HashMap<usize, Task>,
+    next_id: usize,
+    /// spawned calls and suspended tasks in the order they run
+    queue: VecDeque<usize>,
+    /// running tasks without futures, they're freed when they finish
+    detached: HashSet<usize>,
+}
+
+thread_local! {
+    /// executor of the running program, `enter` switches it
+    static EXECUTOR: RefCell<Rc<RefCell<Executor>>> = RefCell::default();
+    /// yielder of the task running right now, `None` on the stack of the program
+    static YIELDER: Cell<Option<*const Yielder<(), usize>>> = const { Cell::new(None) };
+}
+
+fn current() -> Rc<RefCell<Executor>> {
+    EXECUTOR.with(|executor| Rc::clone(&executor.borrow()))
+}
+
+fn with<T>(f: impl FnOnce(&mut Executor) -> T) -> T {
+    f(&mut current().borrow_mut())
+}
+
+/// runs the function with the executor, futures made meanwhile belong to it
+pub fn enter<T>(executor: &Rc<RefCell<Executor>>, f: impl FnOnce() -> T) -> T {
+    let prev = EXECUTOR.with(|current| current.replace(Rc::clone(executor)));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    EXECUTOR.with(|current| current.replace(prev));
+    result.unwrap_or_else(|e| panic::resume_unwind(e))
+}
+
+/// drops tasks left running by the stopped program and stops spawned ones from starting,
+/// other tasks are kept for their futures
+pub fn cancel(executor: &RefCell<Executor>) {
+    let cancelled: Vec<Task> = {
+        let mut executor = executor.borrow_mut();
+        let ids: Vec<usize> = executor
+            .tasks
+            .iter()
+            .filter(|(_, task)| matches!(task, Task::Running | Task::Suspended(..)))
+            .map(|(id, _)| *id)
+            .collect();
+        executor.queue.clear();
+        let mut cancelled = vec![];
+        for id in ids {
+            executor.detached.remove(&id);
+            cancelled.extend(executor.tasks.remove(&id));
+        }
+        cancelled
+    };
+    // suspended tasks unwind their stacks, their futures are released meanwhile
+    drop(cancelled);
+}
+
+fn push(task: Task) -> FutureType {
+    let executor = current();
+    let id = {
+        let mut executor = executor.borrow_mut();
+        let id = executor.next_id;
+        executor.next_id += 1;
+        executor.tasks.insert(id, task);
+        id
+    };
+    FutureType {
+        task: Rc::new(TaskId {
+            id,
+            executor: Rc::downgrade(&executor),
+        }),
+    }
+}
+
+fn id_of(future: &FutureType) -> usize {
+    future.task.id
+}
+
+/// frees the task of the dropped future, or detaches it if it still has to run
+fn release(executor: &RefCell<Executor>, id: usize) {
+    let freed = {
+        // tasks are dropped after the executor is released, a panic here would abort
+        let Ok(mut executor) = executor.try_borrow_mut() else {
+            return;
+        };
+        let running = match executor.tasks.get(&id) {
+            Some(Task::Running | Task::Suspended(..)) => true,
+            Some(Task::Call { .. }) => executor.queue.contains(&id),
+            _ => false,
+        };
+        if running {
+            executor.detached.insert(id);
+            return;
+        }
+        executor.tasks.remove(&id)
+    };
+    // the task may hold other futures, they're released after the executor is
+    drop(freed);
+}
+
+/// future of the async function call, arguments are already checked
+pub fn call(
+    func: FuncImpl,
+    args: Vec<LiteralType>,
+    env: Rc<RefCell<Env>>,
+    call: Token,
+) -> LiteralType {
+    LiteralType::Future(push(Task::Call {
+        func,
+        args,
+        env,
+        call,
+    }))
+}
+
+/// future completing after `ms` milliseconds
+pub fn sleep(ms: u64) -> LiteralType {
+    LiteralType::Future(push(Task::Timer(
+        Instant::now() + Duration::from_millis(ms),
+    )))
+}
+
+/// starts the future in the background, the next time the executor waits
+pub fn spawn(value: LiteralType) -> LiteralType {
+    if let LiteralType::Future(future) = &value {
+        with(|executor| executor.queue.push_back(id_of(future)));
+    }
+    value
+}
+
+/// future of the values of all futures, in their order, other values are kept as they are
+pub fn join_all(values: Vec<LiteralType>) -> LiteralType {
+    let futures = values
+        .into_iter()
+        .map(|value| match spawn(value) {
+            LiteralType::Future(future) => future,
+            value => push(Task::Done(value)),
+        })
+        .collect();
+    LiteralType::Future(push(Task::All(futures)))
+}
+
+/// drives the future until it completes, other tasks run while it waits.
+/// tasks suspend, the program runs the tasks from the queue
+pub fn block_on(future: &FutureType, env: &Rc<RefCell<Env>>, at: &Token) -> LiteralType {
+    let id = id_of(future);
+    // the awaited call runs on the stack of the awaiting task
+    run_call(id);
+    loop {
+        if let Some(value) = poll(id) {
+            return value;
+        }
+        if let Some(yielder) = YIELDER.get() {
+            // SAFETY: the yielder lives on the stack of the running task,
+            // it's only set while the task runs
+            unsafe { (*yielder).suspend(id) };
+            // other tasks ran meanwhile
+            YIELDER.set(Some(yielder));
+            continue;
+        }
+        if step() {
+            continue;
+        }
+        match deadline(Some(id)) {
+            Some(deadline) => thread::sleep(deadline.saturating_duration_since(Instant::now())),
+            // the future waits for itself through the waiting tasks
+            None => {
+                let err = env.borrow().err();
+                err.fail(err.diagnostic(E0x315, at.line, at.pos, vec![]))
+            }
+        }
+    }
+}
+
+/// runs spawned tasks that weren't awaited, after the program is over
+pub fn run() {
+    while with(|executor| !executor.queue.is_empty()) {
+        if step() {
+            continue;
+        }
+        match deadline(None) {
+            Some(deadline) => thread::sleep(deadline.saturating_duration_since(Instant::now())),
+            // the rest of the tasks wait for each other
+            None => break,
+        }
+    }
+}
+
+/// value of the completed future
+fn poll(id: usize) -> Option<LiteralType> {
+    let value = with(|executor| ready(executor, id).then(|| value(executor, id)))?;
+    finish(id, value.clone());
+    Some(value)
+}
+
+/// value of the ready future
+fn value(executor: &Executor, id: usize) -> LiteralType {
+    match &executor.tasks[&id] {
+        Task::Done(value) => value.clone(),
+        Task::All(futures) => LiteralType::Vec(Rc::new(
+            futures.iter().map(|f| value(executor, id_of(f))).collect(),
+        )),
+        _ => LiteralType::Void,
+    }
+}
+
+/// runs the call until it completes on the current stack, if it hasn't started
+fn run_call(id: usize) {
+    let task = with(|executor| match executor.tasks.get(&id) {
+        Some(Task::Call { .. }) => executor.tasks.insert(id, Task::Running),
+        _ => None,
+    });
+    let Some(Task::Call {
+        func,
+        args,
+        env,
+        call,
+    }) = task
+    else {
+        return;
+    };
+    let value = exec_func(func, args, &env, &call);
+    finish(id, value);
+}
+
+/// stores the value of the task, or frees the task if its futures were dropped
+fn finish(id: usize, value: LiteralType) {
+    let freed = with(|executor| {
+        if executor.detached.remove(&id) {
+            return (executor.tasks.remove(&id), Some(value));
+        }
+        (executor.tasks.insert(id, Task::Done(value)), None)
+    });
+    // the values may hold other futures, they're released after the executor is
+    drop(freed);
+}
+
+/// runs the queued tasks once, false if none of them could continue
+fn step() -> bool {
+    let mut progress = false;
+    for _ in 0..with(|executor| executor.queue.len()) {
+        let Some(id) = with(|executor| executor.queue.pop_front()) else {
+            break;
+        };
+        let task = with(|executor| match executor.tasks.get(&id) {
+            Some(Task::Call { .. }) => executor.tasks.insert(id, Task::Running),
+            Some(Task::Suspended(_, on)) if ready(executor, *on) => {
+                executor.tasks.insert(id, Task::Running)
+            }
+            Some(Task::Suspended(..)) => {
+                executor.queue.push_back(id);
+                None
+            }
+            _ => None,
+        });
+        let routine = match task {
+            Some(Task::Call {
+                func,
+                args,
+                env,
+                call,
+            }) => Coroutine::with_stack(stack(), move |yielder: &Yielder<(), usize>, ()| {
+                YIELDER.set(Some(yielder));
+                exec_func(func, args, &env, &call)
+            }),
+            Some(Task::Suspended(routine, _)) => routine,
+            _ => continue,
+        };
+        resume(id, routine);
+        progress = true;
+    }
+    progress
+}
+
+fn stack() -> DefaultStack {
+    DefaultStack::new(STACK_SIZE).expect("the stack of the task can't be allocated")
+}
+
+/// runs the task until it waits for a pending future or completes
+fn resume(id: usize, mut routine: Routine) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| routine.resume(())));
+    YIELDER.set(None);
+    match result {
+        Ok(CoroutineResult::Yield(on)) => with(|executor| {
+            executor.tasks.insert(id, Task::Suspended(routine, on));
+            executor.queue.push_back(id);
+        }),
+        Ok(CoroutineResult::Return(value)) => finish(id, value),
+        // errors of the task stop the program
+        Err(e) => panic::resume_unwind(e),
+    }
+}
+
+/// true if the future is completed, without completing it
+fn ready(executor: &Executor, id: usize) -> bool {
+    match executor.tasks.get(&id) {
+        Some(Task::Done(_)) => true,
+        Some(Task::Timer(deadline)) => Instant::now() >= *deadline,
+        Some(Task::All(futures)) => futures.iter().all(|f| ready(executor, id_of(f))),
+        _ => false,
+    }
+}
+
+/// the earliest pending timer the future, or the suspended tasks, wait for
+fn deadline(future: Option<usize>) -> Option<Instant> {
+    let now = Instant::now();
+    with(|executor| {
+        let mut waits: Vec<usize> = executor
+            .tasks
+            .values()
+            .filter_map(|task| match task {
+                Task::Suspended(_, on) => Some(*on),
+                _ => None,
+            })
+            .chain(future)
+            .collect();
+        let mut seen = HashSet::new();
+        let mut earliest = None;
+        while let Some(id) = waits.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            match executor.tasks.get(&id) {
+                Some(Task::Timer(deadline)) if *deadline > now => {
+                    earliest = Some(earliest.map_or(*deadline, |e: Instant| e.min(*deadline)))
+                }
+                Some(Task::All(futures)) => waits.extend(futures.iter().map(id_of)),
+                _ => {}
+            }
+        }
+        earliest
+    })
+}
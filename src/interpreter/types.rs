@@ -3,7 +3,12 @@ use crate::{
     ast::{LiteralKind, LiteralType, Token, TokenType},
     errors::raw,
 };
-use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    rc::Rc,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TypeKind {
@@ -22,6 +27,11 @@ TODO:
         // Tuple<(type)>
         types: Vec<TypeKind>,
     },
+    Map {
+        // Map<type, type>
+        key: Box<TypeKind>,
+        value: Box<TypeKind>,
+    },
     Record {
         // Record<{name: type, name: type, ..}>
         fields: Vec<(Token, TypeKind)>,
@@ -52,6 +62,16 @@ TODO:
         // "string" 5.21 false
         kind: LiteralKind,
     },
+    Generic {
+        // type with its parameters, value of generic aliases and records
+        params: Vec<Token>,
+        value: Token,
+    },
+    Instance {
+        // name<type, type, ..>, generic alias or record with its arguments
+        name: Token,
+        args: Vec<TypeKind>,
+    },
 }
 
 impl fmt::Display for TypeKind {
@@ -60,10 +80,11 @@ impl fmt::Display for TypeKind {
             TypeKind::Vec { kind } => {
                 write!(f, "<{}>", kind)
             }
+            TypeKind::Map { key, value } => write!(f, "<{}, {}>", key, value),
             TypeKind::Tuple { types } => {
                 write!(f, "(")?;
                 for (i, typ) in types.iter().enumerate() {
-                    write!(f, "{}", typ.to_string())?;
+                    write!(f, "{}", typ)?;
                     if i != types.len() - 1 {
                         write!(f, ", ")?;
                     }
@@ -85,6 +106,17 @@ impl fmt::Display for TypeKind {
             TypeKind::Maybe { lhs } => write!(f, "{}?", lhs),
             TypeKind::Important { lhs } => write!(f, "{}!", lhs),
             TypeKind::Literal { kind } => write!(f, "{:?}", kind),
+            TypeKind::Generic { value, .. } => write!(f, "{}", value.lexeme),
+            TypeKind::Instance { name, args } => {
+                write!(f, "{}<", name.lexeme)?;
+                for (i, arg) in args.iter().enumerate() {
+                    write!(f, "{}", arg)?;
+                    if i != args.len() - 1 {
+                        write!(f, ", ")?;
+                    }
+                }
+                write!(f, ">")
+            }
             TypeKind::Callback { params, ret } => {
                 write!(f, "|")?;
                 for (i, p) in params.iter().enumerate() {
@@ -102,7 +134,7 @@ impl fmt::Display for TypeKind {
 pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>) -> bool {
     match value_type.token {
         TokenType::FuncIdent => true,
-        TokenType::Enum => {
+        TokenType::Ident if env.borrow().is_enum(&value_type.lexeme) => {
             let d = env.borrow().get_enum(&value_type.lexeme);
 
             if let LiteralType::Enum {
@@ -133,8 +165,15 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
             false
         }
         TokenType::Ident => {
+            // name<T, T> instantiates the generic alias
+            if let Some(LiteralKind::Type(ref t)) = value_type.value {
+                if let TypeKind::Instance { ref name, ref args } = **t {
+                    let d = env.borrow().get_type(&name.lexeme);
+                    return type_check(&generic_value(&d, args), val, env);
+                }
+            }
             let d = env.borrow().get_type(&value_type.lexeme);
-            type_check(&d, val, env)
+            type_check(&generic_value(&d, &[]), val, env)
         }
         TokenType::Type => {
             if let Some(LiteralKind::Type(ref t)) = value_type.value {
@@ -144,7 +183,7 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
                         let lhs_t = typekind_to_literaltype(*lhs.clone());
                         let rhs_t = typekind_to_literaltype(*rhs.clone());
                         if lhs_t == *val || rhs_t == *val {
-                            return true;
+                            true
                         } else {
                             let lhs_n = match **lhs {
                                 TypeKind::Var { ref name } => type_check(name, val, env),
@@ -154,25 +193,24 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
                                 TypeKind::Var { ref name } => type_check(name, val, env),
                                 _ => rhs_t == *val,
                             };
-                            return lhs_n || rhs_n;
+                            lhs_n || rhs_n
                         }
                     }
                     TypeKind::Maybe { ref lhs } => {
                         let lhs_t = typekind_to_literaltype(*lhs.clone());
                         if lhs_t == *val || LiteralType::Null == *val {
-                            return true;
+                            true
                         } else {
-                            let lhs_n = match **lhs {
+                            match **lhs {
                                 TypeKind::Var { ref name } => type_check(name, val, env),
                                 _ => lhs_t == *val,
-                            };
-                            return lhs_n;
+                            }
                         }
                     }
                     TypeKind::Record { fields } => {
                         if let LiteralType::Record(ref rec) = *val {
                             let rec_map: HashMap<_, _> = rec.iter().cloned().collect();
-                            return fields.iter().all(|(name, field_type)| {
+                            fields.iter().all(|(name, field_type)| {
                                 if let Some(v) = rec_map.get(&name.lexeme) {
                                     let field_token = Token {
                                         token: string_to_tokentype(&field_type.to_string()),
@@ -183,11 +221,11 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
                                         line: name.line,
                                         pos: name.pos,
                                     };
-                                    return type_check(&field_token, &v.eval(Rc::clone(&env)), env);
+                                    type_check(&field_token, &v.eval(Rc::clone(env)), env)
                                 } else {
                                     false
                                 }
-                            });
+                            })
                         } else {
                             false
                         }
@@ -202,16 +240,31 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
             if val.type_name() == "function" {
                 return true;
             }
-            matches!(val, LiteralType::Number(_))
+            matches!(val, LiteralType::Int(_) | LiteralType::Float(_))
         }
+        TokenType::IntIdent => matches!(val, LiteralType::Int(_)),
+        TokenType::FloatIdent => matches!(val, LiteralType::Float(_)),
         TokenType::StrIdent => matches!(val, LiteralType::String(_)),
         TokenType::BoolIdent => matches!(val, LiteralType::Boolean(_)),
         TokenType::CharIdent => matches!(val, LiteralType::Char(_)),
         TokenType::Null => matches!(val, LiteralType::Null),
         TokenType::VoidIdent => matches!(val, LiteralType::Void),
         TokenType::VecLit => {
+            if let LiteralType::Range(range) = val {
+                // items of the range are ints, checking the first one is enough
+                let first = range.iter().take(1).map(LiteralType::Int).collect();
+                return type_check(value_type, &LiteralType::Vec(Rc::new(first)), env);
+            }
             if let LiteralType::Vec(ref array) = *val {
                 if let Some(LiteralKind::Type(ref t)) = value_type.value {
+                    // nested collections check their items against the whole item type
+                    if let TypeKind::Vec { kind } = &**t {
+                        if let TypeKind::Var { name } = &**kind {
+                            if matches!(name.token, TokenType::VecLit | TokenType::MapLit) {
+                                return array.iter().all(|item| type_check(name, item, env));
+                            }
+                        }
+                    }
                     if let TypeKind::Vec { .. } = **t {
                         return array.iter().all(|item| {
                             type_check(
@@ -222,7 +275,7 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
                                     line: value_type.line,
                                     pos: value_type.pos,
                                 },
-                                &item,
+                                item,
                                 env,
                             )
                         });
@@ -233,6 +286,21 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
                 false
             }
         }
+        TokenType::MapLit => {
+            let (LiteralType::Map(map), Some(LiteralKind::Type(t))) = (val, &value_type.value)
+            else {
+                return false;
+            };
+            let TypeKind::Map { key, value } = t.as_ref() else {
+                return false;
+            };
+            let check = |kind: &TypeKind, val: &LiteralType| match kind {
+                TypeKind::Var { name } => type_check(name, val, env),
+                _ => true,
+            };
+            map.iter()
+                .all(|(k, v)| check(key, &k.to_literal()) && check(value, v))
+        }
         TokenType::TupleLit => {
             if let LiteralType::Tuple(ref tuple) = *val {
                 if let Some(LiteralKind::Type(ref t)) = value_type.value {
@@ -244,7 +312,6 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
                                 tuple.len()
                             )
                             .as_str());
-                            return false;
                         }
                         let mut state = true;
                         for (i, tuple) in tuple.iter().enumerate() {
@@ -261,10 +328,10 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
                                     line: token.line,
                                     pos: token.pos,
                                 },
-                                &tuple,
+                                tuple,
                                 env,
                             );
-                            if s == false {
+                            if !s {
                                 state = s;
                             }
                         }
@@ -285,7 +352,7 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
         | TokenType::FalseLit
         | TokenType::CharLit => {
             match *val {
-                LiteralType::Number(ref n) => return check_num(n, value_type),
+                LiteralType::Int(_) | LiteralType::Float(_) => return check_num(val, value_type),
                 LiteralType::String(ref s) => return check_str(s, value_type),
                 LiteralType::Boolean(ref b) => return check_bool(b, value_type),
                 LiteralType::Char(ref c) => return check_char(c, value_type),
@@ -298,14 +365,14 @@ pub fn type_check(value_type: &Token, val: &LiteralType, env: &Rc<RefCell<Env>>)
     }
 }
 
-fn check_num(n: &f32, value_type: &Token) -> bool {
+fn check_num(n: &LiteralType, value_type: &Token) -> bool {
     matches!(value_type.token, TokenType::NumLit)
-        && matches!(literalkind_to_literaltype(value_type.value.clone().unwrap_or(LiteralKind::Null)), LiteralType::Number(ref m) if m == n)
+        && literalkind_to_literaltype(value_type.value.clone().unwrap_or(LiteralKind::Null)) == *n
 }
 
 fn check_str(s: &String, value_type: &Token) -> bool {
     matches!(value_type.token, TokenType::StrLit)
-        && matches!(literalkind_to_literaltype(value_type.value.clone().unwrap_or(LiteralKind::Null)), LiteralType::String(ref n) if n == s)
+        && matches!(literalkind_to_literaltype(value_type.value.clone().unwrap_or(LiteralKind::Null)), LiteralType::String(ref n) if **n == *s)
 }
 
 fn check_bool(b: &bool, value_type: &Token) -> bool {
@@ -331,8 +398,9 @@ pub fn literalkind_to_literaltype(kind: LiteralKind) -> LiteralType {
         LiteralKind::Bool { value } => LiteralType::Boolean(value),
         LiteralKind::Null => LiteralType::Null,
         LiteralKind::Char { value } => LiteralType::Char(value),
-        LiteralKind::Number { value, .. } => LiteralType::Number(value),
-        LiteralKind::String { value } => LiteralType::String(value),
+        LiteralKind::Int { value, .. } => LiteralType::Int(value),
+        LiteralKind::Float { value } => LiteralType::Float(value),
+        LiteralKind::String { value } => LiteralType::String(Rc::new(value)),
         LiteralKind::Type(t) => typekind_to_literaltype(*t),
     }
 }
@@ -344,10 +412,13 @@ pub fn typekind_to_literaltype(kind: TypeKind) -> LiteralType {
         TypeKind::Var { name } => var_to_lt(name),
         TypeKind::Callback { ret, .. } => typekind_to_literaltype(*ret),
         TypeKind::Vec { kind } => typekind_to_literaltype(*kind),
+        TypeKind::Map { .. } => LiteralType::Map(Rc::new(BTreeMap::new())),
         TypeKind::Literal { kind } => literalkind_to_literaltype(kind),
         TypeKind::Either { lhs, .. } => typekind_to_literaltype(*lhs),
         TypeKind::Maybe { lhs } => typekind_to_literaltype(*lhs),
         TypeKind::Important { lhs } => typekind_to_literaltype(*lhs),
+        TypeKind::Generic { value, .. } => var_to_lt(value),
+        TypeKind::Instance { name, .. } => var_to_lt(name),
     }
 }
 
@@ -362,9 +433,16 @@ fn rec_to_lt(fields: Vec<(Token, TypeKind)>) -> LiteralType {
     let mut rec = vec![];
     for (k, v) in fields {
         let v = typekind_to_literaltype(v);
-        rec.push((k.lexeme, Expression::Value { id: 0, value: v }));
+        rec.push((
+            k.lexeme,
+            Expression::Value {
+                id: 0,
+                token: Token::null(),
+                value: v,
+            },
+        ));
     }
-    LiteralType::Record(rec)
+    LiteralType::Record(Rc::new(rec))
 }
 fn tuple_to_lt(types: Vec<TypeKind>) -> LiteralType {
     let mut tuple = vec![];
@@ -378,6 +456,8 @@ fn tuple_to_lt(types: Vec<TypeKind>) -> LiteralType {
 pub fn string_to_tokentype(s: &str) -> TokenType {
     match s {
         "number" => TokenType::NumIdent,
+        "int" => TokenType::IntIdent,
+        "float" => TokenType::FloatIdent,
         "string" => TokenType::StrIdent,
         "boolean" => TokenType::BoolIdent,
         "char" => TokenType::CharIdent,
@@ -386,3 +466,236 @@ pub fn string_to_tokentype(s: &str) -> TokenType {
         _ => TokenType::AnyIdent,
     }
 }
+
+/// wraps the value of the generic alias or record with its type parameters
+pub fn generic(params: &[Token], value: Token) -> Token {
+    if params.is_empty() {
+        return value;
+    }
+    Token {
+        token: TokenType::Type,
+        lexeme: value.lexeme.clone(),
+        value: Some(LiteralKind::Type(Box::new(TypeKind::Generic {
+            params: params.to_vec(),
+            value: value.clone(),
+        }))),
+        line: value.line,
+        pos: value.pos,
+    }
+}
+
+/// instantiates the generic alias with the arguments, missing ones are `any`
+pub fn generic_value(alias: &Token, args: &[TypeKind]) -> Token {
+    if let Some(LiteralKind::Type(ref t)) = alias.value {
+        if let TypeKind::Generic {
+            ref params,
+            ref value,
+        } = **t
+        {
+            let bindings = params
+                .iter()
+                .enumerate()
+                .map(|(i, param)| {
+                    let arg = match args.get(i) {
+                        Some(TypeKind::Var { name }) => name.clone(),
+                        _ => any_type(),
+                    };
+                    (param.lexeme.clone(), arg)
+                })
+                .collect();
+            return instantiate(value, &bindings);
+        }
+    }
+    alias.clone()
+}
+
+/// replaces type parameters in the type with their bound types
+pub fn instantiate(token: &Token, bindings: &HashMap<String, Token>) -> Token {
+    if token.token == TokenType::Ident {
+        if let Some(bound) = bindings.get(&token.lexeme) {
+            return bound.clone();
+        }
+    }
+    let Some(LiteralKind::Type(ref t)) = token.value else {
+        return token.clone();
+    };
+    let kind = instantiate_kind(t, bindings);
+    let lexeme = match (&token.token, &kind) {
+        // vector types are named after their items
+        (TokenType::VecLit, TypeKind::Vec { kind }) => kind.to_string(),
+        (TokenType::MapLit, TypeKind::Map { key, value }) => format!("Map<{}, {}>", key, value),
+        (_, TypeKind::Instance { .. }) => kind.to_string(),
+        _ => token.lexeme.clone(),
+    };
+    Token {
+        token: token.token.clone(),
+        lexeme,
+        value: Some(LiteralKind::Type(Box::new(kind))),
+        line: token.line,
+        pos: token.pos,
+    }
+}
+
+fn instantiate_kind(kind: &TypeKind, bindings: &HashMap<String, Token>) -> TypeKind {
+    let each = |kinds: &Vec<TypeKind>| {
+        kinds
+            .iter()
+            .map(|k| instantiate_kind(k, bindings))
+            .collect()
+    };
+    let boxed = |kind: &TypeKind| Box::new(instantiate_kind(kind, bindings));
+    match kind {
+        TypeKind::Var { name } => TypeKind::Var {
+            name: instantiate(name, bindings),
+        },
+        TypeKind::Vec { kind } => TypeKind::Vec { kind: boxed(kind) },
+        TypeKind::Map { key, value } => TypeKind::Map {
+            key: boxed(key),
+            value: boxed(value),
+        },
+        TypeKind::Tuple { types } => TypeKind::Tuple { types: each(types) },
+        TypeKind::Record { fields } => TypeKind::Record {
+            fields: fields
+                .iter()
+                .map(|(name, k)| (name.clone(), instantiate_kind(k, bindings)))
+                .collect(),
+        },
+        TypeKind::Either { lhs, rhs } => TypeKind::Either {
+            lhs: boxed(lhs),
+            rhs: boxed(rhs),
+        },
+        TypeKind::Maybe { lhs } => TypeKind::Maybe { lhs: boxed(lhs) },
+        TypeKind::Important { lhs } => TypeKind::Important { lhs: boxed(lhs) },
+        TypeKind::Callback { params, ret } => TypeKind::Callback {
+            params: each(params),
+            ret: boxed(ret),
+        },
+        TypeKind::Instance { name, args } => TypeKind::Instance {
+            name: name.clone(),
+            args: each(args),
+        },
+        // inner parameters shadow the bound ones
+        TypeKind::Generic { params, value } => {
+            let mut bindings = bindings.clone();
+            params.iter().for_each(|p| {
+                bindings.remove(&p.lexeme);
+            });
+            TypeKind::Generic {
+                params: params.clone(),
+                value: instantiate(value, &bindings),
+            }
+        }
+        TypeKind::Literal { kind } => TypeKind::Literal { kind: kind.clone() },
+    }
+}
+
+/// binds type parameters of the annotation to the types of the value,
+/// the first bound type of the parameter wins
+pub fn bind(
+    token: &Token,
+    val: &LiteralType,
+    params: &[Token],
+    bindings: &mut HashMap<String, Token>,
+) {
+    if token.token == TokenType::Ident && params.iter().any(|p| p.lexeme == token.lexeme) {
+        if !bindings.contains_key(&token.lexeme) {
+            bindings.insert(token.lexeme.clone(), type_of(val));
+        }
+        return;
+    }
+    let Some(LiteralKind::Type(ref t)) = token.value else {
+        return;
+    };
+    match (t.as_ref(), val) {
+        (TypeKind::Vec { kind }, LiteralType::Vec(items)) => {
+            if let (TypeKind::Var { name }, Some(item)) = (kind.as_ref(), items.first()) {
+                bind(name, item, params, bindings);
+            }
+        }
+        (TypeKind::Vec { kind }, LiteralType::Range(range)) => {
+            if let (TypeKind::Var { name }, Some(item)) = (kind.as_ref(), range.iter().next()) {
+                bind(name, &LiteralType::Int(item), params, bindings);
+            }
+        }
+        (TypeKind::Map { key, value }, LiteralType::Map(map)) => {
+            if let Some((k, v)) = map.iter().next() {
+                if let TypeKind::Var { name } = key.as_ref() {
+                    bind(name, &k.to_literal(), params, bindings);
+                }
+                if let TypeKind::Var { name } = value.as_ref() {
+                    bind(name, v, params, bindings);
+                }
+            }
+        }
+        (TypeKind::Tuple { types }, LiteralType::Tuple(items)) => {
+            for (kind, item) in types.iter().zip(items) {
+                if let TypeKind::Var { name } = kind {
+                    bind(name, item, params, bindings);
+                }
+            }
+        }
+        (TypeKind::Maybe { lhs }, val) if *val != LiteralType::Null => {
+            if let TypeKind::Var { name } = lhs.as_ref() {
+                bind(name, val, params, bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// type of the value, vectors and maps are typed by their first item
+pub fn type_of(val: &LiteralType) -> Token {
+    let builtin = |token: TokenType, lexeme: &str| {
+        let name = Token::empty(token.clone(), lexeme, None);
+        let value = Some(LiteralKind::Type(Box::new(TypeKind::Var { name })));
+        Token::empty(token, lexeme, value)
+    };
+    match val {
+        LiteralType::Int(_) => builtin(TokenType::IntIdent, "int"),
+        LiteralType::Float(_) => builtin(TokenType::FloatIdent, "float"),
+        LiteralType::String(_) => builtin(TokenType::StrIdent, "string"),
+        LiteralType::Boolean(_) => builtin(TokenType::BoolIdent, "bool"),
+        LiteralType::Char(_) => builtin(TokenType::CharIdent, "char"),
+        LiteralType::Vec(items) => {
+            let item = items.first().map_or(any_type(), type_of);
+            Token::empty(
+                TokenType::VecLit,
+                &item.lexeme.clone(),
+                Some(LiteralKind::Type(Box::new(TypeKind::Vec {
+                    kind: Box::new(TypeKind::Var { name: item }),
+                }))),
+            )
+        }
+        LiteralType::Range(range) => type_of(&LiteralType::Vec(Rc::new(
+            range.iter().take(1).map(LiteralType::Int).collect(),
+        ))),
+        LiteralType::Map(map) => {
+            let (key, value) = map
+                .iter()
+                .next()
+                .map_or((any_type(), any_type()), |(k, v)| {
+                    (type_of(&k.to_literal()), type_of(v))
+                });
+            Token::empty(
+                TokenType::MapLit,
+                &format!("Map<{}, {}>", key.lexeme, value.lexeme),
+                Some(LiteralKind::Type(Box::new(TypeKind::Map {
+                    key: Box::new(TypeKind::Var { name: key }),
+                    value: Box::new(TypeKind::Var { name: value }),
+                }))),
+            )
+        }
+        LiteralType::Enum { parent, .. } => Token::empty(TokenType::Enum, &parent.lexeme, None),
+        _ => any_type(),
+    }
+}
+
+/// `any` type token
+pub fn any_type() -> Token {
+    let name = Token::empty(TokenType::AnyIdent, "any", None);
+    Token::empty(
+        TokenType::AnyIdent,
+        "any",
+        Some(LiteralKind::Type(Box::new(TypeKind::Var { name }))),
+    )
+}
@@ -0,0 +1,66 @@
+// Absurd library, the CLI and applications embedding Absurd are built on it
+mod ast;
+pub(crate) mod bundler;
+mod checker;
+#[doc(hidden)]
+pub mod cli;
+mod emit;
+mod engine;
+pub(crate) mod errors;
+mod formatter;
+mod interpreter;
+pub(crate) mod linter;
+mod parser;
+pub(crate) mod repl;
+mod resolver;
+mod std;
+mod vm;
+
+pub use ast::{LiteralType, MapKey};
+pub use engine::{
+    native::{IntoNative, NativeFunc},
+    value::{AbsurdKey, FromAbsurd, IntoAbsurd, Mismatch, Number},
+    Engine, Module,
+};
+pub use errors::{Diagnostic, ErrorCode, Failure, Severity, Span};
+
+// errors of the sources unwind back to `Engine` and the REPL, and tasks unwind out of their stacks
+#[cfg(panic = "abort")]
+compile_error!("absurd requires `panic = \"unwind\"`, errors of the sources are carried by unwinding");
+
+// Constants values, initial values and language information
+pub const VERSION: &str = "1.0.0";
+
+/// `Config` struct is for managing configuration across the interpreter.
+/// Struct has the following fields:
+///
+/// * `test`: true if Absurd is running in `test mode`. `[stable]`
+/// * `unsafe_mode`: true if unsafe features are enabled. `[planned]`
+/// * `diagnostics`: true if diagnostic are enabled. `[planned]`
+/// * `emit`: type of emit the interpreter should emit. `[stable]`
+/// * `vm`: true if the code is compiled to bytecode and runs on the VM. `[unstable]`
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub test: bool,
+    pub unsafe_mode: bool,
+    pub diagnostics: bool,
+    pub emit: &'static str,
+    pub vm: bool,
+}
+impl Config {
+    pub fn new() -> Self {
+        Self {
+            test: false,
+            unsafe_mode: false,
+            diagnostics: false,
+            emit: "default",
+            vm: false,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
@@ -0,0 +1,317 @@
+// Absurd linter, it walks the resolved AST and reports suspicious code, names are
+// looked up in the declarations and uses found by the resolver
+pub mod rules;
+use crate::ast::{CallType, LiteralType, MatchCase, Pattern, Statement, Token};
+use crate::interpreter::expr::Expression;
+use crate::resolver::{Bindings, Decl, DeclKind};
+use crate::std::MUT_FUNCS;
+use rules::{Lint, Rule};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone)]
+pub struct Linter {
+    rules: Vec<Rule>,
+    lints: Vec<Lint>,
+    bindings: Bindings,
+    /// declarations reassigned by the source, including natives changing their first argument
+    assigned: HashSet<usize>,
+    /// enum names with their variant names
+    enums: HashMap<String, Vec<String>>,
+}
+
+impl Linter {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Linter {
+            rules,
+            lints: vec![],
+            bindings: Bindings::default(),
+            assigned: HashSet::new(),
+            enums: HashMap::new(),
+        }
+    }
+
+    /// entry method, `bindings` are found by resolving the statements,
+    /// returns lints sorted by their position
+    pub fn lint(&mut self, stmts: &[Statement], bindings: &Bindings) -> Vec<Lint> {
+        self.bindings = bindings.clone();
+        self.assigned = bindings.assigns.values().copied().collect();
+        self.stmts(stmts);
+        let used: HashSet<usize> = bindings.uses.values().flatten().copied().collect();
+        for (idx, decl) in bindings.decls.iter().enumerate() {
+            self.shadowed(decl);
+            self.unused(decl, used.contains(&idx), self.assigned.contains(&idx));
+        }
+        self.lints.sort_by_key(|l| (l.line, l.pos.0));
+        self.lints.clone()
+    }
+
+    fn report(&mut self, rule: Rule, message: String, token: Option<&Token>) {
+        if self.rules.contains(&rule) {
+            self.lints.push(Lint::new(rule, message, token));
+        }
+    }
+
+    /// reports declarations that were never read or reassigned
+    fn unused(&mut self, decl: &Decl, is_used: bool, is_assigned: bool) {
+        let name = &decl.name;
+        let is_used =
+            is_used || (decl.depth == 0 && self.bindings.unresolved.contains(&name.lexeme));
+        match decl.kind {
+            DeclKind::Var { is_mut, is_pub } if !is_pub => {
+                if !is_used {
+                    let msg = format!("variable '{}' is never used", name.lexeme);
+                    self.report(Rule::UnusedVariable, msg, Some(name));
+                }
+                if is_mut && !is_assigned {
+                    let msg = format!("variable '{}' is never reassigned", name.lexeme);
+                    self.report(Rule::UnusedMut, msg, Some(name));
+                }
+            }
+            DeclKind::Import { .. } if !is_used => {
+                let msg = format!("import '{}' is never used", name.lexeme);
+                self.report(Rule::UnusedImport, msg, Some(name));
+            }
+            _ => {}
+        }
+    }
+
+    /// reports declarations hiding another one, functions don't shadow and aren't shadowed
+    fn shadowed(&mut self, decl: &Decl) {
+        let Some(prev) = decl.shadows.map(|idx| &self.bindings.decls[idx]) else {
+            return;
+        };
+        if decl.kind != DeclKind::Func && prev.kind != DeclKind::Func {
+            let msg = format!(
+                "'{}' shadows a binding from line {}",
+                decl.name.lexeme, prev.name.line
+            );
+            self.report(Rule::ShadowedName, msg, Some(&decl.name));
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Statement]) {
+        let mut is_dead = false;
+        let mut is_reported = false;
+        for stmt in stmts {
+            // only the first statement of the dead code is reported
+            if is_dead && !is_reported {
+                is_reported = true;
+                self.report(
+                    Rule::UnreachableCode,
+                    "unreachable statement".to_string(),
+                    stmt.token(),
+                );
+            }
+            self.stmt(stmt);
+            is_dead |= matches!(
+                stmt,
+                Statement::Return { .. } | Statement::Break { .. } | Statement::Continue { .. }
+            );
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Expression { expr } | Statement::Return { expr, .. } => self.expr(expr),
+            Statement::Block { stmts } => self.stmts(stmts),
+            Statement::Var { value, .. } => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+            }
+            Statement::Func { body, .. } => self.func(body),
+            Statement::Impl { funcs, .. } => funcs.iter().for_each(|f| self.stmt(f)),
+            Statement::If {
+                cond,
+                body,
+                else_branch,
+            } => {
+                self.expr(cond);
+                self.stmt(body);
+                if let Some(branch) = else_branch {
+                    self.stmt(branch);
+                }
+            }
+            Statement::While { cond, body, .. } => {
+                self.expr(cond);
+                self.stmt(body);
+            }
+            Statement::For { expr, body, .. } => {
+                self.expr(expr);
+                self.stmt(body);
+            }
+            Statement::Match { cond, cases } => {
+                self.matchs(cond, cases, |linter, body| linter.stmt(body))
+            }
+            Statement::Enum { name, items, .. } => {
+                let items = items.iter().map(|(i, _)| i.lexeme.clone()).collect();
+                self.enums.insert(name.lexeme.clone(), items);
+            }
+            Statement::Record { fields, .. } => fields.iter().for_each(|f| {
+                if let Some(value) = &f.default_value {
+                    self.expr(value);
+                }
+            }),
+            Statement::Use { .. }
+            | Statement::Break { .. }
+            | Statement::Continue { .. }
+            | Statement::Type { .. }
+            | Statement::Mod { .. }
+            | Statement::Sh { .. } => {}
+        }
+    }
+
+    fn func(&mut self, body: &Statement) {
+        match body {
+            Statement::Block { stmts } => self.stmts(stmts),
+            _ => self.stmt(body),
+        }
+    }
+
+    /// `body` lints bodies of the arms
+    fn matchs<T>(
+        &mut self,
+        cond: &Expression,
+        cases: &[MatchCase<T>],
+        mut body: impl FnMut(&mut Self, &T),
+    ) {
+        self.expr(cond);
+        for (idx, case) in cases.iter().enumerate() {
+            let is_default =
+                matches!(case.pattern, Pattern::Wildcard { .. }) && case.guard.is_none();
+            if is_default && self.is_exhaustive(&cases[..idx]) {
+                self.report(
+                    Rule::UselessDefault,
+                    "default arm is never reached, all cases are covered".to_string(),
+                    Some(case.pattern.token()),
+                );
+            }
+            if let Some(guard) = &case.guard {
+                self.expr(guard);
+            }
+            body(self, &case.body);
+        }
+    }
+
+    /// checks if unguarded cases cover every enum variant or both booleans
+    fn is_exhaustive<T>(&self, cases: &[MatchCase<T>]) -> bool {
+        let mut bools = HashSet::new();
+        let mut variants = HashSet::new();
+        let mut parent = None;
+        for case in cases.iter().filter(|case| case.guard.is_none()) {
+            let patterns = match &case.pattern {
+                Pattern::Or { patterns } => patterns.iter().collect(),
+                pattern => vec![pattern],
+            };
+            for pattern in patterns {
+                match pattern {
+                    Pattern::Value { value, .. } if matches!(**value, LiteralType::Boolean(_)) => {
+                        bools.insert(**value == LiteralType::Boolean(true));
+                    }
+                    Pattern::Enum {
+                        parent: name,
+                        name: v,
+                        payload,
+                    } => {
+                        if parent.is_some_and(|p: &String| p != &name.lexeme) {
+                            return false;
+                        }
+                        parent = Some(&name.lexeme);
+                        // payloads of other patterns don't cover the variant
+                        if payload.as_ref().is_none_or(|p| p.is_irrefutable()) {
+                            variants.insert(v.lexeme.clone());
+                        }
+                    }
+                    _ => return false,
+                }
+            }
+        }
+        if bools.len() == 2 {
+            return true;
+        }
+        match parent.and_then(|p| self.enums.get(p)) {
+            Some(items) => !items.is_empty() && items.iter().all(|i| variants.contains(i)),
+            None => false,
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Assign { value, .. } => self.expr(value),
+            Expression::Call {
+                name,
+                args,
+                call_type,
+                ..
+            } => {
+                self.expr(name);
+                // `push(items, item)` changes `mut` items like an assignment
+                if let (Expression::Var { id, .. }, Some(Expression::Var { id: first, .. })) =
+                    (name.as_ref(), args.first())
+                {
+                    if self.is_mut_func(*id) {
+                        let decls = self.bindings.uses.get(first).cloned().unwrap_or_default();
+                        self.assigned.extend(decls);
+                    }
+                }
+                match call_type {
+                    // `Enum::Variant(payload)`, variant isn't a binding
+                    CallType::Enum => args.iter().skip(1).for_each(|a| self.expr(a)),
+                    // `record.field`, field isn't a binding
+                    CallType::Struct => {}
+                    _ => args.iter().for_each(|a| self.expr(a)),
+                }
+            }
+            Expression::If {
+                cond,
+                body,
+                else_branch,
+                ..
+            } => {
+                self.expr(cond);
+                self.expr(body);
+                if let Some(branch) = else_branch {
+                    self.expr(branch);
+                }
+            }
+            Expression::Match { cond, cases, .. } => {
+                self.matchs(cond, cases, |linter, body| linter.expr(body))
+            }
+            Expression::Record { fields, .. } => fields.iter().for_each(|(_, v)| self.expr(v)),
+            Expression::Vec { items, .. } | Expression::Tuple { items, .. } => {
+                items.iter().for_each(|i| self.expr(i))
+            }
+            Expression::Map { items, .. } => items.iter().for_each(|(k, v)| {
+                self.expr(k);
+                self.expr(v);
+            }),
+            Expression::Unary { left, .. } => self.expr(left),
+            Expression::Binary { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expression::Grouping { expression, .. } => self.expr(expression),
+            Expression::Await { expr, .. } => self.expr(expr),
+            Expression::Func { body, .. } => self.func(body),
+            Expression::Range { lhs, rhs, step, .. } => {
+                self.expr(lhs);
+                self.expr(rhs);
+                if let Some(step) = step {
+                    self.expr(step);
+                }
+            }
+            Expression::Var { .. } | Expression::Value { .. } => {}
+        }
+    }
+
+    /// checks if the callee is the imported native changing its first argument
+    fn is_mut_func(&self, callee: usize) -> bool {
+        let decls = self.bindings.uses.get(&callee).into_iter().flatten();
+        decls
+            .map(|&idx| &self.bindings.decls[idx].kind)
+            .any(|kind| {
+                matches!(kind, DeclKind::Import { src, name }
+                if MUT_FUNCS.contains(&(src.as_str(), name.as_str())))
+            })
+    }
+}
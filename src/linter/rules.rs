@@ -0,0 +1,110 @@
+// Absurd lint rules and reports
+use crate::ast::Token;
+use coloredpp::Colorize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// `let` binding that is never read
+    UnusedVariable,
+    /// name imported with `use` that is never read
+    UnusedImport,
+    /// binding that hides a binding from an outer scope
+    ShadowedName,
+    /// statement after `return`, `break` or `continue`
+    UnreachableCode,
+    /// `let mut` binding that is never reassigned
+    UnusedMut,
+    /// `match` default arm, while every case is already covered
+    UselessDefault,
+}
+
+impl Rule {
+    pub fn all() -> Vec<Rule> {
+        vec![
+            Rule::UnusedVariable,
+            Rule::UnusedImport,
+            Rule::ShadowedName,
+            Rule::UnreachableCode,
+            Rule::UnusedMut,
+            Rule::UselessDefault,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::UnusedVariable => "unused-variable",
+            Rule::UnusedImport => "unused-import",
+            Rule::ShadowedName => "shadowed-name",
+            Rule::UnreachableCode => "unreachable-code",
+            Rule::UnusedMut => "unused-mut",
+            Rule::UselessDefault => "useless-default",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::all().into_iter().find(|r| r.name() == name)
+    }
+}
+
+/// single linter report
+#[derive(Debug, Clone)]
+pub struct Lint {
+    pub rule: Rule,
+    pub message: String,
+    pub line: usize,
+    pub pos: (usize, usize),
+}
+
+impl Lint {
+    pub fn new(rule: Rule, message: String, token: Option<&Token>) -> Self {
+        let (line, pos) = token.map_or((0, (0, 0)), |t| (t.line, t.pos));
+        Lint {
+            rule,
+            message,
+            line,
+            pos,
+        }
+    }
+
+    /// human readable report, printed to stderr
+    pub fn report(&self, path: &str) -> String {
+        let head = format!("lint warning ({}):", self.rule.name());
+        format!(
+            "{} {}, at {}:{}:{}-{}",
+            head.fg_hex("#fcb949").bold(),
+            self.message,
+            path,
+            self.line,
+            self.pos.0,
+            self.pos.1
+        )
+    }
+
+    /// machine readable report, one JSON object per line
+    pub fn json(&self, path: &str) -> String {
+        format!(
+            "{{\"file\":\"{}\",\"rule\":\"{}\",\"message\":\"{}\",\"line\":{},\"start\":{},\"end\":{}}}",
+            escape(path),
+            self.rule.name(),
+            escape(&self.message),
+            self.line,
+            self.pos.0,
+            self.pos.1
+        )
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
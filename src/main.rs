@@ -1,42 +1,15 @@
-mod ast;
-mod bundler;
-mod errors;
-mod interpreter;
-mod parser;
-mod resolver;
-mod std;
-use crate::bundler::interpreter;
-use ::std::{fs::File, io::Read};
 use abs_cli::CLI;
-use errors::log;
-
-// Constants values, initial values and language information
-pub const VERSION: &str = "1.0.0";
-
-/// `Config` struct is for managing configuration across the interpreter.
-/// Struct has the following fields:
-///
-/// * `test`: true if Absurd is running in `test mode`. `[stable]`
-/// * `unsafe_mode`: true if unsafe features are enabled. `[planned]`
-/// * `diagnostics`: true if diagnostic are enabled. `[planned]`
-/// * `emit`: type of emit the interpreter should emit. `[planned]`
-#[derive(Debug, Clone)]
-pub struct Config {
-    pub test: bool,
-    pub unsafe_mode: bool,
-    pub diagnostics: bool,
-    pub emit: &'static str,
-}
-impl Config {
-    pub fn new() -> Self {
-        Self {
-            test: false,
-            unsafe_mode: false,
-            diagnostics: false,
-            emit: "default",
-        }
-    }
-}
+use absurd::{
+    cli::{self, explain, log, Rule},
+    Config, VERSION,
+};
+use coloredpp::Colorize;
+use std::{
+    env,
+    fs::{self, File},
+    io::Read,
+    process::exit,
+};
 
 /// The `main` (entry) function is used for managing CLI and pre-processing.
 fn main() {
@@ -48,14 +21,29 @@ fn main() {
         .description("Absurd Programming Language")
         .option("-t, --test", "enable testing mode")
         .option("-d, --diagnose", "run diagnostics for better debugging")
+        .option("--vm", "compile the file to bytecode and run it on the VM")
         .option(
             "-e, --emit",
             "type of output for the interpreter to emit ([default|tokens|ast|env])",
         )
         .arg("run", "run [file]", "interpret the file")
-        .arg("lint", "lint [file]", "run the linter")
-        .arg("format", "format [file]", "format the file")
-        .arg("error", "error [code]", "get more info about the error");
+        .arg(
+            "lint",
+            "lint [file] (--json, --only=rules, --disable=rules)",
+            "run the linter, rules are comma separated",
+        )
+        .arg(
+            "format",
+            "format [file] (--check)",
+            "format the file, or check if it is formatted",
+        )
+        .arg(
+            "check",
+            "check [file]",
+            "check types of the file without running it",
+        )
+        .arg("error", "error [code]", "get more info about the error")
+        .arg("repl", "repl", "start the interactive mode, same as bare `absurd`");
     program.parse();
 
     // @todo handle flags with different args
@@ -64,11 +52,212 @@ fn main() {
         config.test = true
     }
 
-    if let Some(run) = program.get("run") {
-        match run.get(0) {
+    if program.get("--vm").is_some() {
+        config.vm = true
+    }
+
+    if let Some(emit) = flag_value("--emit") {
+        config.emit = match emit.as_str() {
+            "default" => "default",
+            "tokens" => "tokens",
+            "ast" => "ast",
+            "env" => "env",
+            _ => {
+                log("cli error: unknown emit type:");
+                eprintln!("{}", emit);
+                exit(1);
+            }
+        };
+    }
+
+    if env::args().len() == 1 || command("repl").is_some() {
+        cli::repl(config);
+    } else if let Some(run) = program.get("run") {
+        match run.first() {
             Some(r) => run_file(r, config),
             None => log("cli error: failed to get the target file"),
         }
+    } else if let Some(files) = command("format") {
+        if files.is_empty() {
+            log("cli error: failed to get the target file");
+            exit(1);
+        }
+        format_files(&files, flag("--check"));
+    } else if let Some(files) = command("lint") {
+        if files.is_empty() {
+            log("cli error: failed to get the target file");
+            exit(1);
+        }
+        lint_files(&files, lint_rules(), flag("--json"));
+    } else if let Some(files) = command("check") {
+        if files.is_empty() {
+            log("cli error: failed to get the target file");
+            exit(1);
+        }
+        check_files(&files);
+    } else if let Some(codes) = command("error") {
+        match codes.first() {
+            Some(code) => explain_error(code),
+            None => {
+                log("cli error: failed to get the error code");
+                exit(1);
+            }
+        }
+    }
+}
+
+/// Function `command` returns positional values of the `name` command, if it's called.
+/// abs-cli matches short options by their characters (`format` matches `-t`),
+/// so commands are read from the raw arguments.
+fn command(name: &str) -> Option<Vec<String>> {
+    let mut args = env::args().skip(1).skip_while(|a| a.starts_with('-'));
+    if args.next()? != name {
+        return None;
+    }
+    Some(args.filter(|a| !a.starts_with('-')).collect())
+}
+
+/// Function `flag` checks if the flag was passed to the command.
+fn flag(name: &str) -> bool {
+    env::args().skip(1).any(|a| a == name)
+}
+
+/// Function `flag_value` returns the value of the `--name=value` or `--name value` flag.
+fn flag_value(name: &str) -> Option<String> {
+    let prefix = format!("{}=", name);
+    let args: Vec<String> = env::args().skip(1).collect();
+    args.iter().enumerate().find_map(|(i, a)| match a.strip_prefix(&prefix) {
+        Some(v) => Some(v.to_string()),
+        None if a == name => args.get(i + 1).cloned(),
+        None => None,
+    })
+}
+
+/// Function `lint_rules` collects the enabled lint rules from `--only` and `--disable` flags.
+fn lint_rules() -> Vec<Rule> {
+    let parse = |value: String| -> Vec<Rule> {
+        value
+            .split(',')
+            .filter(|n| !n.is_empty())
+            .map(|n| match Rule::from_name(n) {
+                Some(rule) => rule,
+                None => {
+                    log("cli error: unknown lint rule:");
+                    eprintln!("{}", n);
+                    exit(1);
+                }
+            })
+            .collect()
+    };
+    let mut rules = flag_value("--only").map_or(Rule::all(), parse);
+    if let Some(disabled) = flag_value("--disable").map(parse) {
+        rules.retain(|r| !disabled.contains(r));
+    }
+    rules
+}
+
+/// Function `lint_files` lints files and prints the reports,
+/// as JSON lines to stdout if `json` is set.
+/// The process exits with a non-zero code if any lint was reported.
+fn lint_files(paths: &[String], rules: Vec<Rule>, json: bool) {
+    let mut count = 0;
+    for path in paths {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => {
+                log("cli error: failed to read the target:");
+                eprintln!("{}", path);
+                exit(1);
+            }
+        };
+        let lints = cli::lint(&contents, rules.clone());
+        for lint in &lints {
+            if json {
+                println!("{}", lint.json(path));
+            } else {
+                eprintln!("{}", lint.report(path));
+            }
+        }
+        count += lints.len();
+    }
+
+    if count != 0 {
+        exit(1);
+    }
+}
+
+/// Function `check_files` checks types of files without running them.
+/// Errors are reported and the process exits with a non-zero code at the first file that has them.
+fn check_files(paths: &[String]) {
+    for path in paths {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => {
+                log("cli error: failed to read the target:");
+                eprintln!("{}", path);
+                exit(1);
+            }
+        };
+        cli::check(&contents);
+    }
+}
+
+/// Function `explain_error` prints the explanation of the error code with its examples.
+fn explain_error(code: &str) {
+    let doc = match explain(code) {
+        Some(d) => d,
+        None => {
+            log("cli error: unknown error code:");
+            eprintln!("{}", code);
+            exit(1);
+        }
+    };
+    let mut is_code = false;
+    for line in doc.lines() {
+        if line.starts_with("```") {
+            is_code = !is_code;
+        } else if is_code {
+            println!("    {}", line);
+        } else if line.starts_with('#') {
+            println!("{}", line.trim_start_matches('#').trim().bold());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Function `format_files` formats files in place.
+/// In the `check` mode files are left untouched and the process exits
+/// with a non-zero code if any of them isn't formatted.
+fn format_files(paths: &[String], check: bool) {
+    let mut unformatted = vec![];
+    for path in paths {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => {
+                log("cli error: failed to read the target:");
+                eprintln!("{}", path);
+                exit(1);
+            }
+        };
+        let formatted = cli::format(&contents);
+        if formatted == contents {
+            continue;
+        }
+        if check {
+            unformatted.push(path);
+        } else if fs::write(path, formatted).is_err() {
+            log("cli error: failed to write the target:");
+            eprintln!("{}", path);
+            exit(1);
+        }
+    }
+
+    if !unformatted.is_empty() {
+        for path in unformatted {
+            eprintln!("not formatted: {}", path);
+        }
+        exit(1);
     }
 }
 
@@ -93,5 +282,5 @@ fn run_file(path: &String, config: Config) {
             return;
         }
     }
-    interpreter(&contents, config.clone());
+    cli::run(&contents, config.clone());
 }
@@ -1,10 +1,10 @@
 use super::Parser;
 use crate::ast::CallType;
-use crate::ast::LiteralKind;
 use crate::ast::LiteralType;
 use crate::ast::TokenType::*;
 use crate::errors::ErrorCode::E0x103;
 use crate::interpreter::expr::Expression;
+use std::rc::Rc;
 
 impl Parser {
     pub fn call(&mut self) -> Expression {
@@ -25,7 +25,6 @@ impl Parser {
                 Dot => expr = self.obj_call(),
                 LParen => expr = self.func_call(),
                 LBracket => expr = self.vector_call(),
-                DblDot => expr = self.range(),
                 Ident => expr = self.call(),
                 _ => {
                     self.retreat();
@@ -43,10 +42,9 @@ impl Parser {
             id: self.id(),
             name: e,
         }];
+        // payload of the enum item or arguments of the static function
         if self.if_token_consume(LParen) {
-            let expr = self.expr();
-            self.consume(RParen);
-            args.push(expr);
+            args.extend(self.call_args());
         };
 
         Expression::Call {
@@ -76,35 +74,21 @@ impl Parser {
         }
     }
 
-    pub fn range(&mut self) -> Expression {
-        let lhs = if let Some(LiteralKind::Number { value, .. }) = self.prev(2).clone().value {
-            value as usize
-        } else {
-            0
-        };
-        let rhs = if let Expression::Value { value, .. } = self.expr() {
-            if let LiteralType::Number(x) = value {
-                x as usize + 1
-            } else {
-                0
-            }
-        } else {
-            0
-        };
-        Expression::Range {
-            id: self.id(),
-            lhs,
-            rhs,
-        }
-    }
-
     pub fn obj_call(&mut self) -> Expression {
         let name = self.prev(2).clone();
         let e = self.consume(Ident);
-        let args = vec![Expression::Value {
+        let mut args = vec![Expression::Value {
             id: self.id(),
-            value: LiteralType::String(e.lexeme),
+            token: e.clone(),
+            value: LiteralType::String(Rc::new(e.lexeme)),
         }];
+        // `value.method(args)`
+        let call_type = if self.if_token_consume(LParen) {
+            args.extend(self.call_args());
+            CallType::Method
+        } else {
+            CallType::Struct
+        };
         Expression::Call {
             id: self.id(),
             name: Box::new(Expression::Var {
@@ -112,12 +96,26 @@ impl Parser {
                 name,
             }),
             args,
-            call_type: CallType::Struct,
+            call_type,
         }
     }
 
     pub fn func_call(&mut self) -> Expression {
         let name = self.prev(2).clone();
+        let args = self.call_args();
+        Expression::Call {
+            id: self.id(),
+            name: Box::new(Expression::Var {
+                id: self.id(),
+                name,
+            }),
+            args,
+            call_type: CallType::Func,
+        }
+    }
+
+    /// arguments of the call until `)`
+    fn call_args(&mut self) -> Vec<Expression> {
         let mut args = vec![];
         while !self.is_token(RParen) {
             let arg = self.expr();
@@ -130,14 +128,6 @@ impl Parser {
             }
         }
         self.consume(RParen);
-        Expression::Call {
-            id: self.id(),
-            name: Box::new(Expression::Var {
-                id: self.id(),
-                name,
-            }),
-            args,
-            call_type: CallType::Func,
-        }
+        args
     }
 }
@@ -1,6 +1,9 @@
 // parses expressions
 use super::Parser;
-use crate::ast::{Statement, Token, TokenType::*};
+use crate::ast::{
+    Statement, Token,
+    TokenType::{self, *},
+};
 use crate::errors::ErrorCode::{E0x103, E0x107};
 use crate::interpreter::expr::{AssignKind, Expression};
 
@@ -14,9 +17,11 @@ impl Parser {
     }
     // goes from most to least important expression
     pub fn expr(&mut self) -> Expression {
-        let expr = self.binary();
+        let expr = self.binary(0);
         self.advance();
         match self.prev(1).token {
+            // ranges bind looser than operators, `0..n + 1`
+            DblDot | DblDotEq => self.range(expr),
             // assignments
             Assign => self.assign(&expr, AssignKind::Normal),
             PlusEq => self.assign(&expr, AssignKind::Plus),
@@ -53,22 +58,24 @@ impl Parser {
                 if self.prev(1).token == Ident {
                     self.advance();
                     self.func_call()
-                } else if self.prev(1).token == Assign {
-                    self.advance();
-                    self.tuple_expr()
                 } else {
                     self.group_expr()
                 }
             }
             Pipe => self.func_expr(),
-            Await => self.await_expr(),
+            Await => {
+                self.advance();
+                self.await_expr()
+            }
             If => self.if_expr(),
+            Match => self.match_expr(),
             _ => {
                 if self.is_literal() {
                     self.advance();
                     Expression::Value {
                         id: self.id(),
-                        value: self.to_value_type(token),
+                        token: token.clone(),
+                        value: self.value_of(token),
                     }
                 } else {
                     self.throw_error(E0x103, vec![self.peek().lexeme.clone()]);
@@ -95,8 +102,20 @@ impl Parser {
         }
     }
 
+    fn match_expr(&mut self) -> Expression {
+        let token = self.consume(Match);
+        let cond = self.expr();
+        let cases = self.match_cases(|parser| parser.expr());
+        Expression::Match {
+            id: self.id(),
+            token,
+            cond: Box::new(cond),
+            cases,
+        }
+    }
+
     fn unary(&mut self) -> Expression {
-        if self.are_tokens(&[Bang, DblBang, Qstn, Decr, Incr, Min]) {
+        if self.are_tokens(&[Bang, DblBang, Qstn, Decr, Incr, Min, Tilde]) {
             self.advance();
             let operator = self.prev(1).clone();
             let rhs = self.unary();
@@ -111,14 +130,19 @@ impl Parser {
         }
     }
 
-    fn binary(&mut self) -> Expression {
+    /// parses binary operators with precedence climbing,
+    /// only operators binding at least as tight as `min_prec` are consumed
+    fn binary(&mut self, min_prec: u8) -> Expression {
         let mut expr = self.unary();
-        while self.are_tokens(&[
-            Plus, Min, Mul, Div, Prcnt, DblAnd, Or, Eq, BangEq, Gr, GrOrEq, Ls, LsOrEq, Sqr, And,
-        ]) {
+        while let Some(prec) = precedence(&self.peek().token) {
+            if prec < min_prec {
+                break;
+            }
             self.advance();
             let operator = self.prev(1).clone();
-            let rhs = self.unary();
+            // `**` is right associative, other operators are left associative
+            let next_prec = if operator.token == Sqr { prec } else { prec + 1 };
+            let rhs = self.binary(next_prec);
             expr = Expression::Binary {
                 id: self.id(),
                 left: Box::new(expr),
@@ -143,6 +167,26 @@ impl Parser {
         }
     }
 
+    /// parses the rest of the range after `lhs..` or `lhs..=`
+    fn range(&mut self, lhs: Expression) -> Expression {
+        let token = self.prev(1).clone();
+        let rhs = self.binary(0);
+        // `lhs..rhs step n`
+        let step = if self.if_token_consume(Step) {
+            Some(Box::new(self.binary(0)))
+        } else {
+            None
+        };
+        Expression::Range {
+            id: self.id(),
+            is_inclusive: token.token == DblDotEq,
+            token,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            step,
+        }
+    }
+
     fn obj_expr(&mut self) -> Expression {
         let mut fields = vec![];
         while !self.if_token_consume(RBrace) {
@@ -161,9 +205,20 @@ impl Parser {
     }
 
     fn arr_expr(&mut self) -> Expression {
+        let bracket = self.prev(1).clone();
         let mut items = vec![];
+        // [:] is an empty map
+        if self.if_token_consume(Colon) {
+            self.consume(RBracket);
+            return self.map_expr(bracket, vec![]);
+        }
         while !self.if_token_consume(RBracket) {
             let e = self.expr();
+            // [key: value, ..] is a map
+            if items.is_empty() && self.if_token_consume(Colon) {
+                let value = self.expr();
+                return self.map_items(bracket, e, value);
+            }
             items.push(e);
             if !self.if_token_consume(Comma) && !self.is_token(RBracket) {
                 self.throw_error(E0x103, vec![self.peek().lexeme.clone()]);
@@ -175,28 +230,53 @@ impl Parser {
         }
     }
 
-    fn tuple_expr(&mut self) -> Expression {
-        let mut items = vec![];
-        while !self.if_token_consume(RParen) {
-            let e = self.expr();
-            items.push(e);
-            if !self.if_token_consume(Comma) && !self.is_token(RParen) {
-                self.throw_error(E0x103, vec![self.peek().lexeme.clone()]);
-            }
+    /// parses the rest of the map literal after its first item
+    fn map_items(&mut self, bracket: Token, key: Expression, value: Expression) -> Expression {
+        let mut items = vec![(key, value)];
+        while self.if_token_consume(Comma) && !self.is_token(RBracket) {
+            let key = self.expr();
+            self.consume(Colon);
+            items.push((key, self.expr()));
         }
-        Expression::Tuple {
+        self.consume(RBracket);
+        self.map_expr(bracket, items)
+    }
+
+    fn map_expr(&mut self, token: Token, items: Vec<(Expression, Expression)>) -> Expression {
+        Expression::Map {
             id: self.id(),
+            token,
             items,
         }
     }
 
+    /// parses `(expr)` as a grouping and `(expr, ..)` or `()` as a tuple
     fn group_expr(&mut self) -> Expression {
         self.advance();
+        if self.if_token_consume(RParen) {
+            return Expression::Tuple {
+                id: self.id(),
+                items: vec![],
+            };
+        }
         let expr = self.expr();
-        self.consume(RParen);
-        Expression::Grouping {
+        if self.if_token_consume(RParen) {
+            return Expression::Grouping {
+                id: self.id(),
+                expression: Box::new(expr),
+            };
+        }
+
+        let mut items = vec![expr];
+        while self.if_token_consume(Comma) && !self.is_token(RParen) {
+            items.push(self.expr());
+        }
+        if !self.if_token_consume(RParen) {
+            self.throw_error(E0x103, vec![self.peek().lexeme.clone()]);
+        }
+        Expression::Tuple {
             id: self.id(),
-            expression: Box::new(expr),
+            items,
         }
     }
 
@@ -273,10 +353,32 @@ impl Parser {
     }
 
     fn await_expr(&mut self) -> Expression {
-        let expr = self.expr();
+        let token = self.prev(1).clone();
+        // `await f() + 1` awaits the call only
+        let expr = self.call();
         Expression::Await {
             id: self.id(),
+            token,
             expr: Box::new(expr),
         }
     }
 }
+
+/// binding power of binary operators, from the loosest to the tightest
+fn precedence(token: &TokenType) -> Option<u8> {
+    let prec = match token {
+        Or => 0,
+        DblAnd => 1,
+        Eq | BangEq => 2,
+        Gr | GrOrEq | Ls | LsOrEq => 3,
+        Pipe => 4,
+        Caret => 5,
+        And => 6,
+        Shl | Shr => 7,
+        Plus | Min => 8,
+        Mul | Div | Prcnt => 9,
+        Sqr => 10,
+        _ => return None,
+    };
+    Some(prec)
+}
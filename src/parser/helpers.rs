@@ -1,26 +1,34 @@
 // helper methods for parser
-use super::Parser;
+use super::{Parser, NEXT_ID};
 use crate::ast::{
     LiteralKind, LiteralType, Token,
     TokenType::{self, *},
 };
+use crate::ast::Statement;
 use crate::errors::ErrorCode::{self, E0x104, E0x106};
-use std::process::exit;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+
+/// unwinding payload of syntax errors, caught by `Parser::recover`
+struct SyntaxError;
 
 impl Parser {
     /// extracts values from token
-    pub fn to_value_type(&mut self, token: Token) -> LiteralType {
+    pub fn value_of(&mut self, token: Token) -> LiteralType {
         match token.token {
             NumLit => {
-                if let Some(LiteralKind::Number { value, .. }) = token.value {
-                    LiteralType::Number(value)
+                if let Some(LiteralKind::Int { value, .. }) = token.value {
+                    LiteralType::Int(value)
+                } else if let Some(LiteralKind::Float { value }) = token.value {
+                    LiteralType::Float(value)
                 } else {
                     self.throw_error(E0x104, vec![self.peek().lexeme])
                 }
             }
             StrLit => {
                 if let Some(LiteralKind::String { value }) = token.value {
-                    LiteralType::String(value)
+                    LiteralType::String(Rc::new(value))
                 } else {
                     self.throw_error(E0x104, vec![self.peek().lexeme])
                 }
@@ -67,16 +75,6 @@ impl Parser {
         }
     }
 
-    #[inline]
-    /// checks if identifier is uppercase
-    pub fn is_uppercase_ident(&self) -> bool {
-        self.peek()
-            .lexeme
-            .chars()
-            .next()
-            .map_or(false, |c| c.is_uppercase())
-    }
-
     #[inline]
     /// checks if token is uppercase
     pub fn is_uppercase(&self, token: Token) -> bool {
@@ -84,7 +82,7 @@ impl Parser {
             .lexeme
             .chars()
             .next()
-            .map_or(false, |c| c.is_uppercase())
+            .is_some_and(|c| c.is_uppercase())
     }
 
     /// takes multiple tokens and consumes whichever matches first
@@ -167,13 +165,60 @@ impl Parser {
     #[inline]
     /// gets id and increases previous
     pub fn id(&mut self) -> usize {
-        self.id += 1;
-        self.id - 1
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
     }
 
+    /// reports the error and unwinds to the closest statement boundary, see `recover`
     pub fn throw_error(&mut self, code: ErrorCode, args: Vec<String>) -> ! {
         self.err
-            .throw(code, self.peek().line, self.peek().pos, args);
-        exit(1);
+            .report(code, self.peek().line, self.peek().pos, args);
+        panic::resume_unwind(Box::new(SyntaxError));
+    }
+
+    /// parses a statement, if it fails, skips the rest of it and returns `None`,
+    /// so the parser keeps going and reports every syntax error at once
+    pub fn recover(&mut self, in_block: bool) -> Option<Statement> {
+        let stmt = self.catch(Self::stmt);
+        if stmt.is_none() {
+            self.synchronize(in_block);
+        }
+        stmt
+    }
+
+    /// runs the parsing function, returns `None` if it failed with a syntax error
+    pub fn catch<T>(&mut self, parse: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        match panic::catch_unwind(AssertUnwindSafe(|| parse(self))) {
+            Ok(value) => Some(value),
+            Err(e) if e.is::<SyntaxError>() => None,
+            Err(e) => panic::resume_unwind(e),
+        }
+    }
+
+    /// skips tokens until the end of the broken statement: after `;`, after its `{}` body,
+    /// or before the `}` closing the enclosing block
+    fn synchronize(&mut self, in_block: bool) {
+        let mut depth = 0;
+        while !self.check(Eof) {
+            match self.peek().token {
+                Semi if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                RBrace if depth == 0 => {
+                    if !in_block {
+                        self.advance();
+                    }
+                    return;
+                }
+                RBrace if depth == 1 => {
+                    self.advance();
+                    return;
+                }
+                RBrace => depth -= 1,
+                LBrace => depth += 1,
+                _ => {}
+            }
+            self.advance();
+        }
     }
 }
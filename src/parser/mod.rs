@@ -5,15 +5,22 @@ use crate::interpreter::expr::Expression;
 mod call;
 pub mod expr;
 mod helpers;
+mod pattern;
 pub mod scanner;
 mod types;
+use std::sync::atomic::AtomicUsize;
+
+/// ids of expressions are unique in the process, locals resolved in every parsed source
+/// (modules, REPL inputs, interpolated strings) share one table
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Debug, Clone)]
 pub struct Parser {
     tokens: Vec<Token>,
     err: Error,
     crnt: usize,
-    id: usize,
+    /// type of the `impl` block being parsed, it's the type of `self` parameters
+    impl_type: Option<Token>,
 }
 
 impl Parser {
@@ -22,7 +29,7 @@ impl Parser {
             tokens,
             err,
             crnt: 0,
-            id: 0,
+            impl_type: None,
         }
     }
 
@@ -32,13 +39,15 @@ impl Parser {
 
         // parse statements until the end of file (source)
         while !self.check(Eof) {
-            stmts.push(self.stmt());
+            if let Some(stmt) = self.recover(false) {
+                stmts.push(stmt);
+            }
         }
         // returns collection of statements
         stmts
     }
 
-    fn stmt(&mut self) -> Statement {
+    pub fn stmt(&mut self) -> Statement {
         // advance to consume keywords
         self.advance();
         match self.prev(1).token {
@@ -49,6 +58,7 @@ impl Parser {
             For => self.fors(),
             While => self.whiles(),
             Break => self.breaks(),
+            Continue => self.continues(),
             Match => self.matchs(),
             Sh => self.shs(),
             Mod => self.mods(),
@@ -56,16 +66,41 @@ impl Parser {
             LBrace => self.block_stmt(),
             TypeStmt => self.types(),
             RecordStmt => self.record(),
+            Impl => self.impls(),
             Enum => self.enums(),
             Label => self.label(),
             _ => self.exprs(),
         }
     }
 
+    /// parses `label name: stmt`, only loops can be labeled
     fn label(&mut self) -> Statement {
-        self.if_token_consume(Ident);
+        let name = self.consume(Ident);
         self.consume(Colon);
-        self.stmt()
+        if !self.are_tokens(&[For, While]) {
+            self.throw_error(E0x106, vec!["for".to_string()]);
+        }
+        match self.stmt() {
+            Statement::For {
+                iterator,
+                index,
+                expr,
+                body,
+                ..
+            } => Statement::For {
+                iterator,
+                index,
+                expr,
+                body,
+                label: Some(name),
+            },
+            Statement::While { cond, body, .. } => Statement::While {
+                cond,
+                body,
+                label: Some(name),
+            },
+            stmt => stmt,
+        }
     }
 
     /// parses variable publicity and returns variable publicit
@@ -176,6 +211,7 @@ impl Parser {
         let mut destruct = None;
 
         // checks if variable is immutable and consumes `pub` keyword, if its there
+        let has_pub = !is_mut && self.is_token(Pub);
         let mut pub_names = self.var_is_pub(is_mut);
         let mut is_pub = !pub_names.is_empty();
         if self.if_token_consume(LBracket) {
@@ -210,16 +246,22 @@ impl Parser {
         // publish by their local names
         if pub_names.is_empty() {
             pub_names = names.clone();
-            is_pub = false;
+            is_pub = has_pub;
         }
 
-        let null_var = Statement::Var {
-            names: names.clone(),
-            value_type: Token::null(),
-            value: Some(Expression::Value {
+        // public variables must have a value
+        let null_value = match is_pub {
+            true => None,
+            false => Some(Expression::Value {
                 id: self.id(),
+                token: Token::null(),
                 value: LiteralType::Null,
             }),
+        };
+        let null_var = Statement::Var {
+            names: names.clone(),
+            value_type: Token::null(),
+            value: null_value.clone(),
             is_mut,
             is_pub,
             pub_names: pub_names.clone(),
@@ -256,10 +298,7 @@ impl Parser {
             return Statement::Var {
                 names: names.clone(),
                 value_type,
-                value: Some(Expression::Value {
-                    id: self.id(),
-                    value: LiteralType::Null,
-                }),
+                value: null_value,
                 is_mut,
                 is_pub,
                 pub_names: pub_names.clone(),
@@ -272,7 +311,8 @@ impl Parser {
         // check if variable has a callback as a value
         let is_func = self.is_token(Pipe);
         let value = self.expr();
-        if is_inference {
+        // only literals can be inferred while parsing, other values stay `any`
+        if is_inference && matches!(value, Expression::Value { .. }) {
             value_type = value.to_literal().to_token();
         }
         self.consume(Semi);
@@ -307,14 +347,21 @@ impl Parser {
         }
 
         let name = self.consume(Ident);
+        let generics = self.type_params();
 
         // handles parameters, `...(i: T, i: T)...`
         self.consume(LParen);
         while !self.if_token_consume(RParen) {
             if self.is_token(Ident) {
                 let param_name = self.consume(Ident);
-                self.consume(Colon);
-                let param_type = self.consume_type();
+                // `self` of methods takes the type of the `impl` block
+                let param_type = match &self.impl_type {
+                    Some(t) if param_name.lexeme == "self" && !self.is_token(Colon) => t.clone(),
+                    _ => {
+                        self.consume(Colon);
+                        self.consume_type()
+                    }
+                };
                 params.push((param_name, param_type))
             } else if self.if_token_consume(Comma) {
             } else if !self.is_token(RParen) {
@@ -326,25 +373,19 @@ impl Parser {
         self.consume(Arrow);
         let value_type = self.consume_type();
 
-        // parse as a short function
-        if self.if_token_consume(Assign) {
+        let body = if self.if_token_consume(Assign) {
+            // parse as a short function
             let expr = self.expr();
             self.consume(Semi);
-            return Statement::Func {
-                name,
-                value_type,
-                body: Box::new(Statement::Expression { expr }),
-                params,
-                is_async,
-                is_pub,
-            };
-        }
-
-        // standard block parsing
-        self.consume(LBrace);
-        let body = self.block_stmt();
+            Statement::Expression { expr }
+        } else {
+            // standard block parsing
+            self.consume(LBrace);
+            self.block_stmt()
+        };
         Statement::Func {
             name,
+            generics,
             value_type,
             body: Box::new(body),
             params,
@@ -354,27 +395,33 @@ impl Parser {
     }
 
     fn returns(&mut self) -> Statement {
+        let keyword = self.prev(1);
         let expr = if self.is_token(Semi) {
             Expression::Value {
                 id: self.id(),
+                token: Token::null(),
                 value: LiteralType::Null,
             }
         } else {
             self.expr()
         };
         self.consume(Semi);
-        Statement::Return { expr }
+        Statement::Return { keyword, expr }
     }
 
-    fn ifs(&mut self) -> Statement {
-        let cond = self.expr();
-
-        let body = if self.if_token_consume(LBrace) {
+    /// parses body of control flow statements, `{ ... }` or `: stmt`
+    fn body_stmt(&mut self) -> Statement {
+        if self.if_token_consume(LBrace) {
             self.block_stmt()
         } else {
             self.consume(Colon);
             self.stmt()
-        };
+        }
+    }
+
+    fn ifs(&mut self) -> Statement {
+        let cond = self.expr();
+        let body = self.body_stmt();
         let mut else_if_branches = vec![];
         // parse elifs
         while self.if_token_consume(Elif) {
@@ -384,11 +431,19 @@ impl Parser {
         }
 
         // parse else, if avaiable
-        let else_branch = if self.if_token_consume(Else) {
+        let mut else_branch = if self.if_token_consume(Else) {
             Some(Box::new(self.stmt()))
         } else {
             None
         };
+        // elifs are chained as nested `else if` statements
+        for (cond, stmts) in else_if_branches.into_iter().rev() {
+            else_branch = Some(Box::new(Statement::If {
+                cond,
+                body: Box::new(Statement::Block { stmts }),
+                else_branch,
+            }));
+        }
         Statement::If {
             cond,
             body: Box::new(body),
@@ -405,28 +460,48 @@ impl Parser {
         };
         self.consume(In);
         let expr = self.expr();
-        let body = self.block_stmt();
+        let body = self.body_stmt();
         Statement::For {
             iterator,
             index,
             expr,
             body: Box::new(body),
+            label: None,
         }
     }
 
     fn whiles(&mut self) -> Statement {
         // everything is obvious, I guess.
         let cond = self.expr();
-        let body = self.block_stmt();
+        let body = self.body_stmt();
         Statement::While {
             cond,
             body: Box::new(body),
+            label: None,
         }
     }
 
     fn breaks(&mut self) -> Statement {
+        let keyword = self.prev(1);
+        let label = self.loop_label();
+        Statement::Break { keyword, label }
+    }
+
+    fn continues(&mut self) -> Statement {
+        let keyword = self.prev(1);
+        let label = self.loop_label();
+        Statement::Continue { keyword, label }
+    }
+
+    /// parses the optional label of `break` and `continue`
+    fn loop_label(&mut self) -> Option<Token> {
+        let label = if self.is_token(Ident) {
+            Some(self.consume(Ident))
+        } else {
+            None
+        };
         self.consume(Semi);
-        Statement::Break {}
+        label
     }
 
     fn enums(&mut self) -> Statement {
@@ -435,11 +510,7 @@ impl Parser {
             raw("enum name must start with uppercase alphabet");
         }
 
-        let is_pub = if self.if_token_consume(Pub) {
-            true
-        } else {
-            false
-        };
+        let is_pub = self.if_token_consume(Pub);
         self.consume(LBrace);
         let mut items = vec![];
         while !self.if_token_consume(RBrace) {
@@ -450,9 +521,10 @@ impl Parser {
             if self.if_token_consume(LParen) {
                 let typ = self.consume_type();
                 self.consume(RParen);
-                items.push((name.clone(), Some(typ)))
+                items.push((name, Some(typ)))
+            } else {
+                items.push((name, None));
             }
-            items.push((name, None));
             if !self.if_token_consume(Comma) && !self.is_token(RBrace) {
                 break;
             }
@@ -467,43 +539,15 @@ impl Parser {
 
     fn matchs(&mut self) -> Statement {
         let cond = self.expr();
-        self.consume(LBrace);
-        let mut cases = vec![];
-
-        // match can only "match" literals and Enums
-        while self.is_literal() || self.is_uppercase_ident() {
-            let expr = self.expr();
-            self.consume(ArrowBig);
-            // consume block
-            if self.if_token_advance(LBrace) {
-                let body = self.block_stmt();
-                cases.push((expr, body))
-            } else {
-                // consume expression
-                let expr = self.expr();
-                self.consume(Comma);
-                cases.push((expr.clone(), Statement::Expression { expr }))
-            }
-        }
-
-        let mut def_case = Statement::Break {};
-        // default branch `_ => {}`
-        if self.if_token_consume(Underscore) {
-            self.consume(ArrowBig);
-            if self.if_token_consume(LBrace) {
-                def_case = self.block_stmt();
+        let cases = self.match_cases(|parser| {
+            if parser.if_token_advance(LBrace) {
+                parser.block_stmt()
             } else {
-                let expr = self.expr();
-                def_case = Statement::Expression { expr };
+                let expr = parser.expr();
+                Statement::Expression { expr }
             }
-        }
-        let stmt = Statement::Match {
-            cond,
-            cases,
-            def_case: Box::new(def_case),
-        };
-        self.consume(RBrace);
-        stmt
+        });
+        Statement::Match { cond, cases }
     }
 
     // very simple syntax
@@ -546,6 +590,7 @@ impl Parser {
                 self.if_token_consume(Comma);
             }
         }
+        let from = self.peek();
         let src = if self.is_token(Ident) {
             let mut lex = self.consume(Ident).lexeme;
             if lex == "std" {
@@ -560,24 +605,46 @@ impl Parser {
             self.consume(StrLit).lexeme
         };
         self.consume(Semi);
-        Statement::Use { src, names, all }
+        Statement::Use {
+            src,
+            from,
+            names,
+            all,
+        }
     }
 
     fn types(&mut self) -> Statement {
         let is_pub = self.if_token_consume(Pub);
         let name = self.consume(Ident);
+        let generics = self.type_params();
         self.consume(Assign);
         let value = self.consume_type();
         self.consume(Semi);
         Statement::Type {
             name,
+            generics,
             value,
             is_pub,
         }
     }
 
+    /// `impl name { func ... }`
+    fn impls(&mut self) -> Statement {
+        let name = self.consume(Ident);
+        self.consume(LBrace);
+        let encl_type = self.impl_type.replace(self.named_type(name.clone()));
+        let mut funcs = vec![];
+        while !self.if_token_consume(RBrace) {
+            self.consume(Func);
+            funcs.push(self.func());
+        }
+        self.impl_type = encl_type;
+        Statement::Impl { name, funcs }
+    }
+
     fn record(&mut self) -> Statement {
         let name = self.consume(Ident);
+        let generics = self.type_params();
 
         let mut extends = vec![];
         if self.if_token_consume(Extends) {
@@ -610,7 +677,7 @@ impl Parser {
             let value = self.consume_type();
             let mut default_value = None;
 
-            if self.if_token_consume(Eq) {
+            if self.if_token_consume(Assign) {
                 default_value = Some(self.expr())
             }
 
@@ -630,6 +697,7 @@ impl Parser {
 
         Statement::Record {
             name,
+            generics,
             extends,
             is_strict: false,
             fields,
@@ -637,6 +705,7 @@ impl Parser {
     }
 
     fn block_stmts(&mut self) -> Vec<Statement> {
+        self.consume(LBrace);
         match self.block_stmt() {
             Statement::Block { stmts } => stmts,
             _ => self.throw_error(E0x105, vec!["a block statement".to_string()]),
@@ -645,9 +714,13 @@ impl Parser {
 
     fn block_stmt(&mut self) -> Statement {
         let mut stmts = vec![];
-        while !self.if_token_consume(RBrace) && !self.is_token(Eof) {
-            let stmt = self.stmt();
-            stmts.push(stmt);
+        while !self.if_token_consume(RBrace) {
+            if self.is_token(Eof) {
+                self.throw_error(E0x106, vec![RBrace.to_string()]);
+            }
+            if let Some(stmt) = self.recover(true) {
+                stmts.push(stmt);
+            }
         }
         Statement::Block { stmts }
     }
@@ -0,0 +1,158 @@
+// parses patterns of `match` arms
+use super::Parser;
+use crate::ast::{
+    LiteralType, MatchCase, Pattern,
+    TokenType::{self, *},
+};
+use crate::errors::ErrorCode::E0x103;
+
+impl Parser {
+    /// `{ pattern if guard => body, .. }`, `body` parses bodies of the arms
+    pub fn match_cases<T>(&mut self, mut body: impl FnMut(&mut Self) -> T) -> Vec<MatchCase<T>> {
+        self.consume(LBrace);
+        let mut cases = vec![];
+        while !self.is_token(RBrace) {
+            let pattern = self.pattern();
+            let guard = if self.if_token_consume(If) {
+                Some(self.expr())
+            } else {
+                None
+            };
+            self.consume(ArrowBig);
+            let body = body(self);
+            // the comma is optional after blocks and the last arm
+            if self.prev(1).token == RBrace || self.is_token(RBrace) {
+                self.if_token_consume(Comma);
+            } else {
+                self.consume(Comma);
+            }
+            cases.push(MatchCase {
+                pattern,
+                guard,
+                body,
+            });
+        }
+        self.consume(RBrace);
+        cases
+    }
+
+    /// `a | b | c`
+    pub fn pattern(&mut self) -> Pattern {
+        let first = self.pattern_item();
+        if !self.is_token(Pipe) {
+            return first;
+        }
+        let mut patterns = vec![first];
+        while self.if_token_consume(Pipe) {
+            patterns.push(self.pattern_item());
+        }
+        Pattern::Or { patterns }
+    }
+
+    fn pattern_item(&mut self) -> Pattern {
+        let token = self.advance();
+        match token.token {
+            Underscore => Pattern::Wildcard { token },
+            // `Enum::Item(payload)`
+            Ident if self.is_token(DblColon) => {
+                self.consume(DblColon);
+                let name = self.consume(Ident);
+                let payload = if self.if_token_consume(LParen) {
+                    let payload = self.pattern();
+                    self.consume(RParen);
+                    Some(Box::new(payload))
+                } else {
+                    None
+                };
+                Pattern::Enum {
+                    parent: token,
+                    name,
+                    payload,
+                }
+            }
+            Ident => Pattern::Bind { name: token },
+            LParen => {
+                let items = self.pattern_items(RParen);
+                // `(a)` is grouped, `(a,)` is the tuple
+                if items.len() == 1 && self.prev(1).token != Comma {
+                    self.consume(RParen);
+                    return items.into_iter().next().unwrap();
+                }
+                self.consume(RParen);
+                Pattern::Tuple { token, items }
+            }
+            LBracket => {
+                let items = self.pattern_items(RBracket);
+                let has_rest = self.if_token_consume(DblDot);
+                self.consume(RBracket);
+                Pattern::Vec {
+                    token,
+                    items,
+                    has_rest,
+                }
+            }
+            LBrace => {
+                let mut fields = vec![];
+                while !self.is_token(RBrace) {
+                    let name = self.consume(Ident);
+                    let field = if self.if_token_consume(Colon) {
+                        self.pattern()
+                    } else {
+                        Pattern::Bind { name: name.clone() }
+                    };
+                    fields.push((name, field));
+                    if !self.if_token_consume(Comma) {
+                        break;
+                    }
+                }
+                self.consume(RBrace);
+                Pattern::Record { token, fields }
+            }
+            _ => {
+                self.retreat();
+                let lhs = self.pattern_value();
+                if !self.are_tokens(&[DblDot, DblDotEq]) {
+                    return Pattern::Value {
+                        token,
+                        value: Box::new(lhs),
+                    };
+                }
+                let is_inclusive = self.advance().token == DblDotEq;
+                let rhs = self.pattern_value();
+                Pattern::Range {
+                    token,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    is_inclusive,
+                }
+            }
+        }
+    }
+
+    /// comma separated patterns until the closing token or `..`
+    fn pattern_items(&mut self, close: TokenType) -> Vec<Pattern> {
+        let mut items = vec![];
+        while !self.is_token(close.clone()) && !self.is_token(DblDot) {
+            items.push(self.pattern());
+            if !self.if_token_consume(Comma) {
+                break;
+            }
+        }
+        items
+    }
+
+    /// literal of the pattern, numbers can be negative
+    fn pattern_value(&mut self) -> LiteralType {
+        let is_negative = self.if_token_consume(Min);
+        if !self.is_literal() {
+            self.throw_error(E0x103, vec![self.peek().lexeme.clone()]);
+        }
+        let token = self.advance();
+        match (is_negative, self.value_of(token)) {
+            (true, LiteralType::Int(n)) => LiteralType::Int(-n),
+            (true, LiteralType::Float(n)) => LiteralType::Float(-n),
+            (true, _) => self.throw_error(E0x103, vec!["-".to_string()]),
+            (false, value) => value,
+        }
+    }
+}
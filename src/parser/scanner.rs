@@ -1,5 +1,5 @@
 use crate::ast::{
-    Base, LiteralKind, Token,
+    Base, Comment, LiteralKind, Token,
     TokenType::{self, *},
 };
 use crate::errors::{Error, ErrorCode::*};
@@ -11,6 +11,7 @@ use unicode_xid::UnicodeXID;
 pub struct Scanner<'a> {
     src: &'a str,
     tokens: Vec<Token>,
+    comments: Vec<Comment>,
     kwds: HashMap<&'static str, TokenType>,
     line: usize,
     pos: usize,
@@ -25,11 +26,14 @@ impl<'a> Scanner<'a> {
             src,
             err,
             tokens: vec![],
+            comments: vec![],
             kwds: HashMap::from([
                 ("record", RecordStmt),
+                ("impl", Impl),
                 ("extends", Extends),
                 ("strict", Strict),
                 ("label", Label),
+                ("step", Step),
                 ("enum", Enum),
                 ("sh", Sh),
                 ("let", Let),
@@ -41,6 +45,7 @@ impl<'a> Scanner<'a> {
                 ("in", In),
                 ("while", While),
                 ("break", Break),
+                ("continue", Continue),
                 ("match", Match),
                 ("mod", Mod),
                 ("use", Use),
@@ -55,6 +60,8 @@ impl<'a> Scanner<'a> {
                 ("true", TrueLit),
                 ("false", FalseLit),
                 ("number", NumIdent),
+                ("int", IntIdent),
+                ("float", FloatIdent),
                 ("string", StrIdent),
                 ("char", CharIdent),
                 ("bool", BoolIdent),
@@ -63,6 +70,7 @@ impl<'a> Scanner<'a> {
                 ("array", ArrayIdent),
                 ("any", AnyIdent),
                 ("Vec", VecT),
+                ("Map", MapT),
                 ("Record", Record),
                 ("Tuple", Tuple),
             ]),
@@ -73,6 +81,14 @@ impl<'a> Scanner<'a> {
         }
     }
 
+    /// scanner of the source continuing another one, its first line is `line`
+    pub fn from_line(src: &'a str, line: usize, err: Error) -> Self {
+        Self {
+            line,
+            ..Self::new(src, err)
+        }
+    }
+
     /// main scanner function
     pub fn scan(&mut self) -> &Vec<Token> {
         // advance until the end of the file
@@ -92,6 +108,11 @@ impl<'a> Scanner<'a> {
         &self.tokens
     }
 
+    /// comments collected while scanning
+    pub fn comments(&self) -> &Vec<Comment> {
+        &self.comments
+    }
+
     /// checks if end of file is reached
     fn is_eof(&self) -> bool {
         self.crnt >= self.src.len()
@@ -116,6 +137,8 @@ impl<'a> Scanner<'a> {
             ':' => self.dbl_char(':', Colon, DblColon),
             '!' => self.mult_char(Bang, &[('=', BangEq), ('!', DblBang)]),
             '&' => self.dbl_char('&', And, DblAnd),
+            '^' => self.push(Caret, None),
+            '~' => self.push(Tilde, None),
             '+' => self.mult_char(Plus, &[('+', Incr), ('=', PlusEq)]),
             '-' => self.mult_char(Min, &[('>', Arrow), ('-', Decr), ('=', MinEq)]),
             '*' => self.mult_char(Mul, &[('=', MulEq), ('*', Sqr)]),
@@ -124,12 +147,15 @@ impl<'a> Scanner<'a> {
             '.' => {
                 if self.peek().is_numeric() {
                     self.numlit('0')
+                } else if self.peek() == '.' {
+                    self.advance();
+                    self.dbl_char('=', DblDot, DblDotEq)
                 } else {
-                    self.dbl_char('.', Dot, DblDot)
+                    self.push(Dot, None)
                 }
             }
-            '<' => self.dbl_char('=', Ls, LsOrEq),
-            '>' => self.dbl_char('=', Gr, GrOrEq),
+            '<' => self.mult_char(Ls, &[('=', LsOrEq), ('<', Shl)]),
+            '>' => self.mult_char(Gr, &[('=', GrOrEq), ('>', Shr)]),
             '\\' => self.mult_char(Esc, &[('{', LParse), ('}', RParse)]),
             // whitespaces and comments
             '/' => self.div(),
@@ -153,7 +179,7 @@ impl<'a> Scanner<'a> {
     /// function for handling two character tokens with multiple variations
     fn mult_char(&mut self, single: TokenType, variants: &[(char, TokenType)]) {
         let token_type =
-            if let Some(&(_, ref token)) = variants.iter().find(|&&(ch, _)| ch == self.peek()) {
+            if let Some((_, token)) = variants.iter().find(|&&(ch, _)| ch == self.peek()) {
                 self.advance();
                 token.clone()
             } else {
@@ -189,15 +215,18 @@ impl<'a> Scanner<'a> {
     /// ignores characters in comment lines
     /// `//`, `#`
     fn line_comment(&mut self) {
+        let (line, pos) = (self.line, self.pos);
         while self.peek() != '\n' && !self.is_eof() {
             self.advance();
         }
         self.pos = 1;
+        self.push_comment(line, pos);
     }
 
     /// ignores characters in comment blocks
     /// `/*`, `*/`
     fn block_comment(&mut self) {
+        let (line, pos) = (self.line, self.pos);
         while !self.is_eof() {
             if self.peek() == '*' && self.peek_next() == '/' {
                 self.advance();
@@ -206,10 +235,27 @@ impl<'a> Scanner<'a> {
             }
             if self.peek() == '\n' {
                 self.line += 1;
-                self.pos = 1;
             }
             self.advance();
         }
+        // tokens after the comment start where it ends
+        let text = &self.src[self.start..self.crnt];
+        self.pos = match text.rfind('\n') {
+            Some(i) => text[i + 1..].chars().count() + 1,
+            None => pos + text.chars().count(),
+        };
+        self.push_comment(line, pos);
+    }
+
+    /// stores comment text, so tools like the formatter can restore it
+    fn push_comment(&mut self, line: usize, pos: usize) {
+        let is_inline = self.tokens.last().is_some_and(|t| t.line == line);
+        self.comments.push(Comment {
+            text: self.src[self.start..self.crnt].trim_end().to_string(),
+            line,
+            pos,
+            is_inline,
+        });
     }
 
     /// handles 'c'haracters
@@ -219,7 +265,7 @@ impl<'a> Scanner<'a> {
         let mut unicode_escape = false;
         let mut unicode_buffer = String::new();
 
-        while self.peek() != '\'' && !self.is_eof() {
+        while (self.peek() != '\'' || in_escape) && self.peek() != '\n' && !self.is_eof() {
             if in_escape {
                 if unicode_escape {
                     match self.peek() {
@@ -253,6 +299,7 @@ impl<'a> Scanner<'a> {
                         't' => value.push('\t'),
                         '\\' => value.push('\\'),
                         '"' => value.push('"'),
+                        '\'' => value.push('\''),
                         'r' => value.push('\r'),
                         'u' => {
                             unicode_escape = true;
@@ -271,14 +318,16 @@ impl<'a> Scanner<'a> {
 
         if self.peek() != '\'' {
             self.err
-                .throw(E0x101, self.line, (self.pos - 1, self.pos), vec![]);
+                .report(E0x101, self.line, (self.pos - 1, self.pos), vec![]);
+            self.push(CharLit, Some(LiteralKind::Char { value: '\0' }));
             return;
         }
         self.advance();
 
-        if value.len() != 1 {
+        if value.chars().count() != 1 {
             self.err
-                .throw(E0x101, self.line, (self.pos - 1, self.pos), vec![]);
+                .report(E0x101, self.line, (self.pos - 1, self.pos), vec![]);
+            self.push(CharLit, Some(LiteralKind::Char { value: '\0' }));
             return;
         }
 
@@ -298,7 +347,7 @@ impl<'a> Scanner<'a> {
         let mut unicode_escape = false;
         let mut unicode_buffer = String::new();
 
-        while self.peek() != '"' && !self.is_eof() {
+        while (self.peek() != '"' || in_escape) && !self.is_eof() {
             if self.peek() == '\n' {
                 self.line += 1;
                 self.pos = 1;
@@ -353,7 +402,8 @@ impl<'a> Scanner<'a> {
 
         if self.is_eof() {
             self.err
-                .throw(E0x102, self.line, (self.pos - 1, self.pos), vec![]);
+                .report(E0x102, self.line, (self.pos - 1, self.pos), vec![]);
+            self.push(StrLit, Some(LiteralKind::String { value }));
             return;
         }
 
@@ -385,9 +435,9 @@ impl<'a> Scanner<'a> {
                 '0'..='9' | '_' | '.' => self.parse_numlit(10, Base::Decimal),
                 _ => self.push(
                     NumLit,
-                    Some(LiteralKind::Number {
+                    Some(LiteralKind::Int {
                         base: Base::Decimal,
-                        value: 0.0,
+                        value: 0,
                     }),
                 ),
             }
@@ -396,11 +446,13 @@ impl<'a> Scanner<'a> {
         }
     }
 
-    /// parses numbers in the number literal token
+    /// parses numbers in the number literal token,
+    /// decimals with a fractional part or an exponent are floats, others are ints
     fn parse_numlit(&mut self, radix: u32, base: Base) {
         if radix != 10 {
             self.advance(); // Skip prefix for non-decimal numbers
         }
+        let mut is_float = self.src[self.start..self.crnt].starts_with('.');
 
         // Parse the integer or fractional part
         while self.peek().is_digit(radix) || self.peek() == '_' {
@@ -408,15 +460,17 @@ impl<'a> Scanner<'a> {
         }
 
         // Check for fractional part if radix is 10
-        if radix == 10 && self.peek() == '.' && self.peek_next().is_digit(10) {
+        if radix == 10 && self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            is_float = true;
             self.advance(); // Skip '.'
-            while self.peek().is_digit(10) || self.peek() == '_' {
+            while self.peek().is_ascii_digit() || self.peek() == '_' {
                 self.advance();
             }
         }
 
         // Check for exponent part (e.g., 'e5', 'E-5')
         if radix == 10 && (self.peek() == 'e' || self.peek() == 'E') {
+            is_float = true;
             self.advance(); // Skip 'e' or 'E'
 
             // Optionally, handle the sign of the exponent
@@ -425,7 +479,7 @@ impl<'a> Scanner<'a> {
             }
 
             // Parse exponent digits
-            while self.peek().is_digit(10) || self.peek() == '_' {
+            while self.peek().is_ascii_digit() || self.peek() == '_' {
                 self.advance();
             }
         }
@@ -436,21 +490,36 @@ impl<'a> Scanner<'a> {
             .filter(|&c| c != '_')
             .collect();
 
+        if is_float {
+            let value = sub.parse::<f64>().unwrap_or(0.0);
+            self.push(NumLit, Some(LiteralKind::Float { value }));
+            return;
+        }
+
         // Parse the value based on the radix
-        let value = if radix == 10 {
-            sub.parse::<f32>().unwrap_or(0.0)
+        let digits = if radix == 10 {
+            &sub[..]
         } else {
-            if sub.len() > 2 {
-                i32::from_str_radix(&sub[2..], radix)
-                    .map(|v| v as f32)
-                    .unwrap_or(0.0)
-            } else {
-                0.0
+            &sub[2.min(sub.len())..]
+        };
+        let value = match i64::from_str_radix(digits, radix) {
+            Ok(value) => value,
+            // a prefix without digits
+            Err(_) if digits.is_empty() => 0,
+            Err(_) => {
+                let len = self.src[self.start..self.crnt].chars().count();
+                self.err.report(
+                    E0x108,
+                    self.line,
+                    (self.pos, self.pos + len),
+                    vec![sub.clone()],
+                );
+                0
             }
         };
 
         // Push the parsed number literal
-        self.push(NumLit, Some(LiteralKind::Number { base, value }));
+        self.push(NumLit, Some(LiteralKind::Int { base, value }));
     }
 
     /// just as name says, pushes tokens
@@ -54,6 +54,8 @@ impl Parser {
             Record => self.object(),
             // Vec<T>
             VecT => self.vec(),
+            // Map<K, V>
+            MapT => self.map(),
             // Tuple<(T, T)>
             Tuple => self.tuple(),
             // |i, i| i
@@ -61,9 +63,8 @@ impl Parser {
             // literal types
             StrLit | NumLit | CharLit | Null | TrueLit | FalseLit => self.literal(),
             // standard types
-            AnyIdent | BoolIdent | CharIdent | VoidIdent | ArrayIdent | NumIdent | StrIdent => {
-                self.builtin()
-            }
+            AnyIdent | BoolIdent | CharIdent | VoidIdent | ArrayIdent | NumIdent | IntIdent
+            | FloatIdent | StrIdent => self.builtin(),
             // for calling aliases
             Ident => self.ident(),
             c => Token {
@@ -92,7 +93,7 @@ impl Parser {
                 break;
             }
         }
-        self.consume(Gr);
+        self.close_type_args();
         let value = Some(LiteralKind::Type(Box::new(TypeKind::Record {
             fields: fields.clone(),
         })));
@@ -111,7 +112,8 @@ impl Parser {
 
     fn builtin(&mut self) -> Token {
         let token = self.consume_some(&[
-            AnyIdent, BoolIdent, CharIdent, Null, VoidIdent, ArrayIdent, NumIdent, StrIdent,
+            AnyIdent, BoolIdent, CharIdent, Null, VoidIdent, ArrayIdent, NumIdent, IntIdent,
+            FloatIdent, StrIdent,
         ]);
         let value = Some(LiteralKind::Type(Box::new(TypeKind::Var {
             name: token.clone(),
@@ -125,22 +127,73 @@ impl Parser {
         }
     }
 
+    /// parses type parameters of a declaration, `<T, U>`
+    pub fn type_params(&mut self) -> Vec<Token> {
+        let mut params = vec![];
+        if self.if_token_consume(Ls) {
+            while !self.if_token_consume(Gr) {
+                let param = self.consume(Ident);
+                params.push(param);
+                if !self.if_token_consume(Comma) {
+                    self.close_type_args();
+                    break;
+                }
+            }
+        }
+        params
+    }
+
+    /// consumes `>` closing the type arguments, `>>` of nested arguments
+    /// is split and its second half is left for the outer type
+    fn close_type_args(&mut self) {
+        let token = &mut self.tokens[self.crnt];
+        if token.token == Shr {
+            token.token = Gr;
+            token.lexeme = ">".to_string();
+            token.pos.0 += 1;
+        } else {
+            self.consume(Gr);
+        }
+    }
+
     fn ident(&mut self) -> Token {
         let token = self.consume(Ident);
-        let value = Some(LiteralKind::Type(Box::new(TypeKind::Var {
-            name: token.clone(),
-        })));
 
-        if self.is_uppercase(token.clone()) {
+        // name<T, T>
+        if self.if_token_consume(Ls) {
+            let mut args = vec![];
+            while !self.if_token_consume(Gr) {
+                args.push(TypeKind::Var {
+                    name: self.consume_type(),
+                });
+                if !self.if_token_consume(Comma) {
+                    self.close_type_args();
+                    break;
+                }
+            }
+            let instance = TypeKind::Instance {
+                name: token.clone(),
+                args,
+            };
             return Token {
-                token: Enum,
-                lexeme: token.lexeme,
-                value: None,
+                token: Ident,
+                lexeme: instance.to_string(),
+                value: Some(LiteralKind::Type(Box::new(instance))),
                 line: token.line,
                 pos: token.pos,
             };
         }
 
+        self.named_type(token)
+    }
+
+    /// annotation of the named type, enums, records and aliases are told apart
+    /// by their declarations once the name is resolved
+    pub fn named_type(&self, token: Token) -> Token {
+        let value = Some(LiteralKind::Type(Box::new(TypeKind::Var {
+            name: token.clone(),
+        })));
+
         Token {
             token: Ident,
             lexeme: token.lexeme,
@@ -196,7 +249,7 @@ impl Parser {
         self.consume(VecT);
         self.consume(Ls);
         let typ = self.consume_type();
-        self.consume(Gr);
+        self.close_type_args();
         Token {
             token: VecLit,
             lexeme: typ.clone().lexeme,
@@ -208,6 +261,26 @@ impl Parser {
         }
     }
 
+    fn map(&mut self) -> Token {
+        // Map<K, V>
+        self.consume(MapT);
+        self.consume(Ls);
+        let key = self.consume_type();
+        self.consume(Comma);
+        let value = self.consume_type();
+        self.close_type_args();
+        Token {
+            token: MapLit,
+            lexeme: format!("Map<{}, {}>", key.lexeme, value.lexeme),
+            pos: self.peek().pos,
+            value: Some(LiteralKind::Type(Box::new(TypeKind::Map {
+                key: Box::new(TypeKind::Var { name: key }),
+                value: Box::new(TypeKind::Var { name: value }),
+            }))),
+            line: self.peek().line,
+        }
+    }
+
     fn tuple(&mut self) -> Token {
         // Tuple<(T, T)>
         self.consume(Tuple);
@@ -224,7 +297,7 @@ impl Parser {
             }
         }
 
-        self.consume(Gr);
+        self.close_type_args();
         Token {
             token: TupleLit,
             lexeme: "tuple".to_string(),
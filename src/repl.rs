@@ -0,0 +1,215 @@
+// Absurd REPL, evaluates the input line by line in a persistent environment
+use crate::{
+    ast::{LiteralType, Statement},
+    bundler::{check, parser_from},
+    checker::Checker,
+    emit,
+    errors::{interactive, log, Error},
+    interpreter::{env::Env, expr::Expression, Interpreter},
+    resolver::Resolver,
+    Config, VERSION,
+};
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    rc::Rc,
+};
+
+const HELP: &str = "\
+:type expr   evaluate the expression and print the type of its value
+:env         print the environment
+:load file   evaluate the file in the session
+:reset       clear the environment
+:help        print this message
+:quit        exit the session";
+
+pub struct Repl {
+    config: Config,
+    env: Rc<RefCell<Env>>,
+    /// declarations of the evaluated inputs, only new inputs are checked against them
+    checker: Checker,
+    /// evaluated inputs, errors point at the lines of the session
+    session: String,
+    /// number of the session lines, the next input starts after them
+    lines: usize,
+}
+
+impl Repl {
+    pub fn new(config: Config) -> Self {
+        let int = Interpreter::new(config.clone(), Error::new(""));
+        Repl {
+            config,
+            env: int.env,
+            checker: Checker::new(Error::new("")),
+            session: String::new(),
+            lines: 0,
+        }
+    }
+
+    /// reads the standard input until its end or `:quit`,
+    /// prompts are printed only if the input is a terminal
+    pub fn run(&mut self) {
+        let stdin = io::stdin();
+        let is_tty = stdin.is_terminal();
+        if is_tty {
+            println!("Absurd {} (type :help for commands)", VERSION);
+        }
+        let mut buffer = String::new();
+        loop {
+            if is_tty {
+                print!("{}", if buffer.is_empty() { "> " } else { "... " });
+                let _ = io::stdout().flush();
+            }
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if buffer.is_empty() && line.trim_start().starts_with(':') {
+                if !self.command(line.trim()) {
+                    break;
+                }
+                continue;
+            }
+            buffer.push_str(&line);
+            // keep reading while braces are still open
+            if is_open(&buffer) {
+                continue;
+            }
+            let input = std::mem::take(&mut buffer);
+            if !input.trim().is_empty() {
+                self.eval(&input);
+            }
+        }
+    }
+
+    /// runs the REPL command, returns false if the session should end
+    fn command(&mut self, line: &str) -> bool {
+        let (name, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = arg.trim();
+        match name {
+            ":type" => {
+                if let Some(value) = self.value(arg) {
+                    println!("{}", value.type_name());
+                }
+            }
+            ":env" => print!("{}", emit::env(&self.env.borrow())),
+            ":load" => match fs::read_to_string(arg) {
+                Ok(contents) => self.eval(&contents),
+                Err(_) => {
+                    log("repl error: failed to read the target:");
+                    eprintln!("{}", arg);
+                }
+            },
+            ":reset" => *self = Repl::new(self.config.clone()),
+            ":help" => println!("{}", HELP),
+            ":quit" => return false,
+            _ => {
+                log("repl error: unknown command:");
+                eprintln!("{}", name);
+            }
+        }
+        true
+    }
+
+    /// parses, resolves and checks only the input, its lines follow the session,
+    /// returns the input, its statements and the checker knowing its declarations
+    fn parse(&self, input: &str) -> Option<(String, Vec<Statement>, Checker)> {
+        let input = format!("{}\n", terminated(input));
+        let err = Error::new(&format!("{}{}", self.session, input));
+        // declarations of the failed input are dropped with the copy
+        let mut checker = self.checker.clone();
+        checker.set_err(err.clone());
+        interactive(|| {
+            let stmts = parser_from(&input, self.lines + 1, err.clone());
+            check(&err);
+            let locals = Resolver::new(err.clone()).resolve(&stmts, &self.env);
+            check(&err);
+            checker.check(&stmts);
+            check(&err);
+            self.env.borrow_mut().set_err(err.clone());
+            self.env.borrow_mut().resolve(locals);
+            stmts
+        })
+        .map(|stmts| (input, stmts, checker))
+    }
+
+    /// executes statements of the input and prints values of expressions
+    fn eval(&mut self, input: &str) {
+        let Some((input, stmts, checker)) = self.parse(input) else {
+            return;
+        };
+        self.lines += input.lines().count();
+        self.session.push_str(&input);
+        self.checker = checker;
+        for stmt in &stmts {
+            // statement stays in the session even if it fails, its side effects stay
+            if interactive(|| self.exec(stmt)).is_none() {
+                return;
+            }
+        }
+    }
+
+    fn exec(&self, stmt: &Statement) {
+        match stmt {
+            Statement::Expression { expr } if !matches!(expr, Expression::Assign { .. }) => {
+                match expr.eval(Rc::clone(&self.env)) {
+                    LiteralType::Null | LiteralType::Void => {}
+                    value => println!("{}", value),
+                }
+            }
+            _ => {
+                let mut int = Interpreter::new_with_env(Rc::clone(&self.env), false, "", None, 0);
+                int.interpret(vec![stmt], 0);
+            }
+        }
+    }
+
+    /// evaluates the expression without adding it to the session
+    fn value(&self, expr: &str) -> Option<LiteralType> {
+        let (_, stmts, _) = self.parse(expr)?;
+        match stmts.as_slice() {
+            [Statement::Expression { expr }] => interactive(|| expr.eval(Rc::clone(&self.env))),
+            _ => {
+                log("repl error: expected an expression");
+                None
+            }
+        }
+    }
+}
+
+/// adds the semicolon after the trailing expression, `1 + 2` is a valid input
+pub fn terminated(input: &str) -> String {
+    let trimmed = input.trim_end();
+    if trimmed.ends_with(';') || trimmed.ends_with('}') {
+        trimmed.to_string()
+    } else {
+        format!("{};", trimmed)
+    }
+}
+
+/// checks if the input has unclosed braces, brackets or parentheses
+fn is_open(input: &str) -> bool {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '/') if chars.clone().next() == Some('/') => {
+                // skip the comment until the end of the line
+                chars.find(|c| *c == '\n');
+            }
+            (None, '{' | '[' | '(') => depth += 1,
+            (None, '}' | ']' | ')') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth > 0
+}
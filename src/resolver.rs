@@ -1,18 +1,75 @@
 // Absurd resolver, it resolves statements and returns locals
-use crate::ast::{Statement, Token};
-use crate::errors::{Error, ErrorCode::*};
-use crate::interpreter::env::Env;
+use crate::ast::{CallType, LiteralType, MatchCase, Statement, Token, TokenType};
+use crate::errors::{Error, ErrorCode, ErrorCode::*};
+use crate::interpreter::env::{Env, Slot};
 use crate::interpreter::expr::Expression;
+use crate::interpreter::impl_func;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeclKind {
+    Var {
+        is_mut: bool,
+        is_pub: bool,
+    },
+    Func,
+    /// `use name from src`, `name` is the name in the source, not its alias
+    Import {
+        src: String,
+        name: String,
+    },
+    /// function parameters, loop iterators and names of patterns
+    Param,
+}
+
+/// name declared by the source
+#[derive(Debug, Clone)]
+pub struct Decl {
+    pub name: Token,
+    pub kind: DeclKind,
+    /// number of scopes around the declaration, 0 for the outermost names
+    pub depth: usize,
+    /// declaration of the same name the name hides
+    pub shadows: Option<usize>,
+}
+
+/// declarations of the source and their uses, the linter reports by them
+#[derive(Debug, Clone, Default)]
+pub struct Bindings {
+    pub decls: Vec<Decl>,
+    /// declarations read by `Var` expressions and by names interpolated into string
+    /// values, by ids of the expressions
+    pub uses: HashMap<usize, Vec<usize>>,
+    /// declarations assigned by `Assign` expressions, by ids of the expressions
+    pub assigns: HashMap<usize, usize>,
+    /// names used where no declaration is seen, the outermost ones may be declared later
+    pub unresolved: HashSet<String>,
+}
+
+/// name of the scope
+#[derive(Debug, Clone)]
+struct Local {
+    is_defined: bool,
+    /// imported names have no slot, they are looked up by name
+    slot: Option<usize>,
+    decl: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct Resolver {
-    locals: HashMap<usize, usize>,
-    scopes: Vec<HashMap<String, bool>>,
+    locals: HashMap<usize, Slot>,
+    /// names of the scopes, scopes follow environments of the interpreter,
+    /// the outermost names aren't resolved
+    scopes: Vec<HashMap<String, Local>>,
+    /// declarations of the outermost names
+    globals: HashMap<String, usize>,
+    bindings: Bindings,
     is_crnt_fnc: bool,
     is_crnt_loop: bool,
+    /// labels of the enclosing loops
+    labels: Vec<String>,
     err: Error,
 }
 
@@ -21,22 +78,26 @@ impl Resolver {
         Resolver {
             locals: HashMap::new(),
             scopes: Vec::new(),
+            globals: HashMap::new(),
+            bindings: Bindings::default(),
             is_crnt_fnc: false,
             is_crnt_loop: false,
+            labels: Vec::new(),
             err,
         }
     }
 
     /// entry method
-    pub fn resolve(
-        &mut self,
-        stmts: &[Statement],
-        env: &Rc<RefCell<Env>>,
-    ) -> HashMap<usize, usize> {
+    pub fn resolve(&mut self, stmts: &[Statement], env: &Rc<RefCell<Env>>) -> HashMap<usize, Slot> {
         stmts.iter().for_each(|stmt| self.resolve_stmt(stmt, env));
         self.locals.clone()
     }
 
+    /// declarations and their uses found by `resolve`
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
     /// statement resolver
     fn resolve_stmt(&mut self, stmt: &Statement, env: &Rc<RefCell<Env>>) {
         match stmt {
@@ -45,27 +106,61 @@ impl Resolver {
                 index,
                 body,
                 expr,
-            } => self.fors(iterator, index, body, expr, env),
+                label,
+            } => self.fors(iterator, index, body, expr, label, env),
             Statement::If {
                 body,
                 else_branch,
                 cond,
             } => self.ifs(cond, body, else_branch, env),
             Statement::Block { stmts } => self.block(stmts, env),
-            Statement::Break {} => self.breaks(),
+            Statement::Break { keyword, label } => self.jumps(E0x302, keyword, label),
+            Statement::Continue { keyword, label } => self.jumps(E0x307, keyword, label),
             Statement::Expression { expr } => self.expr(expr, env),
-            Statement::Func { body, params, .. } => {
+            Statement::Func {
+                name, body, params, ..
+            } => {
+                // the name is defined before the body, so the function can call itself
+                self.declare(name, DeclKind::Func);
+                self.define(name);
                 self.func(*body.clone(), params.as_slice(), env)
             }
-            Statement::Match {
-                cond,
-                cases,
-                def_case,
-            } => self.matchs(cond, def_case, cases, env),
-            Statement::Return { expr } => self.returns(expr, env),
-            Statement::Use { names, .. } => self.uses(names),
-            Statement::Var { names, value, .. } => self.var(names, value, env),
-            Statement::While { body, cond } => self.whiles(body, cond, env),
+            // functions of the block are defined as `type::func`
+            Statement::Impl { name, funcs } => funcs
+                .iter()
+                .for_each(|f| self.resolve_stmt(&impl_func(name, f).0, env)),
+            // arms of the statement run their blocks in the scope of the pattern
+            Statement::Match { cond, cases } => {
+                self.matchs(cond, cases, env, |resolver, body| match body {
+                    Statement::Block { stmts } => resolver.resolve_many(stmts, env),
+                    body => resolver.resolve_stmt(body, env),
+                })
+            }
+            Statement::Return { keyword, expr } => self.returns(keyword, expr, env),
+            Statement::Use { src, names, .. } => self.uses(src, names),
+            Statement::Var {
+                names,
+                value,
+                is_mut,
+                is_pub,
+                ..
+            } => {
+                let kind = DeclKind::Var {
+                    is_mut: *is_mut,
+                    is_pub: *is_pub,
+                };
+                self.var(names, value, kind, env)
+            }
+            // defaults are evaluated where the record is built, so they aren't given slots
+            Statement::Record { fields, .. } => {
+                let locals = self.locals.clone();
+                fields
+                    .iter()
+                    .filter_map(|field| field.default_value.as_ref())
+                    .for_each(|value| self.expr(value, env));
+                self.locals = locals;
+            }
+            Statement::While { body, cond, label } => self.whiles(body, cond, label, env),
             _ => {}
         }
     }
@@ -76,38 +171,59 @@ impl Resolver {
         index: &Option<Token>,
         body: &Statement,
         expr: &Expression,
+        label: &Option<Token>,
         env: &Rc<RefCell<Env>>,
     ) {
         self.expr(expr, env);
         let encl_loop = self.is_crnt_loop;
         self.is_crnt_loop = true;
+        self.labels.extend(label.iter().map(|l| l.lexeme.clone()));
         self.scope_start();
-        self.declare(iterator);
+        self.declare(iterator, DeclKind::Param);
         self.define(iterator);
         if let Some(i) = index {
-            self.declare(i);
+            self.declare(i, DeclKind::Param);
             self.define(i);
         }
         self.resolve_stmt(body, env);
         self.scope_end();
+        if label.is_some() {
+            self.labels.pop();
+        }
         self.is_crnt_loop = encl_loop;
     }
 
-    fn uses(&mut self, names: &Vec<(Token, Option<Token>)>) {
+    fn uses(&mut self, src: &str, names: &[(Token, Option<Token>)]) {
         for (old, new) in names {
-            if let Some(new_name) = new {
-                self.declare(new_name);
-                self.define(new_name);
-            } else {
-                self.declare(old);
-                self.define(old);
+            let name = new.as_ref().unwrap_or(old);
+            let kind = DeclKind::Import {
+                src: src.trim_matches('"').to_string(),
+                name: old.lexeme.clone(),
+            };
+            let decl = self.record(name, kind);
+            if let Some(scope) = self.scopes.last_mut() {
+                let local = Local {
+                    is_defined: true,
+                    slot: None,
+                    decl,
+                };
+                if scope.insert(name.lexeme.clone(), local).is_some() {
+                    self.err
+                        .report(E0x305, name.line, name.pos, vec![name.lexeme.clone()]);
+                }
             }
         }
     }
 
-    fn var(&mut self, names: &Vec<Token>, value: &Option<Expression>, env: &Rc<RefCell<Env>>) {
+    fn var(
+        &mut self,
+        names: &Vec<Token>,
+        value: &Option<Expression>,
+        kind: DeclKind,
+        env: &Rc<RefCell<Env>>,
+    ) {
         for name in names {
-            self.declare(name);
+            self.declare(name, kind.clone());
             if let Some(value) = value {
                 self.expr(value, env);
             }
@@ -115,35 +231,54 @@ impl Resolver {
         }
     }
 
-    fn whiles(&mut self, body: &Statement, cond: &Expression, env: &Rc<RefCell<Env>>) {
+    fn whiles(
+        &mut self,
+        body: &Statement,
+        cond: &Expression,
+        label: &Option<Token>,
+        env: &Rc<RefCell<Env>>,
+    ) {
         let encl_loop = self.is_crnt_loop;
         self.expr(cond, env);
         self.is_crnt_loop = true;
-        self.scope_start();
-        self.resolve_stmt(body, env);
-        self.scope_end();
+        self.labels.extend(label.iter().map(|l| l.lexeme.clone()));
+        self.branch(body, env);
+        if label.is_some() {
+            self.labels.pop();
+        }
         self.is_crnt_loop = encl_loop;
     }
 
-    fn breaks(&mut self) {
+    /// `break` and `continue` must be in a loop, their labels must name an enclosing loop
+    fn jumps(&mut self, code: ErrorCode, keyword: &Token, label: &Option<Token>) {
         if !self.is_crnt_loop {
-            self.err.throw(E0x302, 0, (0, 0), vec![]);
+            let at = label.as_ref().unwrap_or(keyword);
+            self.err.report(code, at.line, at.pos, vec![]);
+        } else if let Some(l) = label {
+            if !self.labels.contains(&l.lexeme) {
+                self.err
+                    .report(E0x308, l.line, l.pos, vec![l.lexeme.clone()]);
+            }
         }
     }
 
     fn func(&mut self, body: Statement, params: &[(Token, Token)], env: &Rc<RefCell<Env>>) {
         let encl_func = self.is_crnt_fnc;
         self.is_crnt_fnc = true;
+        // loops don't enclose function bodies
+        let encl_loop = self.is_crnt_loop;
+        self.is_crnt_loop = false;
+        let encl_labels = std::mem::take(&mut self.labels);
         self.scope_start();
         params.iter().for_each(|(name, _)| {
-            self.declare(name);
+            self.declare(name, DeclKind::Param);
             self.define(name);
         });
         match body {
             Statement::Block { stmts } => {
                 self.resolve_many(stmts.as_slice(), env);
                 stmts.iter().for_each(|stmt| {
-                    if let Statement::Return { expr } = stmt {
+                    if let Statement::Return { expr, .. } = stmt {
                         self.expr(expr, env);
                     }
                 });
@@ -151,54 +286,70 @@ impl Resolver {
             _ => self.resolve_stmt(&body, env),
         }
         self.scope_end();
+        self.labels = encl_labels;
+        self.is_crnt_loop = encl_loop;
         self.is_crnt_fnc = encl_func;
     }
 
-    fn matchs(
+    /// `body` resolves bodies of the arms
+    fn matchs<T>(
         &mut self,
         cond: &Expression,
-        def_case: &Statement,
-        cases: &Vec<(Expression, Statement)>,
+        cases: &[MatchCase<T>],
         env: &Rc<RefCell<Env>>,
+        mut body: impl FnMut(&mut Self, &T),
     ) {
         self.expr(cond, env);
-        cases.iter().for_each(|(case, body)| {
+        cases.iter().for_each(|case| {
             self.scope_start();
-            self.expr(case, env);
-            self.resolve_stmt(body, env);
+            for name in case.pattern.names() {
+                self.declare(name, DeclKind::Param);
+                self.define(name);
+            }
+            if let Some(guard) = &case.guard {
+                self.expr(guard, env);
+            }
+            body(self, &case.body);
             self.scope_end();
         });
-
-        self.resolve_stmt(def_case, env);
     }
 
-    fn returns(&mut self, expr: &Expression, env: &Rc<RefCell<Env>>) {
+    fn returns(&mut self, keyword: &Token, expr: &Expression, env: &Rc<RefCell<Env>>) {
         if self.is_crnt_fnc {
             self.expr(expr, env);
         } else {
-            self.err.throw(E0x303, 0, (0, 0), vec![]);
+            self.err.report(E0x303, keyword.line, keyword.pos, vec![]);
         }
     }
 
     fn ifs(
         &mut self,
         cond: &Expression,
-        body: &Box<Statement>,
+        body: &Statement,
         else_branch: &Option<Box<Statement>>,
         env: &Rc<RefCell<Env>>,
     ) {
         self.expr(cond, env);
-        self.scope_start();
-        self.resolve_stmt(body, env);
-        self.scope_end();
+        self.branch(body, env);
         if let Some(branch) = else_branch {
-            self.scope_start();
-            self.resolve_stmt(branch, env);
-            self.scope_end();
+            self.branch(branch, env);
+        }
+    }
+
+    /// bodies of branches and loops have their own scopes, so names defined by a body
+    /// that doesn't run don't shift slots of the next names
+    fn branch(&mut self, body: &Statement, env: &Rc<RefCell<Env>>) {
+        match body {
+            Statement::Block { .. } => self.resolve_stmt(body, env),
+            body => {
+                self.scope_start();
+                self.resolve_stmt(body, env);
+                self.scope_end();
+            }
         }
     }
 
-    fn block(&mut self, stmts: &Vec<Statement>, env: &Rc<RefCell<Env>>) {
+    fn block(&mut self, stmts: &[Statement], env: &Rc<RefCell<Env>>) {
         self.scope_start();
         self.resolve_many(stmts, env);
         self.scope_end();
@@ -226,17 +377,46 @@ impl Resolver {
                     self.expr(&branch, env);
                 }
             }
-            Expression::Assign { value, .. } => self.expr(value, env),
+            Expression::Match { cond, cases, .. } => {
+                self.matchs(cond, cases, env, |resolver, body| resolver.expr(body, env))
+            }
+            Expression::Assign {
+                name, value, id, ..
+            } => {
+                self.expr(value, env);
+                match self.lookup(&name.lexeme) {
+                    Some(decl) => {
+                        self.bindings.assigns.insert(*id, decl);
+                    }
+                    None => {
+                        self.bindings.unresolved.insert(name.lexeme.clone());
+                    }
+                }
+                self.resolve_local(name, *id);
+            }
             Expression::Vec { items, .. } => {
                 items.iter().for_each(|item| self.expr(item, env));
             }
             Expression::Tuple { items, .. } => {
                 items.iter().for_each(|item| self.expr(item, env));
             }
+            Expression::Map { items, .. } => {
+                items.iter().for_each(|(key, value)| {
+                    self.expr(key, env);
+                    self.expr(value, env);
+                });
+            }
             Expression::Var { .. } => self.varexpr(expr),
-            Expression::Call { name, args, .. } => {
+            Expression::Call {
+                name,
+                args,
+                call_type,
+                ..
+            } => {
                 self.expr(name.as_ref(), env);
-                args.iter().for_each(|arg| self.expr(arg, env));
+                // item of `Enum::Item` isn't a variable
+                let skip = usize::from(*call_type == CallType::Enum);
+                args.iter().skip(skip).for_each(|arg| self.expr(arg, env));
             }
             Expression::Func { body, params, .. } => self.callback(body, params, env),
             Expression::Await { expr, .. } => self.expr(expr, env),
@@ -246,78 +426,178 @@ impl Resolver {
                 self.expr(right, env);
             }
             Expression::Grouping { expression, .. } => self.expr(expression, env),
+            Expression::Range { lhs, rhs, step, .. } => {
+                self.expr(lhs, env);
+                self.expr(rhs, env);
+                if let Some(step) = step {
+                    self.expr(step, env);
+                }
+            }
+            Expression::Value {
+                value: LiteralType::String(s),
+                id,
+                ..
+            } => self.interpolated(s, *id),
             _ => {}
         }
     }
 
+    /// names used inside of string interpolation `"{name}"`, they are looked up by name
+    fn interpolated(&mut self, s: &str, id: usize) {
+        let mut decls = vec![];
+        let mut rest = s;
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let names = rest[start + 1..start + end]
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .filter(|n| !n.is_empty());
+            decls.extend(names.filter_map(|name| self.lookup(name)));
+            rest = &rest[start + end + 1..];
+        }
+        if !decls.is_empty() {
+            self.bindings.uses.insert(id, decls);
+        }
+    }
+
     fn callback(&mut self, body: &Statement, params: &[(Token, Token)], env: &Rc<RefCell<Env>>) {
         let encl_func = self.is_crnt_fnc;
         self.is_crnt_fnc = true;
+        // loops don't enclose function bodies
+        let encl_loop = self.is_crnt_loop;
+        self.is_crnt_loop = false;
+        let encl_labels = std::mem::take(&mut self.labels);
         self.scope_start();
         params.iter().for_each(|(name, _)| {
-            self.declare(name);
+            self.declare(name, DeclKind::Param);
             self.define(name);
         });
         match body {
             Statement::Block { stmts } => {
                 self.resolve_many(stmts.as_slice(), env);
                 stmts.iter().for_each(|stmt| {
-                    if let Statement::Return { expr } = stmt {
+                    if let Statement::Return { expr, .. } = stmt {
                         self.expr(expr, env);
                     }
                 });
             }
-            _ => self.resolve_stmt(&body, env),
+            _ => self.resolve_stmt(body, env),
         }
 
         self.scope_end();
+        self.labels = encl_labels;
+        self.is_crnt_loop = encl_loop;
         self.is_crnt_fnc = encl_func;
     }
 
     fn varexpr(&mut self, expr: &Expression) {
-        if let Expression::Var { name, .. } = expr {
-            if let Some(false) = self.scopes.last().and_then(|scope| scope.get(&name.lexeme)) {
-                self.err.throw(
+        if let Expression::Var { name, id } = expr {
+            let local = self.scopes.last().and_then(|scope| scope.get(&name.lexeme));
+            if local.is_some_and(|local| !local.is_defined) {
+                self.err.report(
                     E0x304,
                     name.line,
                     name.pos,
                     vec!["a local variable".to_string()],
                 );
             }
+            match self.lookup(&name.lexeme) {
+                Some(decl) => {
+                    self.bindings.uses.insert(*id, vec![decl]);
+                }
+                None => {
+                    self.bindings.unresolved.insert(name.lexeme.clone());
+                }
+            }
+            self.resolve_local(name, *id);
         } else if let Expression::Call { name, .. } = expr {
             if let Expression::Var { name, .. } = name.as_ref() {
                 self.resolve_local(name, expr.id());
             } else {
-                self.err
-                    .throw(E0x304, 0, (0, 0), vec!["a variable".to_string()]);
+                self.not_variable(name);
             }
         } else {
-            self.err
-                .throw(E0x304, 0, (0, 0), vec!["a variable".to_string()]);
+            self.not_variable(expr);
         }
     }
 
-    fn declare(&mut self, name: &Token) {
+    fn not_variable(&mut self, expr: &Expression) {
+        let at = expr.token().cloned().unwrap_or(Token::null());
+        self.err
+            .report(E0x304, at.line, at.pos, vec!["a variable".to_string()]);
+    }
+
+    /// names take the next slot of the scope, in the order the interpreter defines them
+    fn declare(&mut self, name: &Token, kind: DeclKind) {
+        let decl = self.record(name, kind);
         if let Some(scope) = self.scopes.last_mut() {
-            if scope.contains_key(&name.lexeme) {
-                self.err
-                    .throw(E0x305, name.line, name.pos, vec![name.lexeme.clone()]);
+            let slot = match scope.get(&name.lexeme) {
+                Some(Local {
+                    slot: Some(slot), ..
+                }) => {
+                    self.err
+                        .report(E0x305, name.line, name.pos, vec![name.lexeme.clone()]);
+                    *slot
+                }
+                _ => scope.values().filter(|local| local.slot.is_some()).count(),
+            };
+            let local = Local {
+                is_defined: false,
+                slot: Some(slot),
+                decl,
+            };
+            scope.insert(name.lexeme.clone(), local);
+        }
+    }
+
+    /// adds the declaration to the bindings, the outermost names are kept by name
+    fn record(&mut self, name: &Token, kind: DeclKind) -> usize {
+        let decl = self.bindings.decls.len();
+        // skipped items of destructuring aren't declarations
+        if name.token != TokenType::Null {
+            self.bindings.decls.push(Decl {
+                name: name.clone(),
+                kind,
+                depth: self.scopes.len(),
+                shadows: self.lookup(&name.lexeme),
+            });
+            if self.scopes.is_empty() {
+                self.globals.insert(name.lexeme.clone(), decl);
             }
-            scope.insert(name.lexeme.clone(), false);
         }
+        decl
+    }
+
+    /// declaration the name refers to, from the innermost scope outwards
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).map(|local| local.decl))
+            .or_else(|| self.globals.get(name).copied())
     }
 
     fn define(&mut self, name: &Token) {
-        if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name.lexeme.clone(), true);
+        if let Some(local) = self
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.get_mut(&name.lexeme))
+        {
+            local.is_defined = true;
         }
     }
 
     fn resolve_local(&mut self, name: &Token, id: usize) {
-        for (i, scope) in self.scopes.iter().rev().enumerate() {
-            if scope.contains_key(&name.lexeme) {
-                self.locals.insert(id, i);
-                return;
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            match scope.get(&name.lexeme).map(|local| local.slot) {
+                Some(Some(index)) => {
+                    self.locals.insert(id, Slot { depth, index });
+                    return;
+                }
+                // imported names are looked up by name from the innermost scope
+                Some(None) => return,
+                None => {}
             }
         }
     }
@@ -332,7 +612,7 @@ impl Resolver {
 
     fn scope_end(&mut self) {
         if self.scopes.pop().is_none() {
-            self.err.throw(E0x306, 0, (0, 0), vec![]);
+            self.err.report(E0x306, 0, (0, 0), vec![]);
         }
     }
 }
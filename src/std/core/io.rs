@@ -1,10 +1,10 @@
 use crate::{
     ast::{LiteralType, Token, Wrapper},
-    errors::raw,
+    errors::{quit, raw},
     std::{func, StdFunc},
 };
 use coloredpp::Colorize;
-use std::{process::exit, rc::Rc};
+use std::rc::Rc;
 
 impl StdFunc {
     pub fn load_core_io(&mut self) {
@@ -29,15 +29,13 @@ impl StdFunc {
             name.as_str(),
             1,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    println!("{}", args[0].clone().unwrap().to_string());
-                    LiteralType::Void
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                println!("{}", args[0].clone().unwrap());
+                LiteralType::Void
+            }))),
         );
     }
 
@@ -51,15 +49,13 @@ impl StdFunc {
             name.as_str(),
             1,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    println!("{}", args[0].clone().unwrap().to_string().red());
-                    LiteralType::Void
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                println!("{}", args[0].clone().unwrap().to_string().red());
+                LiteralType::Void
+            }))),
         );
     }
 
@@ -72,15 +68,13 @@ impl StdFunc {
             name.as_str(),
             1,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    println!("{}", args[0].clone().unwrap().to_string().yellow());
-                    LiteralType::Void
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                println!("{}", args[0].clone().unwrap().to_string().yellow());
+                LiteralType::Void
+            }))),
         );
     }
 
@@ -93,15 +87,12 @@ impl StdFunc {
             name.as_str(),
             1,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    println!("{}", args[0].clone().unwrap().to_string().red());
-                    exit(0);
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                raw(&args[0].clone().unwrap().to_string())
+            }))),
         );
     }
 
@@ -114,19 +105,17 @@ impl StdFunc {
             name.as_str(),
             1,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() > 0 {
-                        if args[0].is_none() {
-                            raw("expected an argument");
-                        }
-                        if let LiteralType::Number(val) = &args[0].clone().unwrap() {
-                            exit(*val as i32);
-                        }
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if !args.is_empty() {
+                    if args[0].is_none() {
+                        raw("expected an argument");
+                    }
+                    if let LiteralType::Int(val) = &args[0].clone().unwrap() {
+                        quit(*val as i32);
                     }
-                    exit(0);
-                }),
-            }),
+                }
+                quit(0);
+            }))),
         );
     }
 
@@ -139,13 +128,11 @@ impl StdFunc {
             name.as_str(),
             0,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|_args: &[Option<LiteralType>]| {
-                    let mut input = String::new();
-                    std::io::stdin().read_line(&mut input).unwrap();
-                    LiteralType::String(input)
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|_args: &[Option<LiteralType>]| {
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input).unwrap();
+                LiteralType::String(Rc::new(input))
+            }))),
         );
     }
 
@@ -158,14 +145,16 @@ impl StdFunc {
             name.as_str(),
             0,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|_args: &[Option<LiteralType>]| {
-                    let mut input = String::new();
-                    std::io::stdin().read_line(&mut input).unwrap();
-                    let input = input.trim().parse::<f32>().unwrap();
-                    LiteralType::Number(input)
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|_args: &[Option<LiteralType>]| {
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input).unwrap();
+                // whole numbers are read as ints
+                let input = input.trim();
+                match input.parse::<i64>() {
+                    Ok(n) => LiteralType::Int(n),
+                    Err(_) => LiteralType::Float(input.parse::<f64>().unwrap()),
+                }
+            }))),
         );
     }
 
@@ -178,14 +167,12 @@ impl StdFunc {
             name.as_str(),
             0,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|_args: &[Option<LiteralType>]| {
-                    let mut input = String::new();
-                    std::io::stdin().read_line(&mut input).unwrap();
-                    let input = input.trim().parse::<bool>().unwrap();
-                    LiteralType::Boolean(input)
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|_args: &[Option<LiteralType>]| {
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input).unwrap();
+                let input = input.trim().parse::<bool>().unwrap();
+                LiteralType::Boolean(input)
+            }))),
         );
     }
 
@@ -198,14 +185,12 @@ impl StdFunc {
             name.as_str(),
             0,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|_args: &[Option<LiteralType>]| {
-                    let mut input = String::new();
-                    std::io::stdin().read_line(&mut input).unwrap();
-                    let input = input.trim().chars().next().unwrap();
-                    LiteralType::Char(input)
-                }),
-            }),
+            Rc::new(Wrapper(Box::new(|_args: &[Option<LiteralType>]| {
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input).unwrap();
+                let input = input.trim().chars().next().unwrap();
+                LiteralType::Char(input)
+            }))),
         );
     }
 }
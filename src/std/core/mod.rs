@@ -1,2 +1,3 @@
 pub mod io;
+pub mod task;
 pub mod test;
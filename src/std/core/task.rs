@@ -0,0 +1,81 @@
+use crate::{
+    ast::{LiteralType, Token, Wrapper},
+    errors::raw,
+    interpreter::task,
+    std::{func, StdFunc},
+};
+use std::rc::Rc;
+
+impl StdFunc {
+    pub fn load_core_task(&mut self) {
+        self.load_spawn(None);
+        self.load_join_all(None);
+        self.load_sleep(None);
+    }
+
+    /// spawn(future) - runs the future in the background, returns it to be awaited later
+    pub fn load_spawn(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "spawn".to_string(),
+        };
+        func(
+            name.as_str(),
+            1,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                match args[0].clone() {
+                    Some(future @ LiteralType::Future(_)) => task::spawn(future),
+                    _ => raw("spawn() expects a future"),
+                }
+            }))),
+        );
+    }
+
+    /// join_all(futures) - future of the vector of their values, futures run together
+    pub fn load_join_all(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "join_all".to_string(),
+        };
+        func(
+            name.as_str(),
+            1,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                match args[0].clone() {
+                    Some(LiteralType::Vec(futures)) => task::join_all(futures.to_vec()),
+                    _ => raw("join_all() expects a vector of futures"),
+                }
+            }))),
+        );
+    }
+
+    /// sleep(ms) - future completing after the milliseconds, other tasks run meanwhile
+    pub fn load_sleep(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "sleep".to_string(),
+        };
+        func(
+            name.as_str(),
+            1,
+            &mut self.env,
+            Rc::new(Wrapper(Box::new(|args: &[Option<LiteralType>]| {
+                if args.len() != 1 {
+                    raw("expected an argument");
+                }
+                match args[0].clone() {
+                    Some(LiteralType::Int(ms)) if ms >= 0 => task::sleep(ms as u64),
+                    _ => raw("sleep() expects a non-negative int of milliseconds"),
+                }
+            }))),
+        );
+    }
+}
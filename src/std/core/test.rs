@@ -26,33 +26,31 @@ impl StdFunc {
                 name.as_str(),
                 1,
                 &mut env,
-                Rc::new(Wrapper {
-                    0: Box::new(move |args: &[Option<LiteralType>]| {
-                        let test_instance = Rc::clone(&test_instance);
-                        let test_instance = test_instance.borrow_mut();
-                        if args.len() != 2 {
-                            raw("expected 2 arguments");
-                        }
-                        if args[0].clone().unwrap_or(LiteralType::Boolean(false))
-                            != LiteralType::Boolean(true)
-                        {
-                            test_instance.fail(
-                                args[1]
-                                    .clone()
-                                    .unwrap_or(LiteralType::String("unknown".to_string()))
-                                    .to_string(),
-                            );
-                        } else {
-                            test_instance.success(
-                                args[1]
-                                    .clone()
-                                    .unwrap_or(LiteralType::String("unknown".to_string()))
-                                    .to_string(),
-                            );
-                        }
-                        LiteralType::Void
-                    }),
-                }),
+                Rc::new(Wrapper(Box::new(move |args: &[Option<LiteralType>]| {
+                    let test_instance = Rc::clone(&test_instance);
+                    let test_instance = test_instance.borrow_mut();
+                    if args.len() != 2 {
+                        raw("expected 2 arguments");
+                    }
+                    if args[0].clone().unwrap_or(LiteralType::Boolean(false))
+                        != LiteralType::Boolean(true)
+                    {
+                        test_instance.fail(
+                            args[1]
+                                .clone()
+                                .unwrap_or(LiteralType::String(Rc::new("unknown".to_string())))
+                                .to_string(),
+                        );
+                    } else {
+                        test_instance.success(
+                            args[1]
+                                .clone()
+                                .unwrap_or(LiteralType::String(Rc::new("unknown".to_string())))
+                                .to_string(),
+                        );
+                    }
+                    LiteralType::Void
+                }))),
             );
         }
     }
@@ -0,0 +1,93 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use crate::{
+    ast::{LiteralType, MapKey, MutWrapper, Token},
+    engine::{
+        native::NativeError,
+        value::{FromAbsurd, Mismatch},
+    },
+    std::{func, StdFunc},
+};
+
+/// map taken by the natives, it's shared with the source
+type Map = Rc<BTreeMap<MapKey, LiteralType>>;
+
+impl StdFunc {
+    pub fn load_literal_map(&mut self) {
+        self.load_insert(None);
+        self.load_remove(None);
+        self.load_contains_key(None);
+        self.load_keys(None);
+        self.load_values(None);
+        self.load_entries(None);
+    }
+
+    /// insert(map, key, value), returns the map with the value under the key,
+    /// changes `mut` maps in place
+    pub fn load_insert(&mut self, name: Option<Token>) {
+        let name = match name {
+            Some(n) => n.lexeme.clone(),
+            None => "insert".to_string(),
+        };
+        func(
+            name.as_str(),
+            3,
+            &mut self.env,
+            Rc::new(MutWrapper(Box::new(|map, args| {
+                let [key, value] = args else {
+                    return Err(NativeError::Arity);
+                };
+                let key = MapKey::from_absurd(key.clone().unwrap_or(LiteralType::Null))
+                    .map_err(NativeError::Mismatch)?;
+                let value = value.clone().unwrap_or(LiteralType::Null);
+                match map {
+                    LiteralType::Map(map) => {
+                        Rc::make_mut(map).insert(key, value);
+                        Ok(LiteralType::Map(Rc::clone(map)))
+                    }
+                    value => Err(NativeError::Mismatch(Mismatch::new::<Map>(value))),
+                }
+            }))),
+        );
+    }
+
+    /// remove(map, key), returns the map without the key
+    pub fn load_remove(&mut self, name: Option<Token>) {
+        self.native(name, "remove", |map: Map, key: MapKey| {
+            let mut map = Rc::unwrap_or_clone(map);
+            map.remove(&key);
+            LiteralType::Map(Rc::new(map))
+        });
+    }
+
+    /// contains(map, key)
+    pub fn load_contains_key(&mut self, name: Option<Token>) {
+        self.native(name, "contains", |map: Map, key: MapKey| {
+            map.contains_key(&key)
+        });
+    }
+
+    /// keys(map), in the order of the map
+    pub fn load_keys(&mut self, name: Option<Token>) {
+        self.native(name, "keys", |map: Map| {
+            LiteralType::Vec(Rc::new(map.keys().map(MapKey::to_literal).collect()))
+        });
+    }
+
+    /// values(map), in the order of their keys
+    pub fn load_values(&mut self, name: Option<Token>) {
+        self.native(name, "values", |map: Map| {
+            LiteralType::Vec(Rc::new(map.values().cloned().collect()))
+        });
+    }
+
+    /// entries(map), `(key, value)` tuples in the order of the map
+    pub fn load_entries(&mut self, name: Option<Token>) {
+        self.native(name, "entries", |map: Map| {
+            let entries = map
+                .iter()
+                .map(|(key, value)| (key.to_literal(), value.clone()));
+            entries.collect::<Vec<_>>()
+        });
+    }
+}
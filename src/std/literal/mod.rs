@@ -1,3 +1,4 @@
+pub mod map;
 pub mod number;
 pub mod string;
 pub mod vector;
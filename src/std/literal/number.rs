@@ -1,9 +1,10 @@
 use crate::{
-    ast::{LiteralType, Token, Wrapper},
-    errors::raw,
-    std::{func, StdFunc},
+    ast::{LiteralType, Token, TokenType, TokenType::*},
+    engine::value::{IntoAbsurd, Number},
+    errors::ErrorCode::{E0x310, E0x318},
+    interpreter::arith,
+    std::StdFunc,
 };
-use std::{process, rc::Rc};
 
 impl StdFunc {
     pub fn load_literal_number(&mut self) {
@@ -38,897 +39,191 @@ impl StdFunc {
         self.load_avg(None);
         self.load_to_degrees(None);
         self.load_to_radians(None);
+        self.load_to_int(None);
+        self.load_to_float(None);
     }
 
     pub fn load_sqr(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "sqr".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    if let LiteralType::Number(n) = args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n * n)
-                    } else {
-                        raw("sqr() expects a number");
-                        process::exit(1)
-                    }
-                }),
-            }),
-        );
+        self.native(name, "sqr", |n: Number| unary("sqr", Sqr, n));
     }
 
     pub fn load_add(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "add".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n + m),
-                                _ => {
-                                    raw("add() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("add() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "add", |n: Number, m: Number| {
+            binary("add", Plus, n, m)
+        });
     }
 
     pub fn load_sub(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "sub".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n - m),
-                                _ => {
-                                    raw("sub() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("sub() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "sub", |n: Number, m: Number| binary("sub", Min, n, m));
     }
 
     pub fn load_mult(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "mult".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n * m),
-                                _ => {
-                                    raw("mult() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("mult() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "mult", |n: Number, m: Number| {
+            binary("mult", Mul, n, m)
+        });
     }
 
     pub fn load_div(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "div".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n / m),
-                                _ => {
-                                    raw("div() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("div() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "div", |n: Number, m: Number| binary("div", Div, n, m));
     }
 
     pub fn load_rem(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "rem".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n % m),
-                                _ => {
-                                    raw("rem() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("rem() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "rem", |n: Number, m: Number| {
+            binary("rem", Prcnt, n, m)
+        });
     }
 
     pub fn load_sqrt(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "sqrt".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.sqrt()),
-                        _ => {
-                            raw("sqrt() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "sqrt", f64::sqrt);
     }
 
     pub fn load_cbrt(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "cbrt".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.cbrt()),
-                        _ => {
-                            raw("cbrt() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "cbrt", f64::cbrt);
     }
 
     pub fn load_pow(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "pow".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n.powf(m)),
-                                _ => {
-                                    raw("pow() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("pow() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "pow", |n: Number, m: Number| binary("pow", Sqr, n, m));
     }
 
     pub fn load_log(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "log".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n.log(m)),
-                                _ => {
-                                    raw("log() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("log() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "log", f64::log);
     }
 
     pub fn load_sin(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "sin".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.sin()),
-                        _ => {
-                            raw("sin() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "sin", f64::sin);
     }
 
     pub fn load_asin(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "asin".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.asin()),
-                        _ => {
-                            raw("asin() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "asin", f64::asin);
     }
 
     pub fn load_cos(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "cos".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.cos()),
-                        _ => {
-                            raw("cos() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "cos", f64::cos);
     }
 
     pub fn load_acos(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "acos".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.acos()),
-                        _ => {
-                            raw("acos() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "acos", f64::acos);
     }
 
     pub fn load_tan(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "tan".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.tan()),
-                        _ => {
-                            raw("tan() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "tan", f64::tan);
     }
 
     pub fn load_atan(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "atan".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.atan()),
-                        _ => {
-                            raw("atan() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "atan", f64::atan);
     }
 
     pub fn load_abs(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "abs".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.abs()),
-                        _ => {
-                            raw("abs() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "abs", |n: Number| match n {
+            Number::Int(n) => n
+                .checked_abs()
+                .map(Number::Int)
+                .ok_or("abs() overflows".to_string()),
+            Number::Float(n) => Ok(Number::Float(n.abs())),
+        });
     }
 
     pub fn load_floor(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "floor".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.floor()),
-                        _ => {
-                            raw("floor() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "floor", |n: Number| match n {
+            Number::Int(n) => Number::Int(n),
+            Number::Float(n) => Number::Float(n.floor()),
+        });
     }
 
     pub fn load_ceil(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "ceil".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.ceil()),
-                        _ => {
-                            raw("ceil() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "ceil", |n: Number| match n {
+            Number::Int(n) => Number::Int(n),
+            Number::Float(n) => Number::Float(n.ceil()),
+        });
     }
 
     pub fn load_round(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "round".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.round()),
-                        _ => {
-                            raw("round() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "round", |n: Number| match n {
+            Number::Int(n) => Number::Int(n),
+            Number::Float(n) => Number::Float(n.round()),
+        });
     }
 
     pub fn load_signum(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "signum".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.signum()),
-                        _ => {
-                            raw("signum() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "signum", |n: Number| match n {
+            Number::Int(n) => Number::Int(n.signum()),
+            Number::Float(n) => Number::Float(n.signum()),
+        });
     }
 
     pub fn load_hypot(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "hypot".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n.hypot(m)),
-                                _ => {
-                                    raw("hypot() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("hypot() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "hypot", f64::hypot);
     }
 
     pub fn load_exp(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "exp".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.exp()),
-                        _ => {
-                            raw("exp() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "exp", f64::exp);
     }
 
     pub fn load_exp2(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "exp2".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.exp2()),
-                        _ => {
-                            raw("exp2() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "exp2", f64::exp2);
     }
 
     pub fn load_exp_m1(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "exp_m1".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.exp_m1()),
-                        _ => {
-                            raw("exp_m1() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "exp_m1", f64::exp_m1);
     }
 
     pub fn load_ln(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "ln".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.ln()),
-                        _ => {
-                            raw("ln() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "ln", f64::ln);
     }
     pub fn load_max(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "max".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n.max(m)),
-                                _ => {
-                                    raw("max() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("max() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "max", |n: Number, m: Number| match (n, m) {
+            (Number::Int(n), Number::Int(m)) => Number::Int(n.max(m)),
+            (n, m) => Number::Float(n.to_float().max(m.to_float())),
+        });
     }
 
     pub fn load_min(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "min".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number(n.min(m)),
-                                _ => {
-                                    raw("min() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("min() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "min", |n: Number, m: Number| match (n, m) {
+            (Number::Int(n), Number::Int(m)) => Number::Int(n.min(m)),
+            (n, m) => Number::Float(n.to_float().min(m.to_float())),
+        });
     }
 
     pub fn load_avg(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "avg".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => {
-                            match args[1].clone().unwrap_or(LiteralType::Void) {
-                                LiteralType::Number(m) => LiteralType::Number((n + m) / 2.0),
-                                _ => {
-                                    raw("avg() expects numbers");
-                                    process::exit(1)
-                                }
-                            }
-                        }
-                        _ => {
-                            raw("avg() expects numbers");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "avg", |n: f64, m: f64| (n + m) / 2.0);
     }
 
     pub fn load_to_degrees(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "to_degrees".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.to_degrees()),
-                        _ => {
-                            raw("to_degrees() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "to_degrees", f64::to_degrees);
     }
 
     pub fn load_to_radians(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "to_radians".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::Number(n) => LiteralType::Number(n.to_radians()),
-                        _ => {
-                            raw("to_radians() expects a number");
-                            process::exit(1)
-                        }
-                    }
-                }),
-            }),
-        );
+        self.native(name, "to_radians", f64::to_radians);
+    }
+
+    pub fn load_to_int(&mut self, name: Option<Token>) {
+        // floats are truncated toward zero
+        self.native(name, "to_int", |n: Number| match n {
+            Number::Int(n) => Ok(n),
+            Number::Float(n) if n.is_finite() && n.abs() < i64::MAX as f64 => Ok(n as i64),
+            Number::Float(n) => Err(format!("to_int() can't convert {}", n)),
+        });
+    }
+
+    pub fn load_to_float(&mut self, name: Option<Token>) {
+        self.native(name, "to_float", |n: f64| n);
+    }
+}
+
+/// applies the operator of the interpreter to the number
+fn unary(name: &str, operator: TokenType, n: Number) -> Result<LiteralType, String> {
+    match arith::unary(&operator, &n.into_absurd()) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err(format!("{}() overflows", name)),
+    }
+}
+
+/// applies the operator of the interpreter to both numbers
+fn binary(name: &str, operator: TokenType, n: Number, m: Number) -> Result<LiteralType, String> {
+    match arith::binary(&n.into_absurd(), &operator, &m.into_absurd()) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(E0x310)) => Err(format!("{}() divides by zero", name)),
+        Some(Err(E0x318)) => Err(format!("{}() takes no negative powers of ints", name)),
+        _ => Err(format!("{}() overflows", name)),
     }
 }
@@ -1,9 +1,6 @@
-use std::rc::Rc;
-
 use crate::{
-    ast::{LiteralType, Token, Wrapper},
-    errors::raw,
-    std::{func, StdFunc},
+    ast::{LiteralType, Token},
+    std::StdFunc,
 };
 
 impl StdFunc {
@@ -31,23 +28,7 @@ impl StdFunc {
     }
 
     pub fn load_string(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "string".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    LiteralType::String(args[0].clone().unwrap_or(LiteralType::Void).to_string())
-                }),
-            }),
-        );
+        self.native(name, "string", |value: LiteralType| value.to_string());
     }
 
     // pub fn load_chars(&mut self, name: Option<Token>) {
@@ -71,188 +52,39 @@ impl StdFunc {
     // }
 
     pub fn load_chars_count(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "chars_count".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(s) => LiteralType::Number(s.chars().count() as f32),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "chars_count", |s: String| s.chars().count() as i64);
     }
 
     pub fn load_contains(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "contains".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match (
-                        &args[0].clone().unwrap_or(LiteralType::Void),
-                        &args[1].clone().unwrap_or(LiteralType::Void),
-                    ) {
-                        (LiteralType::String(a), LiteralType::String(b)) => {
-                            LiteralType::Boolean(a.contains(b))
-                        }
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "contains", |s: String, part: String| {
+            s.contains(&part)
+        });
     }
 
     pub fn load_find(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "find".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match (
-                        &args[0].clone().unwrap_or(LiteralType::Void),
-                        &args[1].clone().unwrap_or(LiteralType::Void),
-                    ) {
-                        (LiteralType::String(a), LiteralType::String(b)) => {
-                            if let Some(index) = a.find(b) {
-                                LiteralType::Number(index as f32)
-                            } else {
-                                LiteralType::Null
-                            }
-                        }
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "find", |s: String, part: String| {
+            s.find(&part).map(|index| index as i64)
+        });
     }
 
     pub fn load_ends_with(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "ends_with".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match (
-                        &args[0].clone().unwrap_or(LiteralType::Void),
-                        &args[1].clone().unwrap_or(LiteralType::Void),
-                    ) {
-                        (LiteralType::String(a), LiteralType::String(b)) => {
-                            LiteralType::Boolean(a.ends_with(b))
-                        }
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "ends_with", |s: String, end: String| {
+            s.ends_with(&end)
+        });
     }
 
     pub fn load_starts_with(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "starts_with".to_string(),
-        };
-        func(
-            name.as_str(),
-            2,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected 2 argument");
-                    }
-                    match (
-                        &args[0].clone().unwrap_or(LiteralType::Void),
-                        &args[1].clone().unwrap_or(LiteralType::Void),
-                    ) {
-                        (LiteralType::String(a), LiteralType::String(b)) => {
-                            LiteralType::Boolean(a.starts_with(b))
-                        }
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "starts_with", |s: String, start: String| {
+            s.starts_with(&start)
+        });
     }
 
     pub fn load_is_empty(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "is_empty".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(a) => LiteralType::Boolean(a.is_empty()),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "is_empty", |s: String| s.is_empty());
     }
 
     pub fn load_len(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "len".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(a) => LiteralType::Number(a.len() as f32),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "len", |s: String| s.len() as i64);
     }
 
     // pub fn load_lines(&mut self, name: Option<Token>) {
@@ -278,49 +110,11 @@ impl StdFunc {
     // }
 
     pub fn load_to_lowercase(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "to_lowercase".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(a) => LiteralType::String(a.to_lowercase()),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "to_lowercase", |s: String| s.to_lowercase());
     }
 
     pub fn load_to_uppercase(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "to_uppercase".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(a) => LiteralType::String(a.to_uppercase()),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "to_uppercase", |s: String| s.to_uppercase());
     }
 
     // pub fn load_parse(&mut self, name: Option<Token>) {
@@ -348,31 +142,9 @@ impl StdFunc {
     // }
 
     pub fn load_replace(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "replace".to_string(),
-        };
-        func(
-            name.as_str(),
-            3,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    match (
-                        &args[0].clone().unwrap_or(LiteralType::Void),
-                        &args[1].clone().unwrap_or(LiteralType::Void),
-                        &args[2].clone().unwrap_or(LiteralType::Void),
-                    ) {
-                        (
-                            LiteralType::String(a),
-                            LiteralType::String(b),
-                            LiteralType::String(c),
-                        ) => LiteralType::String(a.replace(b, c)),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "replace", |s: String, from: String, to: String| {
+            s.replace(&from, &to)
+        });
     }
 
     // pub fn load_split(&mut self, name: Option<Token>) {
@@ -449,71 +221,14 @@ impl StdFunc {
     // }
 
     pub fn load_trim(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "trim".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(a) => LiteralType::String(a.trim().to_string()),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "trim", |s: String| s.trim().to_string());
     }
 
     pub fn load_trim_start(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "trim_start".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(a) => LiteralType::String(a.trim_start().to_string()),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "trim_start", |s: String| s.trim_start().to_string());
     }
 
     pub fn load_trim_end(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "trim_end".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected an argument");
-                    }
-                    match &args[0].clone().unwrap_or(LiteralType::Void) {
-                        LiteralType::String(a) => LiteralType::String(a.trim_end().to_string()),
-                        _ => LiteralType::Null,
-                    }
-                }),
-            }),
-        );
+        self.native(name, "trim_end", |s: String| s.trim_end().to_string());
     }
 }
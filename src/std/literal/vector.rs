@@ -1,8 +1,8 @@
 use std::rc::Rc;
 
 use crate::{
-    ast::{LiteralType, Token, Wrapper},
-    errors::raw,
+    ast::{LiteralType, MutWrapper, Token},
+    engine::{native::NativeError, value::Mismatch},
     std::{func, StdFunc},
 };
 
@@ -22,7 +22,7 @@ impl StdFunc {
         self.load_get(None);
     }
 
-    /// push(vector, item);
+    /// push(vector, item); changes `mut` vectors in place
     pub fn load_push(&mut self, name: Option<Token>) {
         let name = match name {
             Some(n) => n.lexeme.clone(),
@@ -30,261 +30,75 @@ impl StdFunc {
         };
         func(
             name.as_str(),
-            1,
+            2,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected two argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let mut v: Vec<LiteralType> = v.clone();
-                                v.push(
-                                    args.get(1)
-                                        .unwrap()
-                                        .as_ref()
-                                        .unwrap_or(&LiteralType::Null)
-                                        .clone(),
-                                );
-                                return LiteralType::Vec(v);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
+            Rc::new(MutWrapper(Box::new(|vector, args| {
+                let [item] = args else {
+                    return Err(NativeError::Arity);
+                };
+                let v = vector_arg(vector)?;
+                Rc::make_mut(v).push(item.clone().unwrap_or(LiteralType::Null));
+                Ok(LiteralType::Vec(Rc::clone(v)))
+            }))),
         );
     }
 
     /// get(vector, index);
     pub fn load_get(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "get".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected two argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let v: Vec<LiteralType> = v.clone();
-                                let i = if let Some(Some(LiteralType::Number(n))) = args.get(1) {
-                                    *n as usize
-                                } else {
-                                    0
-                                };
-                                return v.get(i).unwrap_or(&LiteralType::Null).clone();
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        self.native(name, "get", |v: Rc<Vec<LiteralType>>, index: i64| {
+            v.get(usize::try_from(index).unwrap_or(usize::MAX)).cloned()
+        });
     }
 
     /// key(vector, item)
     pub fn load_key(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "key".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected two argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let v: Vec<LiteralType> = v.clone();
-                                let mut key = -1;
-                                v.iter().for_each(|p| {
-                                    if p != args
-                                        .get(1)
-                                        .unwrap()
-                                        .as_ref()
-                                        .unwrap_or(&LiteralType::Null)
-                                    {
-                                        key += 1;
-                                    }
-                                });
-                                return LiteralType::Number(key as f32);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        // number of the items which aren't the item, without one
+        self.native(name, "key", |v: Rc<Vec<LiteralType>>, item: LiteralType| {
+            v.iter().filter(|p| **p != item).count() as i64 - 1
+        });
     }
 
     /// has(vector, item)
     pub fn load_has(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "has".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected two argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let v: Vec<LiteralType> = v.clone();
-                                let mut bool = false;
-                                v.iter().for_each(|p| {
-                                    if p == args
-                                        .get(1)
-                                        .unwrap()
-                                        .as_ref()
-                                        .unwrap_or(&LiteralType::Null)
-                                    {
-                                        bool = true;
-                                    }
-                                });
-
-                                return LiteralType::Boolean(bool);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        self.native(name, "has", |v: Rc<Vec<LiteralType>>, item: LiteralType| {
+            v.contains(&item)
+        });
     }
 
     /// connect(array, array);
     pub fn load_connect(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "connect".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected two argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let mut v: Vec<LiteralType> = v.clone();
-                                let v2 = if let Some(Some(LiteralType::Vec(vc))) = args.get(1) {
-                                    vc.clone()
-                                } else {
-                                    vec![]
-                                };
-                                v2.iter().for_each(|c| v.push(c.clone()));
-                                return LiteralType::Vec(v);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
+        self.native(
+            name,
+            "connect",
+            |v: Rc<Vec<LiteralType>>, other: Rc<Vec<LiteralType>>| {
+                LiteralType::Vec(Rc::new([v.as_slice(), other.as_slice()].concat()))
+            },
         );
     }
 
     /// for_each(vector, |item| void)
     pub fn load_for_each(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "for_each".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected two argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let v: Vec<LiteralType> = v.clone();
-                                if let Some(Some(LiteralType::DeclrFunc(f))) = args.get(1) {
-                                    v.iter().for_each(|c| {
-                                        (*f.func).call(vec![Some(c.clone())]);
-                                    });
-                                };
-                                return LiteralType::Void;
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
+        // only natives are called, other values are skipped
+        self.native(
+            name,
+            "for_each",
+            |v: Rc<Vec<LiteralType>>, f: LiteralType| {
+                if let LiteralType::DeclrFunc(f) = f {
+                    v.iter().for_each(|c| {
+                        (*f.func).call(vec![Some(c.clone())]);
+                    });
+                }
+            },
         );
     }
 
     pub fn load_reverse(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "reverse".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected one argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let mut v: Vec<LiteralType> = v.clone();
-                                v.reverse();
-                                return LiteralType::Vec(v);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        self.native(name, "reverse", |v: Rc<Vec<LiteralType>>| {
+            LiteralType::Vec(Rc::new(v.iter().rev().cloned().collect()))
+        });
     }
 
+    /// pop(vector); changes `mut` vectors in place
     pub fn load_pop(&mut self, name: Option<Token>) {
         let name = match name {
             Some(n) => n.lexeme.clone(),
@@ -294,167 +108,40 @@ impl StdFunc {
             name.as_str(),
             1,
             &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected one argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let mut v: Vec<LiteralType> = v.clone();
-                                v.pop();
-                                return LiteralType::Vec(v);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
+            Rc::new(MutWrapper(Box::new(|vector, args| {
+                if !args.is_empty() {
+                    return Err(NativeError::Arity);
+                }
+                let v = vector_arg(vector)?;
+                Rc::make_mut(v).pop();
+                Ok(LiteralType::Vec(Rc::clone(v)))
+            }))),
         );
     }
 
     pub fn load_last(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "last".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected one argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let mut v: Vec<LiteralType> = v.clone();
-                                return v.pop().unwrap_or(LiteralType::Null);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        self.native(name, "last", |v: Rc<Vec<LiteralType>>| v.last().cloned());
     }
 
     pub fn load_first(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "first".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected one argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let mut v: Vec<LiteralType> = v.clone();
-                                v.reverse();
-                                return v.pop().unwrap_or(LiteralType::Null);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        self.native(name, "first", |v: Rc<Vec<LiteralType>>| v.first().cloned());
     }
 
     /// join(vec, string)
     pub fn load_join(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "join".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 2 {
-                        raw("expected two argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let v: Vec<LiteralType> = v.clone();
-                                let sep = if let Some(Some(LiteralType::String(s))) = args.get(1) {
-                                    s.clone()
-                                } else {
-                                    "".to_string()
-                                };
-
-                                let mut s = String::new();
-                                for (_, v) in v.iter().enumerate() {
-                                    s.push_str(&v.to_string());
-                                    sep.chars().for_each(|f| s.push(f))
-                                }
-
-                                return LiteralType::String(s);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        // the separator follows every item
+        self.native(name, "join", |v: Rc<Vec<LiteralType>>, sep: String| {
+            v.iter()
+                .map(|item| format!("{}{}", item, sep))
+                .collect::<String>()
+        });
     }
 
     /// to_string(vec)
     pub fn load_to_string(&mut self, name: Option<Token>) {
-        let name = match name {
-            Some(n) => n.lexeme.clone(),
-            None => "to_string".to_string(),
-        };
-        func(
-            name.as_str(),
-            1,
-            &mut self.env,
-            Rc::new(Wrapper {
-                0: Box::new(|args: &[Option<LiteralType>]| {
-                    if args.len() != 1 {
-                        raw("expected one argument");
-                    }
-                    match args.get(0) {
-                        Some(v) => match v.as_ref().unwrap_or(&LiteralType::Null) {
-                            LiteralType::Vec(v) => {
-                                let v: Vec<LiteralType> = v.clone();
-
-                                let mut s = String::new();
-                                for (_, v) in v.iter().enumerate() {
-                                    s.push_str(&v.to_string());
-                                }
-
-                                return LiteralType::String(s);
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    };
-                    LiteralType::Null
-                }),
-            }),
-        );
+        self.native(name, "to_string", |v: Rc<Vec<LiteralType>>| {
+            v.iter().map(|item| item.to_string()).collect::<String>()
+        });
     }
 
     //// map(vector, |item| void)
@@ -495,3 +182,13 @@ impl StdFunc {
     //     );
     // }
 }
+
+/// the vector changed by the native
+fn vector_arg(vector: &mut LiteralType) -> Result<&mut Rc<Vec<LiteralType>>, NativeError> {
+    match vector {
+        LiteralType::Vec(v) => Ok(v),
+        value => Err(NativeError::Mismatch(
+            Mismatch::new::<Rc<Vec<LiteralType>>>(value),
+        )),
+    }
+}
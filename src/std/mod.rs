@@ -4,16 +4,24 @@ pub mod literal;
 
 use crate::{
     ast::{DeclrFuncType, FuncValType, LiteralType, Token},
-    errors::raw,
+    engine::native::{Native, NativeFunc},
+    errors::ErrorCode::E0x416,
     interpreter::{
         env::{Env, FuncKind},
         Interpreter,
     },
 };
 
+/// natives changing their first argument, `mut` variables passed as it change in place
+pub const MUT_FUNCS: [(&str, &str); 3] = [
+    ("std::literal::vector", "push"),
+    ("std::literal::vector", "pop"),
+    ("std::literal::map", "insert"),
+];
+
 pub fn func(name: &str, arity: usize, env: &mut Rc<RefCell<Env>>, func: Rc<dyn FuncValType>) {
     let params = vec![];
-    env.borrow().define_pub_func(
+    env.borrow_mut().define_pub_func(
         name.to_string(),
         LiteralType::DeclrFunc(DeclrFuncType {
             name: name.to_string(),
@@ -28,6 +36,30 @@ pub fn func(name: &str, arity: usize, env: &mut Rc<RefCell<Env>>, func: Rc<dyn F
     )
 }
 
+/// same as `func`, but the function is defined in the module imported by `use name from source`
+pub fn mod_func(
+    source: &str,
+    name: &str,
+    arity: usize,
+    env: &Rc<RefCell<Env>>,
+    func: Rc<dyn FuncValType>,
+) {
+    env.borrow_mut().define_mod_func(
+        source.to_string(),
+        LiteralType::DeclrFunc(DeclrFuncType {
+            name: name.to_string(),
+            arity,
+            func,
+        }),
+        name.to_string(),
+        FuncKind {
+            params: vec![],
+            is_async: false,
+            is_pub: true,
+        },
+    )
+}
+
 #[derive(Clone)]
 pub struct StdFunc {
     env: Rc<RefCell<Env>>,
@@ -38,12 +70,32 @@ impl StdFunc {
     pub fn new(env: Rc<RefCell<Env>>, is_test: bool) -> Self {
         Self { env, is_test }
     }
+
+    /// defines the Rust function as the native named by the alias of `use` or by `name`,
+    /// arguments are checked by the types of its parameters
+    pub fn native<Args>(
+        &mut self,
+        alias: Option<Token>,
+        name: &str,
+        f: impl NativeFunc<Args> + 'static,
+    ) {
+        let name = alias.map_or(name.to_string(), |alias| alias.lexeme);
+        let native = Native::typed(&self.env, f);
+        func(&name, native.arity(), &mut self.env, Rc::new(native));
+    }
 }
 
+/// loaders of the std natives by their names, in the modules of the groups
+type StdMap<'a> = HashMap<
+    &'a str,
+    Vec<(
+        &'a str,
+        HashMap<&'a str, Box<dyn FnMut(&Option<Token>) + 'a>>,
+    )>,
+>;
+
 impl Interpreter {
-    pub fn std_map(
-        &mut self,
-    ) -> HashMap<&str, Vec<(&str, HashMap<&str, Box<dyn FnMut(&Option<Token>) + '_>>)>> {
+    pub fn std_map(&mut self) -> StdMap<'_> {
         let std = StdFunc::new(Rc::clone(&self.env), self.project.test);
         HashMap::from([
             (
@@ -156,6 +208,41 @@ impl Interpreter {
                             }) as Box<dyn FnMut(&Option<Token>)>,
                         )]),
                     ),
+                    (
+                        "task",
+                        HashMap::from([
+                            (
+                                "spawn",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_spawn(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "join_all",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_join_all(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "sleep",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_sleep(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                        ]),
+                    ),
                 ],
             ),
             (
@@ -474,6 +561,26 @@ impl Interpreter {
                                 })
                                     as Box<dyn FnMut(&Option<Token>)>,
                             ),
+                            (
+                                "to_int",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_to_int(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "to_float",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_to_float(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
                         ]),
                     ),
                     (
@@ -736,69 +843,116 @@ impl Interpreter {
                             ),
                         ]),
                     ),
+                    (
+                        "map",
+                        HashMap::from([
+                            (
+                                "insert",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_insert(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "remove",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_remove(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "contains",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_contains_key(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "keys",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_keys(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "values",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_values(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                            (
+                                "entries",
+                                Box::new({
+                                    let mut std = std.clone();
+                                    move |name2: &Option<Token>| {
+                                        std.load_entries(name2.clone());
+                                    }
+                                })
+                                    as Box<dyn FnMut(&Option<Token>)>,
+                            ),
+                        ]),
+                    ),
                 ],
             ),
         ])
     }
 
-    pub fn load_std(&mut self, src: String, names: Vec<(Token, Option<Token>)>) {
+    /// loads the std module of the `use` statement, unknown modules fail at `from` and
+    /// unknown names at the name
+    pub fn load_std(&mut self, src: String, from: &Token, names: Vec<(Token, Option<Token>)>) {
         let parts: Vec<&str> = src.split("::").collect();
         let mut std = StdFunc::new(Rc::clone(&self.env), self.project.test);
+        let error = self.env.borrow().err();
+        let unknown = |at: &Token| error.throw(E0x416, at.line, at.pos, vec![src.clone()]);
 
-        if parts[0] != "std" {
-            raw(format!("standard library '{src}' doesn't exist").as_str());
-            return;
+        let (Some(&"std"), Some(group), Some(module)) = (parts.first(), parts.get(1), parts.get(2))
+        else {
+            return unknown(from);
+        };
+        let mut std_map = self.std_map();
+        let Some((_, funcs)) = std_map
+            .get_mut(group)
+            .and_then(|modules| modules.iter_mut().find(|(name, _)| name == module))
+        else {
+            return unknown(from);
+        };
+        if parts.len() > 3 {
+            return unknown(from);
         }
 
-        if parts.len() < 2 {
-            raw("standard library module not specified");
-            return;
+        if names.is_empty() {
+            match (*group, *module) {
+                ("core", "io") => std.load_core_io(),
+                ("core", "test") => std.load_core_test(),
+                ("core", "task") => std.load_core_task(),
+                ("literal", "num") => std.load_literal_number(),
+                ("literal", "string") => std.load_literal_string(),
+                ("literal", "vector") => std.load_literal_vector(),
+                ("literal", "map") => std.load_literal_map(),
+                _ => unknown(from),
+            }
         }
-
-        match self.std_map().get_mut(parts[1]) {
-            Some(n) => match n.iter_mut().find(|(i, _)| parts[2] == *i) {
-                Some(m) => {
-                    if names.is_empty() {
-                        match parts[1] {
-                            "core" => match parts[2] {
-                                "io" => std.load_core_io(),
-                                "test" => std.load_core_test(),
-                                _ => raw(format!(
-                                    "std module '{}::{}' doesn't exist",
-                                    parts[1], parts[2]
-                                )
-                                .as_str()),
-                            },
-                            "literal" => match parts[2] {
-                                "num" => std.load_literal_number(),
-                                "string" => std.load_literal_string(),
-                                "vector" => std.load_literal_vector(),
-                                _ => raw(format!(
-                                    "std module '{}::{}' doesn't exist",
-                                    parts[1], parts[2]
-                                )
-                                .as_str()),
-                            },
-                            _ => raw(format!("std module '{}' doesn't exist", parts[1]).as_str()),
-                        }
-                    }
-                    names.iter().for_each(|(name1, name2)| {
-                        if let Some(l) = m.1.get_mut(&name1.lexeme.as_str()) {
-                            (l)(name2);
-                        } else {
-                            raw(format!(
-                                "std module '{}::{}::{}' doesn't exist",
-                                parts[1], parts[2], parts[3]
-                            )
-                            .as_str())
-                        }
-                    });
-                }
-                None => {
-                    raw(format!("std module '{}::{}' doesn't exist", parts[1], parts[2]).as_str())
-                }
-            },
-            None => raw(format!("std module '{}' doesn't exist", parts[1]).as_str()),
+        for (name, alias) in &names {
+            match funcs.get_mut(name.lexeme.as_str()) {
+                Some(load) => load(alias),
+                None => unknown(name),
+            }
         }
     }
 }
@@ -0,0 +1,139 @@
+// bytecode of compiled scripts and function bodies
+use crate::ast::{LiteralType, Statement, Token};
+use crate::interpreter::{env::ValueKind, expr::Expression};
+
+/// instruction of the VM, operands are indices into the tables of the `Function`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Op {
+    /// pushes the constant
+    Const(usize),
+    /// pushes the string of the `strings` table, values of its placeholders are on the stack
+    Interpolate(usize),
+    Pop,
+    /// pushes the value of the local
+    GetLocal(usize),
+    /// pops the value into the local, checking the declared type of the local
+    DefineLocal(usize),
+    /// sets the local to null, for variables declared without a value
+    DeclareLocal(usize),
+    /// assigns the top of the stack to the local, `assigns` holds the assignment
+    SetLocal {
+        slot: usize,
+        assign: usize,
+    },
+    /// pushes the value of the name of the `globals` table, looked up in the environment
+    GetGlobal(usize),
+    /// assigns the top of the stack to the name of the `assigns` table in the environment
+    SetGlobal(usize),
+    /// defines names of the `Var` statement of the fallback with the popped value
+    Define(usize),
+    /// applies the operator of the `tokens` table to the top of the stack
+    Unary(usize),
+    /// applies the operator of the `tokens` table to two values on the top of the stack
+    Binary(usize),
+    Jump(usize),
+    /// pops the condition and jumps if it's falsy
+    JumpIfFalse(usize),
+    /// collects items on the top of the stack
+    Vec(usize),
+    Tuple(usize),
+    /// collects key and value pairs of the map literal of the `exprs` table
+    Map(usize),
+    /// collects fields of the record literal of the `exprs` table
+    Record(usize),
+    /// creates the range of the `exprs` table from its step and bounds
+    Range(usize),
+    /// calls the value below the arguments, the call is in the `calls` table
+    Call(usize),
+    /// `type::item(payload)` or `type::func(args)`
+    EnumCall(usize),
+    /// `value.method(args)`, the receiver is below the arguments
+    MethodCall(usize),
+    /// awaits the popped future, `tokens` locates the `await`
+    Await(usize),
+    /// pops the value and starts iterating it, `tokens` locates the iterated expression
+    Iterate(usize),
+    /// binds the next item of the innermost iteration, jumps to `exit` after the last one
+    Next {
+        item: usize,
+        index: Option<usize>,
+        exit: usize,
+    },
+    /// drops the innermost iteration
+    EndIter,
+    /// leaves the loop of the `loops` table
+    Break(usize),
+    /// starts the next iteration of the loop of the `loops` table
+    Continue(usize),
+    /// returns the popped value from the function
+    Return,
+    /// end of the code, void functions end without `return`
+    End,
+    /// runs the statement of the fallback on the tree-walker
+    Exec(usize),
+    /// evaluates the expression of the fallback on the tree-walker, pushes its value
+    Eval(usize),
+}
+
+/// local of the compiled function, stored in the slot of the frame
+#[derive(Debug, PartialEq, Clone)]
+pub struct Local {
+    pub name: Token,
+    pub kind: ValueKind,
+}
+
+/// jump targets of the compiled loop
+#[derive(Debug, PartialEq, Clone)]
+pub struct Loop {
+    pub label: Option<String>,
+    pub break_at: usize,
+    pub continue_at: usize,
+    /// number of iterations running inside the loop, including its own
+    pub iters: usize,
+}
+
+/// node the compiler leaves to the tree-walker
+#[derive(Debug, PartialEq, Clone)]
+pub enum Node {
+    Stmt(Box<Statement>),
+    Expr(Box<Expression>),
+}
+
+/// part of the code running on the tree-walker, visible locals are defined in its
+/// environment and read back after it
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fallback {
+    pub node: Node,
+    /// names and slots of the visible locals, inner ones shadow outer ones
+    pub locals: Vec<(String, usize)>,
+    /// enclosing loops from the outermost one, `break` and `continue` of the node jump to them
+    pub loops: Vec<usize>,
+    /// the node runs in the environment of the script instead of a scope of locals
+    pub is_global: bool,
+}
+
+/// compiled code with its constant pool and tables
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Function {
+    pub code: Vec<Op>,
+    pub constants: Vec<LiteralType>,
+    /// operators and locations of errors
+    pub tokens: Vec<Token>,
+    /// locals indexed by their slots
+    pub locals: Vec<Local>,
+    /// names looked up in the environment, paired with ids of their expressions
+    pub globals: Vec<(Token, usize)>,
+    /// `Assign` expressions
+    pub assigns: Vec<Expression>,
+    /// `Call` expressions, paired with the slot of the receiver or the first argument
+    /// if it's a local
+    pub calls: Vec<(Expression, Option<usize>)>,
+    /// literals and ranges built from the values on the stack
+    pub exprs: Vec<Expression>,
+    /// text between placeholders of interpolated strings, and the text after the last one
+    pub strings: Vec<(Vec<String>, String)>,
+    pub loops: Vec<Loop>,
+    pub fallbacks: Vec<Fallback>,
+    /// the body is an expression, its value is returned
+    pub is_expr: bool,
+}
@@ -0,0 +1,608 @@
+// compiles resolved statements to the bytecode of the VM
+//
+// locals live in slots of the frame, names declared outside of the compiled code
+// are looked up in the environment. statements and expressions the compiler doesn't
+// handle run on the tree-walker, with the visible locals defined in its environment
+use super::chunk::{Fallback, Function, Local, Loop, Node, Op};
+use crate::ast::{CallType, FuncImpl, LiteralType, Statement, Token};
+use crate::bundler::try_parse_expr;
+use crate::errors::Error;
+use crate::interpreter::{
+    env::{FuncKind, ValueKind, VarKind},
+    expr::{placeholders, Expression},
+};
+
+pub struct Compiler {
+    function: Function,
+    /// names and slots of the locals of the open scopes, the outermost names of the script
+    /// are kept in the environment
+    scopes: Vec<Vec<(String, usize)>>,
+    /// loops around the compiled code, from the outermost one
+    loops: Vec<usize>,
+    /// number of iterations running at the compiled code
+    iters: usize,
+}
+
+impl Compiler {
+    fn new() -> Self {
+        Self {
+            function: Function::default(),
+            scopes: vec![],
+            loops: vec![],
+            iters: 0,
+        }
+    }
+
+    /// compiles the script, statements declaring anything but variables run on the tree-walker
+    pub fn script(stmts: &[Statement]) -> Function {
+        let mut compiler = Self::new();
+        for stmt in stmts {
+            if is_local(stmt) {
+                compiler.stmt(stmt);
+            } else {
+                compiler.exec(stmt);
+            }
+        }
+        compiler.emit(Op::End);
+        compiler.function
+    }
+
+    /// compiles the body of the function, `None` if the body can't keep its locals in slots
+    pub fn func(func: &FuncImpl) -> Option<Function> {
+        if !is_local(&func.body) {
+            return None;
+        }
+        let mut compiler = Self::new();
+        compiler.scopes.push(vec![]);
+        // parameters are defined as functions by the tree-walker, so they can't be assigned
+        let kind = ValueKind::Func(FuncKind {
+            params: func
+                .params
+                .iter()
+                .map(|(a, b)| (a.lexeme.clone(), b.lexeme.clone()))
+                .collect(),
+            is_async: func.is_async,
+            is_pub: func.is_pub,
+        });
+        for (name, _) in &func.params {
+            compiler.declare(name, kind.clone());
+        }
+        match func.body.as_ref() {
+            // the body shares the scope of the parameters
+            Statement::Block { stmts } => {
+                stmts.iter().for_each(|stmt| compiler.stmt(stmt));
+                compiler.emit(Op::End);
+            }
+            Statement::Expression { expr } => {
+                compiler.expr(expr);
+                compiler.emit(Op::Return);
+                compiler.function.is_expr = true;
+            }
+            _ => {
+                compiler.emit(Op::End);
+            }
+        }
+        Some(compiler.function)
+    }
+
+    /// bodies of branches and loops have their own scopes, as the resolver gives them
+    fn branch(&mut self, body: &Statement) {
+        if matches!(body, Statement::Block { .. }) {
+            return self.stmt(body);
+        }
+        self.scopes.push(vec![]);
+        self.stmt(body);
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Expression { expr } => {
+                self.expr(expr);
+                self.emit(Op::Pop);
+            }
+            Statement::Block { stmts } => {
+                self.scopes.push(vec![]);
+                stmts.iter().for_each(|stmt| self.stmt(stmt));
+                self.scopes.pop();
+            }
+            Statement::Var { .. } => self.var(stmt),
+            Statement::If {
+                cond,
+                body,
+                else_branch,
+            } => {
+                self.expr(cond);
+                let skip = self.emit(Op::JumpIfFalse(0));
+                self.branch(body);
+                match else_branch {
+                    Some(branch) => {
+                        let end = self.emit(Op::Jump(0));
+                        self.patch(skip);
+                        self.branch(branch);
+                        self.patch(end);
+                    }
+                    None => self.patch(skip),
+                }
+            }
+            Statement::While { cond, body, label } => {
+                let id = self.loop_start(label);
+                self.expr(cond);
+                let exit = self.emit(Op::JumpIfFalse(0));
+                self.branch(body);
+                self.emit(Op::Jump(self.function.loops[id].continue_at));
+                self.patch(exit);
+                self.loop_end(id);
+            }
+            Statement::For {
+                iterator,
+                index,
+                expr,
+                body,
+                label,
+            } => {
+                self.expr(expr);
+                let at = self.token(expr.token().unwrap_or(iterator).clone());
+                self.emit(Op::Iterate(at));
+                self.iters += 1;
+                let id = self.loop_start(label);
+                // the iterator and the index live until the end of the loop
+                self.scopes.push(vec![]);
+                let index = index.as_ref().map(|i| self.declare(i, iterated(i)));
+                let item = self.declare(iterator, iterated(iterator));
+                let next = self.emit(Op::Next {
+                    item,
+                    index,
+                    exit: 0,
+                });
+                self.stmt(body);
+                self.emit(Op::Jump(self.function.loops[id].continue_at));
+                self.scopes.pop();
+                self.patch(next);
+                self.loop_end(id);
+                self.emit(Op::EndIter);
+                self.iters -= 1;
+            }
+            Statement::Break { label, .. } => match self.target(label) {
+                Some(id) => {
+                    self.emit(Op::Break(id));
+                }
+                None => self.exec(stmt),
+            },
+            Statement::Continue { label, .. } => match self.target(label) {
+                Some(id) => {
+                    self.emit(Op::Continue(id));
+                }
+                None => self.exec(stmt),
+            },
+            Statement::Return { expr, .. } => {
+                self.expr(expr);
+                self.emit(Op::Return);
+            }
+            _ => self.exec(stmt),
+        }
+    }
+
+    fn var(&mut self, stmt: &Statement) {
+        let Statement::Var {
+            names,
+            destruct,
+            value_type,
+            value,
+            is_mut,
+            is_pub,
+            is_func,
+            ..
+        } = stmt
+        else {
+            return;
+        };
+        // variables of the script are defined in the environment
+        if self.scopes.is_empty() {
+            match value {
+                Some(value) => {
+                    self.expr(value);
+                    let id = self.fallback(Node::Stmt(Box::new(stmt.clone())));
+                    self.emit(Op::Define(id));
+                }
+                None => self.exec(stmt),
+            }
+            return;
+        }
+        let kind = ValueKind::Var(VarKind {
+            is_pub: *is_pub,
+            is_mut: *is_mut,
+            is_func: *is_func,
+            value_type: value_type.clone(),
+        });
+        match value {
+            Some(value) if destruct.is_none() && names.len() == 1 => {
+                self.expr(value);
+                let slot = self.declare(&names[0], kind);
+                self.emit(Op::DefineLocal(slot));
+            }
+            // destructuring is left to the tree-walker
+            Some(value) => {
+                self.expr(value);
+                names.iter().for_each(|name| {
+                    self.declare(name, kind.clone());
+                });
+                let id = self.fallback(Node::Stmt(Box::new(stmt.clone())));
+                self.emit(Op::Define(id));
+            }
+            None => {
+                for name in names {
+                    let slot = self.declare(name, kind.clone());
+                    self.emit(Op::DeclareLocal(slot));
+                }
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Value {
+                value: LiteralType::String(s),
+                ..
+            } if s.contains('{') => self.interpolate(expr, s),
+            Expression::Value { value, .. } => {
+                self.function.constants.push(value.clone());
+                self.emit(Op::Const(self.function.constants.len() - 1));
+            }
+            Expression::Var { id, name } => match self.resolve(&name.lexeme) {
+                Some(slot) => {
+                    self.emit(Op::GetLocal(slot));
+                }
+                None => {
+                    self.function.globals.push((name.clone(), *id));
+                    self.emit(Op::GetGlobal(self.function.globals.len() - 1));
+                }
+            },
+            Expression::Assign { name, value, .. } => {
+                self.expr(value);
+                self.function.assigns.push(expr.clone());
+                let assign = self.function.assigns.len() - 1;
+                match self.resolve(&name.lexeme) {
+                    Some(slot) => self.emit(Op::SetLocal { slot, assign }),
+                    None => self.emit(Op::SetGlobal(assign)),
+                };
+            }
+            Expression::Grouping { expression, .. } => self.expr(expression),
+            Expression::Unary { left, operator, .. } => {
+                self.expr(left);
+                let operator = self.token(operator.clone());
+                self.emit(Op::Unary(operator));
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                // both sides are evaluated, like on the tree-walker
+                self.expr(left);
+                self.expr(right);
+                let operator = self.token(operator.clone());
+                self.emit(Op::Binary(operator));
+            }
+            Expression::If {
+                cond,
+                body,
+                else_branch,
+                ..
+            } => {
+                self.expr(cond);
+                let skip = self.emit(Op::JumpIfFalse(0));
+                self.expr(body);
+                let end = self.emit(Op::Jump(0));
+                self.patch(skip);
+                match else_branch {
+                    Some(branch) => self.expr(branch),
+                    None => {
+                        self.function.constants.push(LiteralType::Null);
+                        self.emit(Op::Const(self.function.constants.len() - 1));
+                    }
+                }
+                self.patch(end);
+            }
+            Expression::Vec { items, .. } => {
+                items.iter().for_each(|item| self.expr(item));
+                self.emit(Op::Vec(items.len()));
+            }
+            Expression::Tuple { items, .. } => {
+                items.iter().for_each(|item| self.expr(item));
+                self.emit(Op::Tuple(items.len()));
+            }
+            Expression::Map { items, .. } => {
+                for (key, value) in items {
+                    self.expr(key);
+                    self.expr(value);
+                }
+                let id = self.literal(expr);
+                self.emit(Op::Map(id));
+            }
+            Expression::Record { fields, .. } => {
+                fields.iter().for_each(|(_, value)| self.expr(value));
+                let id = self.literal(expr);
+                self.emit(Op::Record(id));
+            }
+            // the step is evaluated first, like on the tree-walker
+            Expression::Range { lhs, rhs, step, .. } => {
+                if let Some(step) = step {
+                    self.expr(step);
+                }
+                self.expr(lhs);
+                self.expr(rhs);
+                let id = self.literal(expr);
+                self.emit(Op::Range(id));
+            }
+            Expression::Await { token, expr, .. } => {
+                self.expr(expr);
+                let at = self.token(token.clone());
+                self.emit(Op::Await(at));
+            }
+            Expression::Call { .. } => self.call(expr),
+            Expression::Match { .. } | Expression::Func { .. } => self.eval(expr),
+        }
+    }
+
+    fn call(&mut self, expr: &Expression) {
+        let Expression::Call {
+            name,
+            args,
+            call_type,
+            ..
+        } = expr
+        else {
+            return;
+        };
+        match (call_type, name.as_ref()) {
+            // the first argument is the name of the method
+            (CallType::Method, receiver) => {
+                self.expr(receiver);
+                args[1..].iter().for_each(|arg| self.expr(arg));
+                let slot = match receiver {
+                    Expression::Var { name, .. } => self.resolve(&name.lexeme),
+                    _ => None,
+                };
+                self.function.calls.push((expr.clone(), slot));
+                self.emit(Op::MethodCall(self.function.calls.len() - 1));
+            }
+            // the first argument is the name of the item or the function
+            (CallType::Enum, Expression::Var { name: parent, .. })
+                if self.resolve(&parent.lexeme).is_none() =>
+            {
+                args[1..].iter().for_each(|arg| self.expr(arg));
+                self.function.calls.push((expr.clone(), None));
+                self.emit(Op::EnumCall(self.function.calls.len() - 1));
+            }
+            (CallType::Enum, _) => self.eval(expr),
+            // natives changing their first argument change the local in place
+            (_, callee) => {
+                self.expr(callee);
+                args.iter().for_each(|arg| self.expr(arg));
+                let slot = match args.first() {
+                    Some(Expression::Var { name, .. }) => self.resolve(&name.lexeme),
+                    _ => None,
+                };
+                self.function.calls.push((expr.clone(), slot));
+                self.emit(Op::Call(self.function.calls.len() - 1));
+            }
+        }
+    }
+
+    /// placeholders are compiled with the string, the string is evaluated on the tree-walker
+    /// if any of them is broken, so it fails when it's reached
+    fn interpolate(&mut self, expr: &Expression, s: &str) {
+        let (parts, rest) = placeholders(s);
+        let exprs: Option<Vec<Expression>> = parts
+            .iter()
+            .map(|(_, src)| try_parse_expr(src, Error::new(src)))
+            .collect();
+        match exprs {
+            Some(exprs) => {
+                exprs.iter().for_each(|expr| self.expr(expr));
+                let texts = parts.into_iter().map(|(text, _)| text).collect();
+                self.function.strings.push((texts, rest));
+                self.emit(Op::Interpolate(self.function.strings.len() - 1));
+            }
+            None => self.eval(expr),
+        }
+    }
+
+    /// runs the statement on the tree-walker
+    fn exec(&mut self, stmt: &Statement) {
+        let id = self.fallback(Node::Stmt(Box::new(stmt.clone())));
+        self.emit(Op::Exec(id));
+    }
+
+    /// evaluates the expression on the tree-walker
+    fn eval(&mut self, expr: &Expression) {
+        let id = self.fallback(Node::Expr(Box::new(expr.clone())));
+        self.emit(Op::Eval(id));
+    }
+
+    fn fallback(&mut self, node: Node) -> usize {
+        let mut locals: Vec<(String, usize)> = vec![];
+        for (name, slot) in self.scopes.iter().flatten() {
+            locals.retain(|(n, _)| n != name);
+            locals.push((name.clone(), *slot));
+        }
+        self.function.fallbacks.push(Fallback {
+            node,
+            locals,
+            loops: self.loops.clone(),
+            is_global: self.scopes.is_empty(),
+        });
+        self.function.fallbacks.len() - 1
+    }
+
+    /// allocates the slot of the local in the innermost scope
+    fn declare(&mut self, name: &Token, kind: ValueKind) -> usize {
+        let slot = self.function.locals.len();
+        self.function.locals.push(Local {
+            name: name.clone(),
+            kind,
+        });
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push((name.lexeme.clone(), slot));
+        }
+        slot
+    }
+
+    /// slot of the local, from the innermost scope outwards
+    fn resolve(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(n, _)| n == name)
+            .map(|(_, slot)| *slot)
+    }
+
+    /// loop targeted by `break` or `continue`, the innermost one if it isn't labeled
+    fn target(&self, label: &Option<Token>) -> Option<usize> {
+        let loops = &self.function.loops;
+        match label {
+            Some(label) => self
+                .loops
+                .iter()
+                .rev()
+                .find(|id| loops[**id].label.as_ref() == Some(&label.lexeme))
+                .copied(),
+            None => self.loops.last().copied(),
+        }
+    }
+
+    /// opens the loop, iterations continue at the current instruction
+    fn loop_start(&mut self, label: &Option<Token>) -> usize {
+        self.function.loops.push(Loop {
+            label: label.as_ref().map(|l| l.lexeme.clone()),
+            break_at: 0,
+            continue_at: self.function.code.len(),
+            iters: self.iters,
+        });
+        self.loops.push(self.function.loops.len() - 1);
+        self.function.loops.len() - 1
+    }
+
+    /// closes the loop, `break` jumps to the current instruction
+    fn loop_end(&mut self, id: usize) {
+        self.function.loops[id].break_at = self.function.code.len();
+        self.loops.pop();
+    }
+
+    fn literal(&mut self, expr: &Expression) -> usize {
+        self.function.exprs.push(expr.clone());
+        self.function.exprs.len() - 1
+    }
+
+    fn token(&mut self, token: Token) -> usize {
+        self.function.tokens.push(token);
+        self.function.tokens.len() - 1
+    }
+
+    fn emit(&mut self, op: Op) -> usize {
+        self.function.code.push(op);
+        self.function.code.len() - 1
+    }
+
+    /// points the jump at the current instruction
+    fn patch(&mut self, at: usize) {
+        let here = self.function.code.len();
+        match &mut self.function.code[at] {
+            Op::Jump(target) | Op::JumpIfFalse(target) | Op::Next { exit: target, .. } => {
+                *target = here
+            }
+            _ => {}
+        }
+    }
+}
+
+/// iterators of `for` are immutable variables
+fn iterated(name: &Token) -> ValueKind {
+    ValueKind::Var(VarKind {
+        is_pub: false,
+        is_mut: false,
+        is_func: false,
+        value_type: name.clone(),
+    })
+}
+
+/// checks if the statement can keep its locals in slots: it declares nothing but private
+/// variables and creates no function, which would look its scope up by names
+pub fn is_local(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Expression { expr } | Statement::Return { expr, .. } => is_local_expr(expr),
+        Statement::Block { stmts } => stmts.iter().all(is_local),
+        Statement::Var {
+            value,
+            is_pub,
+            is_func,
+            ..
+        } => !is_pub && !is_func && value.iter().all(is_local_expr),
+        Statement::If {
+            cond,
+            body,
+            else_branch,
+        } => is_local_expr(cond) && is_local(body) && else_branch.iter().all(|b| is_local(b)),
+        Statement::While { cond, body, .. } => is_local_expr(cond) && is_local(body),
+        Statement::For { expr, body, .. } => is_local_expr(expr) && is_local(body),
+        Statement::Match { cond, cases } => {
+            is_local_expr(cond)
+                && cases
+                    .iter()
+                    .all(|c| c.guard.iter().all(is_local_expr) && is_local(&c.body))
+        }
+        Statement::Break { .. } | Statement::Continue { .. } => true,
+        Statement::Func { .. }
+        | Statement::Impl { .. }
+        | Statement::Enum { .. }
+        | Statement::Type { .. }
+        | Statement::Record { .. }
+        | Statement::Mod { .. }
+        | Statement::Use { .. }
+        | Statement::Sh { .. } => false,
+    }
+}
+
+fn is_local_expr(expr: &Expression) -> bool {
+    match expr {
+        Expression::Func { .. } => false,
+        Expression::Value { .. } | Expression::Var { .. } => true,
+        Expression::Assign { value, .. } => is_local_expr(value),
+        Expression::Grouping { expression, .. } => is_local_expr(expression),
+        Expression::Unary { left, .. } => is_local_expr(left),
+        Expression::Await { expr, .. } => is_local_expr(expr),
+        Expression::Binary { left, right, .. } => is_local_expr(left) && is_local_expr(right),
+        Expression::If {
+            cond,
+            body,
+            else_branch,
+            ..
+        } => {
+            is_local_expr(cond)
+                && is_local_expr(body)
+                && else_branch.iter().all(|b| is_local_expr(b))
+        }
+        Expression::Vec { items, .. } | Expression::Tuple { items, .. } => {
+            items.iter().all(is_local_expr)
+        }
+        Expression::Map { items, .. } => items
+            .iter()
+            .all(|(key, value)| is_local_expr(key) && is_local_expr(value)),
+        Expression::Record { fields, .. } => fields.iter().all(|(_, v)| is_local_expr(v)),
+        Expression::Call { name, args, .. } => {
+            is_local_expr(name) && args.iter().all(is_local_expr)
+        }
+        Expression::Match { cond, cases, .. } => {
+            is_local_expr(cond)
+                && cases
+                    .iter()
+                    .all(|c| c.guard.iter().all(is_local_expr) && is_local_expr(&c.body))
+        }
+        Expression::Range { lhs, rhs, step, .. } => {
+            is_local_expr(lhs) && is_local_expr(rhs) && step.iter().all(|s| is_local_expr(s))
+        }
+    }
+}
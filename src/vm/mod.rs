@@ -0,0 +1,541 @@
+// stack VM running the bytecode of the compiler, selected with `absurd run --vm`
+//
+// every call of a compiled function runs in its own frame with slots of its locals,
+// the value stack and running iterations. names outside of the function are looked up
+// in the environment the function was defined in
+pub mod chunk;
+pub mod compiler;
+use crate::ast::{FuncImpl, LiteralType, RangeType, Statement, Token};
+use crate::errors::ErrorCode::*;
+use crate::interpreter::{
+    call_func, check_arity, check_return,
+    env::{Env, ValueKind, ValueType, VarKind},
+    expr::{
+        assign, assigned, binary, call_native, find_method, item, native_arg, no_method, placeholder,
+        range_bound, range_step, static_func, unary, variable, Expression,
+    },
+    iterate, no_return, returned, task,
+    types::type_check,
+    ControlFlow, Interpreter, Iteration,
+};
+use chunk::{Function, Node, Op};
+use compiler::Compiler;
+use std::{cell::RefCell, collections::BTreeMap, mem, rc::Rc};
+
+struct Frame<'a> {
+    function: &'a Function,
+    /// runs the fallbacks, its environment is the environment of the frame
+    int: &'a mut Interpreter,
+    slots: Vec<LiteralType>,
+    /// kinds of the locals replaced by assignments, like the tree-walker replaces them
+    kinds: Vec<Option<ValueKind>>,
+    stack: Vec<LiteralType>,
+    iters: Vec<Iteration>,
+    ip: usize,
+}
+
+/// compiles the body of the function, `None` if it has to run on the tree-walker
+pub fn compile(func: &FuncImpl) -> Option<Rc<Function>> {
+    Compiler::func(func).map(Rc::new)
+}
+
+/// runs the script, its top-level names are defined in the environment of the interpreter
+pub fn run(int: &mut Interpreter, stmts: &[Statement]) {
+    let function = Compiler::script(stmts);
+    Frame::new(&function, int, vec![]).run();
+}
+
+/// runs the compiled body of the function with checked arguments
+pub fn call(
+    func: FuncImpl,
+    code: &Function,
+    args: Vec<LiteralType>,
+    env: &Rc<RefCell<Env>>,
+    call: &Token,
+) -> LiteralType {
+    let mut int = Interpreter::new_with_env(Rc::clone(&func.env), false, "", None, 1);
+    int.project.vm = true;
+    match Frame::new(code, &mut int, args).run() {
+        Some(value) if code.is_expr => check_return(&func, value, env, call),
+        Some(value) => returned(&func, value, env, call),
+        None => no_return(&func, env, call),
+    }
+}
+
+impl<'a> Frame<'a> {
+    /// arguments take the first slots
+    fn new(function: &'a Function, int: &'a mut Interpreter, args: Vec<LiteralType>) -> Self {
+        let mut slots = args;
+        slots.resize(function.locals.len(), LiteralType::Null);
+        Self {
+            function,
+            int,
+            slots,
+            kinds: vec![None; function.locals.len()],
+            stack: vec![],
+            iters: vec![],
+            ip: 0,
+        }
+    }
+
+    /// runs the code until `return` or its end, returns the value of `return`
+    fn run(&mut self) -> Option<LiteralType> {
+        let function = self.function;
+        loop {
+            let op = function.code[self.ip];
+            self.ip += 1;
+            match op {
+                Op::Const(id) => self.stack.push(function.constants[id].clone()),
+                Op::Interpolate(id) => {
+                    let (texts, rest) = &function.strings[id];
+                    let values = self.split(texts.len());
+                    let mut result = String::new();
+                    for (text, value) in texts.iter().zip(values) {
+                        result.push_str(text);
+                        result.push_str(&placeholder(value));
+                    }
+                    result.push_str(rest);
+                    self.stack.push(LiteralType::String(Rc::new(result)));
+                }
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::GetLocal(slot) => self.stack.push(self.slots[slot].clone()),
+                Op::DefineLocal(slot) => {
+                    let value = self.pop();
+                    let local = &function.locals[slot];
+                    if let ValueKind::Var(var) = &local.kind {
+                        if !type_check(&var.value_type, &value, &self.int.env) {
+                            let args = vec![var.value_type.lexeme.clone(), value.type_name()];
+                            let err = self.int.env.borrow().err();
+                            err.throw(E0x301, local.name.line, local.name.pos, args);
+                        }
+                    }
+                    self.slots[slot] = value;
+                    self.kinds[slot] = None;
+                }
+                Op::DeclareLocal(slot) => {
+                    self.slots[slot] = LiteralType::Null;
+                    self.kinds[slot] = None;
+                }
+                Op::SetLocal { slot, assign } => {
+                    let Expression::Assign { name, kind, .. } = &function.assigns[assign] else {
+                        unreachable!()
+                    };
+                    let value = self.pop();
+                    let local = self.kinds[slot]
+                        .as_ref()
+                        .unwrap_or(&function.locals[slot].kind);
+                    let value =
+                        assigned(name, &self.slots[slot], local, kind, value, &self.int.env);
+                    if self.kinds[slot].is_none() {
+                        self.kinds[slot] = Some(ValueKind::Var(VarKind {
+                            is_mut: true,
+                            is_pub: false,
+                            is_func: false,
+                            value_type: name.clone(),
+                        }));
+                    }
+                    self.slots[slot] = value.clone();
+                    self.stack.push(value);
+                }
+                Op::GetGlobal(id) => {
+                    let (name, expr) = &function.globals[id];
+                    self.stack.push(variable(name, *expr, &self.int.env));
+                }
+                Op::SetGlobal(id) => {
+                    let expr = &function.assigns[id];
+                    let Expression::Assign { name, kind, .. } = expr else {
+                        unreachable!()
+                    };
+                    let value = self.pop();
+                    let value = assign(name, expr.id(), kind, value, &self.int.env);
+                    self.stack.push(value);
+                }
+                Op::Define(id) => {
+                    let value = self.pop();
+                    self.fallback(id, |int, node| {
+                        if let Node::Stmt(stmt) = node {
+                            int.define(stmt, value);
+                        }
+                    });
+                }
+                Op::Unary(id) => {
+                    let value = self.pop();
+                    let value = unary(&function.tokens[id], value, &self.int.env);
+                    self.stack.push(value);
+                }
+                Op::Binary(id) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let value = binary(left, &function.tokens[id], right, &self.int.env);
+                    self.stack.push(value);
+                }
+                Op::Jump(target) => self.ip = target,
+                Op::JumpIfFalse(target) => {
+                    if !self.pop().is_truthy() {
+                        self.ip = target;
+                    }
+                }
+                Op::Vec(len) => {
+                    let items = self.split(len);
+                    self.stack.push(LiteralType::Vec(Rc::new(items)));
+                }
+                Op::Tuple(len) => {
+                    let items = self.split(len);
+                    self.stack.push(LiteralType::Tuple(items));
+                }
+                Op::Map(id) => {
+                    let value = self.map(&function.exprs[id]);
+                    self.stack.push(value);
+                }
+                Op::Record(id) => {
+                    let value = self.record(&function.exprs[id]);
+                    self.stack.push(value);
+                }
+                Op::Range(id) => {
+                    let value = self.range(&function.exprs[id]);
+                    self.stack.push(value);
+                }
+                Op::Call(id) => {
+                    let (expr, slot) = &function.calls[id];
+                    let value = self.call(expr, *slot);
+                    self.stack.push(value);
+                }
+                Op::EnumCall(id) => {
+                    let value = self.enum_call(&function.calls[id].0);
+                    self.stack.push(value);
+                }
+                Op::MethodCall(id) => {
+                    let (expr, slot) = &function.calls[id];
+                    let value = self.method_call(expr, *slot);
+                    self.stack.push(value);
+                }
+                Op::Await(id) => {
+                    let value = match self.pop() {
+                        LiteralType::Future(future) => {
+                            task::block_on(&future, &self.int.env, &function.tokens[id])
+                        }
+                        value => value,
+                    };
+                    self.stack.push(value);
+                }
+                Op::Iterate(id) => {
+                    let value = self.pop();
+                    let values = iterate(value, &function.tokens[id], &self.int.env);
+                    self.iters.push(values);
+                }
+                Op::Next { item, index, exit } => {
+                    match self.iters.last_mut().and_then(|values| values.next()) {
+                        Some((value, key)) => {
+                            if let Some(index) = index {
+                                self.slots[index] = key;
+                                self.kinds[index] = None;
+                            }
+                            self.slots[item] = value;
+                            self.kinds[item] = None;
+                        }
+                        None => self.ip = exit,
+                    }
+                }
+                Op::EndIter => {
+                    self.iters.pop();
+                }
+                Op::Break(id) => self.jump(id, true),
+                Op::Continue(id) => self.jump(id, false),
+                Op::Return => return Some(self.pop()),
+                Op::End => return None,
+                Op::Exec(id) => {
+                    let order = if function.fallbacks[id].is_global {
+                        0
+                    } else {
+                        1
+                    };
+                    let flow = self.fallback(id, |int, node| match node {
+                        Node::Stmt(stmt) => int.exec(vec![stmt], order),
+                        Node::Expr(_) => ControlFlow::Next,
+                    });
+                    // jumps out of the statement go to the compiled loops
+                    let loops = &function.fallbacks[id].loops;
+                    let target = |label: &Option<String>| {
+                        loops.iter().rev().copied().find(|id| match label {
+                            Some(label) => function.loops[*id].label.as_ref() == Some(label),
+                            None => true,
+                        })
+                    };
+                    match flow {
+                        ControlFlow::Next => {}
+                        ControlFlow::Return(value) => return Some(*value),
+                        ControlFlow::Break(label) => {
+                            if let Some(id) = target(&label) {
+                                self.jump(id, true);
+                            }
+                        }
+                        ControlFlow::Continue(label) => {
+                            if let Some(id) = target(&label) {
+                                self.jump(id, false);
+                            }
+                        }
+                    }
+                }
+                Op::Eval(id) => {
+                    let value = self.fallback(id, |int, node| match node {
+                        Node::Expr(expr) => expr.eval(Rc::clone(&int.env)),
+                        Node::Stmt(_) => LiteralType::Null,
+                    });
+                    self.stack.push(value);
+                }
+            }
+        }
+    }
+
+    fn pop(&mut self) -> LiteralType {
+        self.stack.pop().unwrap_or(LiteralType::Null)
+    }
+
+    /// pops `len` values in the order they were pushed
+    fn split(&mut self, len: usize) -> Vec<LiteralType> {
+        let at = self.stack.len() - len;
+        self.stack.split_off(at)
+    }
+
+    /// leaves the loop or starts its next iteration, iterations of inner loops are dropped
+    fn jump(&mut self, id: usize, is_break: bool) {
+        let target = &self.function.loops[id];
+        self.iters.truncate(target.iters);
+        self.ip = if is_break {
+            target.break_at
+        } else {
+            target.continue_at
+        };
+    }
+
+    /// runs the node of the fallback on the tree-walker, locals are defined in an enclosed
+    /// environment and read back after it
+    fn fallback<T>(&mut self, id: usize, run: impl FnOnce(&mut Interpreter, &Node) -> T) -> T {
+        let fallback = &self.function.fallbacks[id];
+        if fallback.is_global {
+            return run(self.int, &fallback.node);
+        }
+        let mut env = Env::enclose(&self.int.env);
+        for (name, slot) in &fallback.locals {
+            let kind = self.kinds[*slot]
+                .clone()
+                .unwrap_or_else(|| self.function.locals[*slot].kind.clone());
+            let value = self.slots[*slot].clone();
+            env.define(name.clone(), ValueType { value, kind });
+        }
+        let env = Rc::new(RefCell::new(env));
+        let prev = mem::replace(&mut self.int.env, Rc::clone(&env));
+        let result = run(self.int, &fallback.node);
+        self.int.env = prev;
+        for (name, slot) in &fallback.locals {
+            if let Some(ValueType { value, kind }) = env.borrow().local(name) {
+                self.slots[*slot] = value.clone();
+                self.kinds[*slot] =
+                    Some(kind.clone()).filter(|k| k != &self.function.locals[*slot].kind);
+            }
+        }
+        result
+    }
+
+    /// `name(args)`, `name[index]` or `name.field`, `slot` is the slot of the first argument
+    fn call(&mut self, expr: &Expression, slot: Option<usize>) -> LiteralType {
+        let Expression::Call { name, args, .. } = expr else {
+            unreachable!()
+        };
+        let values = self.split(args.len());
+        let callee = self.pop();
+        let env = Rc::clone(&self.int.env);
+        let call = name.token().cloned().unwrap_or(Token::null());
+        match callee {
+            LiteralType::Func(func) => {
+                check_arity(&func, values.len(), &call, &env);
+                call_func(func, values, env, &call, |i| args[i].token().cloned())
+            }
+            LiteralType::DeclrFunc(func) => match slot {
+                Some(slot) if func.func.is_mut() && self.is_mut(slot) => {
+                    // the local gives its value up like variables of the tree-walker do
+                    self.slots[slot] = LiteralType::Null;
+                    let err = env.borrow().err();
+                    let mut values = values.into_iter();
+                    let first = values.next().unwrap_or(LiteralType::Null);
+                    let mut value = native_arg(first, &call, &err);
+                    let rest = values.map(|v| Some(native_arg(v, &call, &err))).collect();
+                    let result = func.func.call_mut(&mut value, rest, &call, &err);
+                    self.slots[slot] = value;
+                    result
+                }
+                Some(_) => call_native(&func, None, values, &call, &env),
+                None => call_native(&func, args.first(), values, &call, &env),
+            },
+            // fields of records are named by the literals
+            LiteralType::Record(_) => match (args.first(), values.first()) {
+                (Some(Expression::Value { .. }), Some(key)) => item(&callee, key, &env),
+                _ => LiteralType::Null,
+            },
+            value @ (LiteralType::Vec(_)
+            | LiteralType::Tuple(_)
+            | LiteralType::Range(_)
+            | LiteralType::Map(_)
+            | LiteralType::String(_)) => match values.first() {
+                Some(key) => item(&value, key, &env),
+                None => LiteralType::Null,
+            },
+            _ => LiteralType::Null,
+        }
+    }
+
+    /// checks if the local is a `mut` variable
+    fn is_mut(&self, slot: usize) -> bool {
+        let kind = self.kinds[slot].as_ref().unwrap_or(&self.function.locals[slot].kind);
+        matches!(kind, ValueKind::Var(VarKind { is_mut: true, .. }))
+    }
+
+    /// `type::item(payload)` or `type::func(args)` of the `impl` block
+    fn enum_call(&mut self, expr: &Expression) -> LiteralType {
+        let Expression::Call { name, args, .. } = expr else {
+            unreachable!()
+        };
+        let Expression::Var { name: parent, id } = name.as_ref() else {
+            unreachable!()
+        };
+        let values = self.split(args.len() - 1);
+        let env = Rc::clone(&self.int.env);
+        let callee = variable(parent, *id, &env);
+        if let Some(func) = static_func(parent, &args[0], &env) {
+            check_arity(&func, values.len(), parent, &env);
+            return call_func(func, values, env, parent, |i| args[i + 1].token().cloned());
+        }
+        if !matches!(callee, LiteralType::Enum { .. }) {
+            let err = env.borrow().err();
+            let args = vec![parent.lexeme.clone(), args[0].to_string()];
+            err.fail(err.diagnostic(E0x317, parent.line, parent.pos, args))
+        }
+        let name = match &args[0] {
+            Expression::Var { name, .. } => name.clone(),
+            _ => Token::null(),
+        };
+        LiteralType::Enum {
+            parent: parent.clone(),
+            name,
+            value: values.into_iter().next().map(Box::new),
+        }
+    }
+
+    /// `value.method(args)`, the method is looked up by the declared type of the receiver
+    fn method_call(&mut self, expr: &Expression, slot: Option<usize>) -> LiteralType {
+        let Expression::Call { name, args, .. } = expr else {
+            unreachable!()
+        };
+        let Expression::Value {
+            value: LiteralType::String(method),
+            ..
+        } = &args[0]
+        else {
+            unreachable!()
+        };
+        let mut values = self.split(args.len() - 1);
+        let value = self.pop();
+        let env = Rc::clone(&self.int.env);
+        let kind = match (slot, name.as_ref()) {
+            (Some(slot), _) => Some(
+                self.kinds[slot]
+                    .clone()
+                    .unwrap_or_else(|| self.function.locals[slot].kind.clone()),
+            ),
+            (None, Expression::Var { name, id }) => {
+                env.borrow().get(name.lexeme.clone(), *id).map(|v| v.kind)
+            }
+            _ => None,
+        };
+        let declared = match kind {
+            Some(ValueKind::Var(var)) => Some(var.value_type.lexeme),
+            _ => None,
+        };
+        let call = name.token().cloned().unwrap_or(Token::null());
+        match find_method(&value, declared, method, &env) {
+            Some(func) => {
+                values.insert(0, value);
+                check_arity(&func, values.len(), &call, &env);
+                call_func(func, values, env, &call, |i| match i {
+                    0 => name.token().cloned(),
+                    i => args[i].token().cloned(),
+                })
+            }
+            None => no_method(&value, method.to_string(), &call, &env),
+        }
+    }
+
+    fn map(&mut self, expr: &Expression) -> LiteralType {
+        let Expression::Map { token, items, .. } = expr else {
+            unreachable!()
+        };
+        let values = self.split(items.len() * 2);
+        let mut map = BTreeMap::new();
+        let mut values = values.into_iter();
+        while let (Some(key), Some(value)) = (values.next(), values.next()) {
+            match key.to_key() {
+                Some(k) => map.insert(k, value),
+                None => {
+                    let err = self.int.env.borrow().err();
+                    let args = vec![key.type_name()];
+                    err.fail(err.diagnostic(E0x311, token.line, token.pos, args))
+                }
+            };
+        }
+        LiteralType::Map(Rc::new(map))
+    }
+
+    fn record(&mut self, expr: &Expression) -> LiteralType {
+        let Expression::Record { fields, .. } = expr else {
+            unreachable!()
+        };
+        let values = self.split(fields.len());
+        LiteralType::Record(Rc::new(
+            fields
+                .iter()
+                .zip(values)
+                .map(|((name, field), value)| {
+                    let value = Expression::Value {
+                        id: field.id(),
+                        token: Token::null(),
+                        value,
+                    };
+                    (name.clone(), value)
+                })
+                .collect(),
+        ))
+    }
+
+    fn range(&mut self, expr: &Expression) -> LiteralType {
+        let Expression::Range {
+            token,
+            lhs,
+            rhs,
+            step,
+            is_inclusive,
+            ..
+        } = expr
+        else {
+            unreachable!()
+        };
+        let rhs_value = self.pop();
+        let lhs_value = self.pop();
+        let env = Rc::clone(&self.int.env);
+        let step = match step {
+            Some(step) => {
+                let value = self.pop();
+                range_bound(step, value, token, &env)
+            }
+            None => 1,
+        };
+        let step = range_step(step, token, &env);
+        LiteralType::Range(RangeType {
+            lhs: range_bound(lhs, lhs_value, token, &env),
+            rhs: range_bound(rhs, rhs_value, token, &env),
+            step,
+            is_inclusive: *is_inclusive,
+        })
+    }
+}